@@ -0,0 +1,66 @@
+use crate::codes::read_code;
+use crate::output::OutputArgs;
+use clap::Args;
+use serde::Serialize;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// The files of the codes in the alist or JSON format.
+    #[arg(required = true)]
+    codes: Vec<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Serialize)]
+struct CodeAnalysis {
+    code: String,
+    n_bits: usize,
+    n_checks: usize,
+    rank: usize,
+    dimension: usize,
+    rate: f64,
+    min_bit_degree: usize,
+    max_bit_degree: usize,
+    mean_bit_degree: f64,
+    min_check_degree: usize,
+    max_check_degree: usize,
+    mean_check_degree: f64,
+    girth: Option<usize>,
+}
+
+pub fn run(args: AnalyzeArgs) -> Result<(), Box<dyn Error>> {
+    let analyses = args
+        .codes
+        .iter()
+        .map(|path| {
+            let code = read_code(path)?;
+            let rank = code.get_rank();
+            let bit_degrees = code.get_bit_degrees();
+            let check_degrees = code.get_check_degrees();
+            Ok(CodeAnalysis {
+                code: path.display().to_string(),
+                n_bits: code.get_n_bits(),
+                n_checks: code.get_n_checks(),
+                rank,
+                dimension: code.get_n_bits() - rank,
+                rate: (code.get_n_bits() - rank) as f64 / code.get_n_bits() as f64,
+                min_bit_degree: bit_degrees.iter().cloned().min().unwrap_or(0),
+                max_bit_degree: bit_degrees.iter().cloned().max().unwrap_or(0),
+                mean_bit_degree: mean(&bit_degrees),
+                min_check_degree: check_degrees.iter().cloned().min().unwrap_or(0),
+                max_check_degree: check_degrees.iter().cloned().max().unwrap_or(0),
+                mean_check_degree: mean(&check_degrees),
+                girth: code.get_girth(),
+            })
+        })
+        .collect::<Result<Vec<CodeAnalysis>, Box<dyn Error>>>()?;
+    args.output.write_records(&analyses)
+}
+
+fn mean(degrees: &[usize]) -> f64 {
+    degrees.iter().sum::<usize>() as f64 / degrees.len() as f64
+}
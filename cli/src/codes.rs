@@ -0,0 +1,571 @@
+use believer::{
+    CodeGenerator, PEGCodeGenerator, ParityCheckMatrix, Protograph, ProtographCodeGenerator,
+    QuasiCyclicCodeGenerator, RegularLDPCCodeGenerator, SpatiallyCoupledCodeGenerator,
+};
+use clap::Subcommand;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+// The largest `m` of codes with `2^m` bits. This keeps the codes in memory.
+const MAX_EXPONENT: usize = 20;
+
+/// A matrix of non negative integers given row by row.
+///
+/// On the command line, the rows are separated by `;` and the entries by `,`, for example
+/// `1,1,0;0,1,1`. In an experiment file, this is an array of rows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Rows(Vec<Vec<usize>>);
+
+impl FromStr for Rows {
+    type Err = String;
+
+    fn from_str(rows: &str) -> Result<Self, Self::Err> {
+        rows.split(';')
+            .map(|row| {
+                row.split(',')
+                    .map(|entry| entry.trim().parse::<usize>())
+                    .collect::<Result<Vec<usize>, _>>()
+            })
+            .collect::<Result<Vec<Vec<usize>>, _>>()
+            .map(Rows)
+            .map_err(|error| format!("invalid matrix: {}", error))
+    }
+}
+
+impl Rows {
+    fn check(&self) -> Result<(), String> {
+        match self.0.first() {
+            Some(first)
+                if !first.is_empty() && self.0.iter().all(|row| row.len() == first.len()) =>
+            {
+                Ok(())
+            }
+            _ => Err(String::from(
+                "the rows of a matrix must be non empty and of the same length",
+            )),
+        }
+    }
+
+    fn get_max_entry(&self) -> usize {
+        self.0.iter().flatten().cloned().max().unwrap_or(0)
+    }
+
+    // The checks of the matrix with a 1 for each non zero entry.
+    fn to_base_matrix(&self) -> ParityCheckMatrix {
+        let checks = self
+            .0
+            .iter()
+            .map(|row| (0..row.len()).filter(|bit| row[*bit] > 0).collect())
+            .collect();
+        ParityCheckMatrix::with_n_bits(self.0[0].len()).with_checks(checks)
+    }
+}
+
+/// The codes that can be generated from the command line or from an experiment file.
+///
+/// In an experiment file, the kind of code is given by the `type` field.
+#[derive(Clone, Debug, PartialEq, Subcommand, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum CodeSpec {
+    /// A repetition code.
+    Repetition {
+        #[arg(long)]
+        n_bits: usize,
+    },
+    /// A single parity check code.
+    SingleParity {
+        #[arg(long)]
+        n_bits: usize,
+    },
+    /// A Hamming code with `r` checks.
+    Hamming {
+        #[arg(long)]
+        r: usize,
+    },
+    /// An extended Hamming code with `r + 1` checks.
+    ExtendedHamming {
+        #[arg(long)]
+        r: usize,
+    },
+    /// The binary Golay code.
+    Golay,
+    /// The extended binary Golay code.
+    ExtendedGolay,
+    /// A Reed-Muller code of order `r` and length `2^m`.
+    ReedMuller {
+        #[arg(long)]
+        r: usize,
+        #[arg(long)]
+        m: usize,
+    },
+    /// A BCH code of length `2^m - 1`.
+    Bch {
+        #[arg(long)]
+        m: usize,
+        #[arg(long)]
+        designed_distance: usize,
+    },
+    /// A random regular LDPC code with `scale * check_degree` bits.
+    RegularLdpc {
+        #[arg(long)]
+        bit_degree: usize,
+        #[arg(long)]
+        check_degree: usize,
+        #[arg(long)]
+        scale: usize,
+        #[arg(long, default_value_t = 0)]
+        #[serde(default)]
+        minimal_girth: usize,
+    },
+    /// A progressive edge growth code where all bits have the same degree.
+    Peg {
+        #[arg(long)]
+        bit_degree: usize,
+        #[arg(long)]
+        n_bits: usize,
+        #[arg(long)]
+        n_checks: usize,
+        #[arg(long)]
+        #[serde(default)]
+        ace: bool,
+    },
+    /// A random quasi-cyclic code lifting each 1 of a base matrix to a circulant.
+    QuasiCyclic {
+        /// The base matrix of 0 and 1, for example `1,1,0;0,1,1`.
+        #[arg(long)]
+        base_matrix: Rows,
+        #[arg(long)]
+        lifting_size: usize,
+        #[arg(long, default_value_t = 0)]
+        #[serde(default)]
+        minimal_girth: usize,
+    },
+    /// A random code lifted from a protograph.
+    Protograph {
+        /// The number of edges between each check and each bit, for example `1,2;2,1`.
+        #[arg(long)]
+        multiplicities: Rows,
+        #[arg(long)]
+        lifting_size: usize,
+        /// Lifts with circulants avoiding cycles smaller than this girth when possible instead
+        /// of random permutations.
+        #[arg(long)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        minimal_girth: Option<usize>,
+    },
+    /// A terminated spatially coupled LDPC code.
+    SpatiallyCoupled {
+        #[arg(long)]
+        bit_degree: usize,
+        #[arg(long)]
+        check_degree: usize,
+        #[arg(long)]
+        coupling_length: usize,
+        #[arg(long)]
+        coupling_width: usize,
+        /// The number of bits at each position of a random component. This is the check degree
+        /// if not given.
+        #[arg(long, conflicts_with = "lifting_size")]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        n_bits_per_position: Option<usize>,
+        /// Uses a protograph component lifted with circulants of this size instead of a random
+        /// component.
+        #[arg(long)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lifting_size: Option<usize>,
+        /// The minimal girth of a protograph component.
+        #[arg(long, default_value_t = 0)]
+        #[serde(default)]
+        minimal_girth: usize,
+    },
+}
+
+impl CodeSpec {
+    /// Checks the parameters of `self` such that generating a code doesn't panic.
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            Self::Repetition { n_bits } | Self::SingleParity { n_bits } if *n_bits == 0 => {
+                Err(String::from("the code needs at least one bit"))
+            }
+            Self::Hamming { r } | Self::ExtendedHamming { r } if !(1..=MAX_EXPONENT).contains(r) => {
+                Err(format!("r must be between 1 and {}", MAX_EXPONENT))
+            }
+            Self::ReedMuller { r, m } if *m > MAX_EXPONENT || r > m => Err(format!(
+                "m must be at most {} and r must be at most m",
+                MAX_EXPONENT
+            )),
+            Self::Bch { m, .. } if !(2..=16).contains(m) => {
+                Err(String::from("m must be between 2 and 16"))
+            }
+            Self::RegularLdpc {
+                bit_degree,
+                check_degree,
+                scale,
+                ..
+            } if *bit_degree == 0 || *check_degree == 0 || *scale == 0 => {
+                Err(String::from("the degrees and the scale must be positive"))
+            }
+            Self::Peg {
+                bit_degree,
+                n_bits,
+                n_checks,
+                ..
+            } if *n_bits == 0 || *bit_degree == 0 || bit_degree > n_checks => Err(String::from(
+                "the number of bits and the bit degree must be positive and the bit degree must be \
+                 at most the number of checks",
+            )),
+            Self::QuasiCyclic {
+                base_matrix,
+                lifting_size,
+                ..
+            } => {
+                base_matrix.check()?;
+                if base_matrix.get_max_entry() > 1 {
+                    Err(String::from("the base matrix must only contain 0 and 1"))
+                } else if *lifting_size == 0 {
+                    Err(String::from("the lifting size must be positive"))
+                } else {
+                    Ok(())
+                }
+            }
+            Self::Protograph {
+                multiplicities,
+                lifting_size,
+                ..
+            } => {
+                multiplicities.check()?;
+                if *lifting_size == 0 || multiplicities.get_max_entry() > *lifting_size {
+                    Err(String::from(
+                        "the lifting size must be positive and at least every multiplicity",
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            Self::SpatiallyCoupled {
+                bit_degree,
+                check_degree,
+                coupling_length,
+                coupling_width,
+                n_bits_per_position,
+                lifting_size,
+                ..
+            } => {
+                if *bit_degree == 0
+                    || *check_degree == 0
+                    || *coupling_length == 0
+                    || *coupling_width == 0
+                {
+                    return Err(String::from(
+                        "the degrees and the coupling length and width must be positive",
+                    ));
+                }
+                if *coupling_width > *check_degree {
+                    return Err(String::from(
+                        "the coupling width must be at most the check degree",
+                    ));
+                }
+                match (n_bits_per_position, lifting_size) {
+                    (Some(_), Some(_)) => Err(String::from(
+                        "the component is either random or lifted from a protograph",
+                    )),
+                    (None, Some(lifting_size)) => {
+                        let max_multiplicity = SpatiallyCoupledCodeGenerator::new(
+                            *bit_degree,
+                            *check_degree,
+                            *coupling_length,
+                            *coupling_width,
+                        )
+                        .get_protograph()
+                        .get_max_multiplicity();
+                        if *lifting_size < max_multiplicity {
+                            Err(format!(
+                                "the lifting size must be at least {}",
+                                max_multiplicity
+                            ))
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    (n_bits_per_position, None) => {
+                        let n_bits = n_bits_per_position.unwrap_or(*check_degree);
+                        if n_bits == 0 || (n_bits * bit_degree) % check_degree != 0 {
+                            Err(String::from(
+                                "the number of bits per position must be positive and give an \
+                                 integer number of checks per position",
+                            ))
+                        } else {
+                            Ok(())
+                        }
+                    }
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generates a code with random number generator `rng` after checking the parameters of
+    /// `self`.
+    ///
+    /// Returns an error if the parameters are invalid or if no quasi-cyclic code satisfies the
+    /// minimal girth.
+    pub fn try_generate_with_rng<R: Rng>(&self, rng: &mut R) -> Result<ParityCheckMatrix, String> {
+        self.check()?;
+        match self {
+            Self::QuasiCyclic { .. } => self
+                .get_quasi_cyclic_generator()
+                .try_generate_with_rng(rng)
+                .ok_or_else(|| String::from("no quasi-cyclic code satisfies the minimal girth")),
+            _ => Ok(self.generate_with_rng(rng)),
+        }
+    }
+
+    // Panics if self is not a quasi-cyclic code.
+    fn get_quasi_cyclic_generator(&self) -> QuasiCyclicCodeGenerator {
+        match self {
+            Self::QuasiCyclic {
+                base_matrix,
+                lifting_size,
+                minimal_girth,
+            } => QuasiCyclicCodeGenerator::from_base_matrix(
+                base_matrix.to_base_matrix(),
+                *lifting_size,
+            )
+            .with_minimal_girth(*minimal_girth),
+            _ => panic!("not a quasi-cyclic code"),
+        }
+    }
+}
+
+/// Generating a code panics if the parameters are invalid. Use `check` or
+/// `try_generate_with_rng` first.
+impl CodeGenerator for CodeSpec {
+    fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        match self {
+            Self::Repetition { n_bits } => ParityCheckMatrix::repetition_code(*n_bits),
+            Self::SingleParity { n_bits } => ParityCheckMatrix::single_parity_code(*n_bits),
+            Self::Hamming { r } => ParityCheckMatrix::hamming_code(*r),
+            Self::ExtendedHamming { r } => ParityCheckMatrix::extended_hamming_code(*r),
+            Self::Golay => ParityCheckMatrix::golay_code(),
+            Self::ExtendedGolay => ParityCheckMatrix::extended_golay_code(),
+            Self::ReedMuller { r, m } => ParityCheckMatrix::reed_muller_code(*r, *m),
+            Self::Bch {
+                m,
+                designed_distance,
+            } => ParityCheckMatrix::bch_code(*m, *designed_distance),
+            Self::RegularLdpc {
+                bit_degree,
+                check_degree,
+                scale,
+                minimal_girth,
+            } => RegularLDPCCodeGenerator::new(*bit_degree, *check_degree, *scale, *minimal_girth)
+                .generate_with_rng(rng),
+            Self::Peg {
+                bit_degree,
+                n_bits,
+                n_checks,
+                ace,
+            } => {
+                let generator = PEGCodeGenerator::new(vec![*bit_degree; *n_bits], *n_checks);
+                if *ace {
+                    generator.with_ace_improvement().generate_with_rng(rng)
+                } else {
+                    generator.generate_with_rng(rng)
+                }
+            }
+            Self::QuasiCyclic { .. } => self.get_quasi_cyclic_generator().generate_with_rng(rng),
+            Self::Protograph {
+                multiplicities,
+                lifting_size,
+                minimal_girth,
+            } => {
+                let protograph = Protograph::from_multiplicities(multiplicities.0.clone());
+                let generator = ProtographCodeGenerator::from_protograph(protograph, *lifting_size);
+                match minimal_girth {
+                    Some(minimal_girth) => generator
+                        .with_circulant_lifting(*minimal_girth)
+                        .generate_with_rng(rng),
+                    None => generator.generate_with_rng(rng),
+                }
+            }
+            Self::SpatiallyCoupled {
+                bit_degree,
+                check_degree,
+                coupling_length,
+                coupling_width,
+                n_bits_per_position,
+                lifting_size,
+                minimal_girth,
+            } => {
+                let generator = SpatiallyCoupledCodeGenerator::new(
+                    *bit_degree,
+                    *check_degree,
+                    *coupling_length,
+                    *coupling_width,
+                );
+                match (lifting_size, n_bits_per_position) {
+                    (Some(lifting_size), _) => generator
+                        .with_protograph_component(*lifting_size, *minimal_girth)
+                        .generate_with_rng(rng),
+                    (None, Some(n_bits)) => generator
+                        .with_random_component(*n_bits)
+                        .generate_with_rng(rng),
+                    (None, None) => generator.generate_with_rng(rng),
+                }
+            }
+        }
+    }
+}
+
+// Files with a `json` extension hold JSON. The codes in other files are in the alist format.
+pub fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+pub fn read_code(path: &Path) -> Result<ParityCheckMatrix, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    if is_json(path) {
+        Ok(serde_json::from_str(&content)?)
+    } else {
+        Ok(ParityCheckMatrix::from_alist(&content)?)
+    }
+}
+
+/// Writes `code` to `path` or to the standard output in the alist format if there is no path.
+pub fn write_code(code: &ParityCheckMatrix, path: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    match path {
+        Some(path) if is_json(path) => fs::write(path, serde_json::to_string_pretty(code)?)?,
+        Some(path) => fs::write(path, code.to_alist())?,
+        None => io::stdout().write_all(code.to_alist().as_bytes())?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn codes_are_read_back_in_both_formats() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        for name in ["believer_cli_code.alist", "believer_cli_code.json"].iter() {
+            let path = std::env::temp_dir().join(name);
+            write_code(&code, Some(&path)).unwrap();
+            assert_eq!(read_code(&path).unwrap(), code);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn random_codes_are_reproducible() {
+        let spec = CodeSpec::RegularLdpc {
+            bit_degree: 3,
+            check_degree: 4,
+            scale: 4,
+            minimal_girth: 6,
+        };
+        let code = spec.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(5));
+        assert_eq!(code.get_n_bits(), 16);
+        assert_eq!(
+            code,
+            spec.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(5))
+        );
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        let mut rng = ChaCha8Rng::seed_from_u64(6);
+        let invalid = vec![
+            CodeSpec::Hamming { r: 0 },
+            CodeSpec::ReedMuller { r: 3, m: 2 },
+            CodeSpec::Bch {
+                m: 1,
+                designed_distance: 3,
+            },
+            CodeSpec::QuasiCyclic {
+                base_matrix: "1,1;1".parse().unwrap(),
+                lifting_size: 5,
+                minimal_girth: 0,
+            },
+            CodeSpec::Protograph {
+                multiplicities: "1,3".parse().unwrap(),
+                lifting_size: 2,
+                minimal_girth: None,
+            },
+            CodeSpec::SpatiallyCoupled {
+                bit_degree: 3,
+                check_degree: 6,
+                coupling_length: 10,
+                coupling_width: 3,
+                n_bits_per_position: Some(3),
+                lifting_size: None,
+                minimal_girth: 0,
+            },
+            CodeSpec::SpatiallyCoupled {
+                bit_degree: 2,
+                check_degree: 2,
+                coupling_length: 10,
+                coupling_width: 3,
+                n_bits_per_position: None,
+                lifting_size: Some(3),
+                minimal_girth: 0,
+            },
+        ];
+        for spec in invalid {
+            assert!(spec.try_generate_with_rng(&mut rng).is_err());
+        }
+    }
+
+    #[test]
+    fn impossible_quasi_cyclic_girth_is_an_error() {
+        let spec = CodeSpec::QuasiCyclic {
+            base_matrix: "1,1;1,1".parse().unwrap(),
+            lifting_size: 1,
+            minimal_girth: 6,
+        };
+        assert!(spec
+            .try_generate_with_rng(&mut ChaCha8Rng::seed_from_u64(7))
+            .is_err());
+    }
+
+    #[test]
+    fn lifted_codes_have_the_expected_sizes() {
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+        let quasi_cyclic = CodeSpec::QuasiCyclic {
+            base_matrix: "1,1,1,0;0,1,1,1".parse().unwrap(),
+            lifting_size: 7,
+            minimal_girth: 6,
+        };
+        let code = quasi_cyclic.try_generate_with_rng(&mut rng).unwrap();
+        assert_eq!((code.get_n_bits(), code.get_n_checks()), (28, 14));
+
+        let protograph = CodeSpec::Protograph {
+            multiplicities: "1,2;2,1".parse().unwrap(),
+            lifting_size: 5,
+            minimal_girth: Some(6),
+        };
+        let code = protograph.try_generate_with_rng(&mut rng).unwrap();
+        assert_eq!((code.get_n_bits(), code.get_n_checks()), (10, 10));
+
+        let spatially_coupled = CodeSpec::SpatiallyCoupled {
+            bit_degree: 3,
+            check_degree: 6,
+            coupling_length: 10,
+            coupling_width: 3,
+            n_bits_per_position: Some(12),
+            lifting_size: None,
+            minimal_girth: 0,
+        };
+        let code = spatially_coupled.try_generate_with_rng(&mut rng).unwrap();
+        assert_eq!((code.get_n_bits(), code.get_n_checks()), (120, 72));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::codes::{is_json, read_code, CodeSpec};
+use crate::output::{write_records, OutputFormat};
+use crate::simulate::{simulate_sweep, Channel, DecoderKind, StoppingArgs, SweepPoint};
+use believer::ParityCheckMatrix;
+use clap::Args;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// The experiment file in the TOML format, or in the JSON format with a `json` extension.
+    experiment: PathBuf,
+
+    /// Replaces the output file of the experiment.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+}
+
+/// A simulation of a decoder over a grid of probabilities described by a file.
+///
+/// The relative paths are relative to the directory of the experiment file. The same file
+/// always gives the same results unless a time budget is used.
+///
+/// ```toml
+/// seed = 7
+/// channel = "binary-symmetric"
+/// probabilities = { start = 0.01, stop = 0.05, n_points = 5 }
+/// output = "results.csv"
+///
+/// [code]
+/// generator = { type = "regular-ldpc", bit_degree = 3, check_degree = 6, scale = 20 }
+/// seed = 11
+///
+/// [decoder]
+/// kind = "gallager"
+/// max_iterations = 20
+///
+/// [stopping]
+/// n_failures = 100
+/// max_iterations = 1000000
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    code: CodeSource,
+    channel: Channel,
+    decoder: DecoderConfig,
+    probabilities: ProbabilityGrid,
+    #[serde(default)]
+    stopping: StoppingArgs,
+    seed: u64,
+    output: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format: Option<OutputFormat>,
+}
+
+/// The code of an experiment read from a file or generated from a seed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CodeSource {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    generator: Option<CodeSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DecoderConfig {
+    kind: DecoderKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_iterations: Option<usize>,
+}
+
+/// The probabilities of an experiment, either listed or evenly spaced from `start` to `stop`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ProbabilityGrid {
+    Values(Vec<f64>),
+    Linear {
+        start: f64,
+        stop: f64,
+        n_points: usize,
+    },
+}
+
+impl ProbabilityGrid {
+    fn get_probabilities(&self) -> Vec<f64> {
+        match self {
+            Self::Values(values) => values.clone(),
+            Self::Linear {
+                start,
+                stop,
+                n_points,
+            } => (0..*n_points)
+                .map(|point| {
+                    if *n_points == 1 {
+                        *start
+                    } else {
+                        start + (stop - start) * point as f64 / (n_points - 1) as f64
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The output of an experiment with the version of believer and the experiment itself.
+///
+/// A code read from a file is also included since the file may change after the experiment.
+#[derive(Serialize)]
+struct StampedResults<'a> {
+    believer_version: &'a str,
+    experiment: &'a Experiment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'a ParityCheckMatrix>,
+    results: &'a [SweepPoint],
+}
+
+impl Experiment {
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        if is_json(path) {
+            Ok(serde_json::from_str(&content)?)
+        } else {
+            Ok(toml::from_str(&content)?)
+        }
+    }
+
+    /// Runs the experiment for `code` given by [`get_code`](#method.get_code).
+    ///
+    /// Returns an error if the decoder doesn't work on the code or at some probabilities, or if
+    /// the stopping criteria are invalid.
+    pub fn run(&self, code: &ParityCheckMatrix) -> Result<Vec<SweepPoint>, Box<dyn Error>> {
+        let probabilities = self.probabilities.get_probabilities();
+        if probabilities.is_empty() {
+            return Err("the experiment has no probability".into());
+        }
+        Ok(simulate_sweep(
+            code,
+            self.channel,
+            self.decoder.kind,
+            self.decoder.max_iterations,
+            &probabilities,
+            &self.stopping.get_criteria()?,
+            self.seed,
+        )?)
+    }
+
+    /// Returns the code of the experiment resolving a relative file from `directory`.
+    pub fn get_code(&self, directory: &Path) -> Result<ParityCheckMatrix, Box<dyn Error>> {
+        match (&self.code.file, &self.code.generator, self.code.seed) {
+            (Some(file), None, None) => read_code(&directory.join(file)),
+            (None, Some(generator), Some(seed)) => {
+                Ok(generator.try_generate_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))?)
+            }
+            (None, Some(_), None) => Err("a generated code needs a seed".into()),
+            _ => Err("the code needs either a file or a generator with a seed".into()),
+        }
+    }
+
+    /// Writes the `results` of `self` for `code` to `path` stamped with the experiment and the
+    /// version of believer. The code is also in the stamp if it was read from a file.
+    ///
+    /// The JSON output is an object with the version, the experiment, the code and the results.
+    /// The CSV output starts with the version, the experiment and the code as JSON on lines
+    /// starting with `#`.
+    pub fn write_results(
+        &self,
+        code: &ParityCheckMatrix,
+        results: &[SweepPoint],
+        path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        let code = self.code.file.as_ref().map(|_| code);
+        let mut writer = BufWriter::new(File::create(path)?);
+        match self.get_format(path) {
+            OutputFormat::Json => {
+                let stamped = StampedResults {
+                    believer_version: env!("CARGO_PKG_VERSION"),
+                    experiment: self,
+                    code,
+                    results,
+                };
+                serde_json::to_writer_pretty(&mut writer, &stamped)?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Csv => {
+                writeln!(writer, "# believer_version: {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(writer, "# experiment: {}", serde_json::to_string(self)?)?;
+                if let Some(code) = code {
+                    writeln!(writer, "# code: {}", serde_json::to_string(code)?)?;
+                }
+                write_records(results, OutputFormat::Csv, &mut writer)?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    // The format is given by the experiment or by the extension of the output.
+    fn get_format(&self, path: &Path) -> OutputFormat {
+        self.format.unwrap_or_else(|| {
+            if is_json(path) {
+                OutputFormat::Json
+            } else {
+                OutputFormat::Csv
+            }
+        })
+    }
+}
+
+pub fn run(args: RunArgs) -> Result<(), Box<dyn Error>> {
+    let experiment = Experiment::from_file(&args.experiment)?;
+    let directory = args.experiment.parent().unwrap_or_else(|| Path::new(""));
+    let code = experiment.get_code(directory)?;
+    let results = experiment.run(&code)?;
+    let output = args
+        .output
+        .unwrap_or_else(|| directory.join(&experiment.output));
+    experiment.write_results(&code, &results, &output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXPERIMENT: &str = r#"
+        seed = 7
+        channel = "erasure"
+        probabilities = { start = 0.1, stop = 0.3, n_points = 3 }
+        output = "results.json"
+
+        [code]
+        generator = { type = "regular-ldpc", bit_degree = 3, check_degree = 4, scale = 4 }
+        seed = 11
+
+        [decoder]
+        kind = "erasure"
+
+        [stopping]
+        max_iterations = 400
+        batch_size = 100
+    "#;
+
+    fn run_experiment(experiment: &Experiment) -> Result<Vec<SweepPoint>, Box<dyn Error>> {
+        let code = experiment.get_code(Path::new(""))?;
+        experiment.run(&code)
+    }
+
+    #[test]
+    fn experiment_is_reproducible() {
+        let experiment: Experiment = toml::from_str(EXPERIMENT).unwrap();
+        let results = run_experiment(&experiment).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!((results[1].probability - 0.2).abs() < 1e-12);
+        assert!(results.iter().all(|point| point.n_iterations == 400));
+        assert_eq!(results, run_experiment(&experiment).unwrap());
+    }
+
+    #[test]
+    fn toml_and_json_experiments_are_the_same() {
+        let experiment: Experiment = toml::from_str(EXPERIMENT).unwrap();
+        let json = serde_json::to_string(&experiment).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Experiment>(&json).unwrap(),
+            experiment
+        );
+    }
+
+    #[test]
+    fn output_is_stamped_with_the_experiment_and_the_version() {
+        let experiment: Experiment = toml::from_str(EXPERIMENT).unwrap();
+        let code = experiment.get_code(Path::new("")).unwrap();
+        let results = experiment.run(&code).unwrap();
+        let path = std::env::temp_dir().join("believer_experiment_results.json");
+        experiment.write_results(&code, &results, &path).unwrap();
+        let output: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(output["believer_version"], env!("CARGO_PKG_VERSION"));
+        let stamped: Experiment = serde_json::from_value(output["experiment"].clone()).unwrap();
+        assert_eq!(stamped, experiment);
+        assert!(output.get("code").is_none());
+        assert_eq!(output["results"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn output_is_stamped_with_the_code_of_a_file() {
+        let directory = std::env::temp_dir();
+        let code = ParityCheckMatrix::hamming_code(3);
+        fs::write(
+            directory.join("believer_experiment_code.alist"),
+            code.to_alist(),
+        )
+        .unwrap();
+        let experiment = EXPERIMENT.replace(
+            "generator = { type = \"regular-ldpc\", bit_degree = 3, check_degree = 4, scale = 4 }\n        seed = 11",
+            "file = \"believer_experiment_code.alist\"",
+        );
+        let experiment: Experiment = toml::from_str(&experiment).unwrap();
+        let read_code = experiment.get_code(&directory).unwrap();
+        assert_eq!(read_code, code);
+        let results = experiment.run(&read_code).unwrap();
+        let path = directory.join("believer_experiment_file_results.csv");
+        experiment
+            .write_results(&read_code, &results, &path)
+            .unwrap();
+        let output = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(directory.join("believer_experiment_code.alist")).unwrap();
+
+        let stamp = output
+            .lines()
+            .find_map(|line| line.strip_prefix("# code: "))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<ParityCheckMatrix>(stamp).unwrap(),
+            code
+        );
+    }
+
+    #[test]
+    fn invalid_probabilities_and_criteria_are_errors() {
+        let syndrome_table = EXPERIMENT
+            .replace("channel = \"erasure\"", "channel = \"binary-symmetric\"")
+            .replace("kind = \"erasure\"", "kind = \"syndrome-table\"");
+        let invalid = vec![
+            syndrome_table.replace("stop = 0.3", "stop = 0.7"),
+            EXPERIMENT.replace("batch_size = 100", "batch_size = 0"),
+            EXPERIMENT.replace("batch_size = 100", "relative_precision = -0.1"),
+            EXPERIMENT.replace("batch_size = 100", "time_budget = -1.0"),
+        ];
+        assert!(run_experiment(&toml::from_str(&syndrome_table).unwrap()).is_ok());
+        for experiment in invalid {
+            let experiment: Experiment = toml::from_str(&experiment).unwrap();
+            assert!(run_experiment(&experiment).is_err());
+        }
+    }
+
+    #[test]
+    fn generated_code_needs_a_seed() {
+        let experiment = EXPERIMENT.replace("seed = 11", "");
+        let experiment: Experiment = toml::from_str(&experiment).unwrap();
+        assert!(experiment.get_code(Path::new("")).is_err());
+    }
+}
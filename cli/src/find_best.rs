@@ -0,0 +1,122 @@
+use crate::codes::{write_code, CodeSpec};
+use crate::output::OutputArgs;
+use believer::{BestCodeFinderUsingErasure, SimulationMonitor, SimulationProgress};
+use clap::Args;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct FindBestArgs {
+    /// The number of generated codes.
+    #[arg(long)]
+    n_codes: usize,
+
+    /// The erasure probability used to compare the codes.
+    #[arg(long, default_value_t = 0.5)]
+    erasure_prob: f64,
+
+    /// Simulates each code for this number of iterations.
+    #[arg(
+        long,
+        required_unless_present = "n_events",
+        conflicts_with = "n_events"
+    )]
+    n_iterations: Option<usize>,
+
+    /// Simulates each code until this number of successes and failures.
+    #[arg(long)]
+    n_events: Option<usize>,
+
+    /// The seed of the search. A random seed is used if not given.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// The file of the best code. A `json` extension gives the JSON format and any other
+    /// extension gives the alist format.
+    #[arg(long)]
+    code_output: Option<PathBuf>,
+
+    /// Doesn't report the progress on the standard error.
+    #[arg(long)]
+    quiet: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(subcommand)]
+    generator: CodeSpec,
+}
+
+#[derive(Serialize)]
+struct SearchResult {
+    n_codes: usize,
+    erasure_prob: f64,
+    found_a_code: bool,
+    n_iterations: u64,
+    n_failures: u64,
+    failure_rate: f64,
+    seed: u64,
+}
+
+pub fn run(args: FindBestArgs) -> Result<(), Box<dyn Error>> {
+    if !(0.0..=1.0).contains(&args.erasure_prob) {
+        return Err(format!("invalid erasure probability {}", args.erasure_prob).into());
+    }
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    // A first code is generated from a copy of the generator to report invalid parameters, or
+    // a quasi-cyclic code without the minimal girth, instead of panicking during the search.
+    args.generator.try_generate_with_rng(&mut rng.clone())?;
+    let finder = BestCodeFinderUsingErasure::from_code_generator(&args.generator)
+        .with_erasure_prob(args.erasure_prob)
+        .among_n_codes(args.n_codes);
+    let quiet = args.quiet;
+    let mut monitor = SimulationMonitor::new().with_observer(|progress: &SimulationProgress| {
+        if !quiet {
+            report(progress)
+        }
+    });
+    let (code, result) = match (args.n_iterations, args.n_events) {
+        (Some(n_iterations), _) => finder
+            .find_best_code_simulating_n_iterations_with_monitor_and_rng(
+                n_iterations,
+                &mut monitor,
+                &mut rng,
+            ),
+        (None, Some(n_events)) => finder.find_best_code_simulating_n_events_with_monitor_and_rng(
+            n_events,
+            &mut monitor,
+            &mut rng,
+        ),
+        (None, None) => return Err("either the number of iterations or of events is needed".into()),
+    };
+    if let (Some(code), Some(path)) = (&code, &args.code_output) {
+        write_code(code, Some(path))?;
+    }
+    args.output.write_records(&[SearchResult {
+        n_codes: args.n_codes,
+        erasure_prob: args.erasure_prob,
+        found_a_code: code.is_some(),
+        n_iterations: result.get_n_iterations(),
+        n_failures: result.get_n_failures(),
+        failure_rate: result.get_failure_rate(),
+        seed,
+    }])
+}
+
+fn report(progress: &SimulationProgress) {
+    let eta = progress.get_eta().map_or_else(
+        || String::from("?"),
+        |eta| format!("{:.0}s", eta.as_secs_f64()),
+    );
+    eprintln!(
+        "{}/{} codes, best failure rate {:.3e}, eta {}",
+        progress.get_n_completed(),
+        progress.get_n_total(),
+        progress.get_result().get_failure_rate(),
+        eta
+    );
+}
@@ -0,0 +1,32 @@
+use crate::codes::{write_code, CodeSpec};
+use clap::Args;
+use rand::{thread_rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// The seed of random codes. A random seed is used if not given.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// The file of the code. A `json` extension gives the JSON format and any other extension
+    /// gives the alist format. The code is written in the alist format to the standard output
+    /// if not given.
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    #[command(subcommand)]
+    code: CodeSpec,
+}
+
+pub fn run(args: GenerateArgs) -> Result<(), Box<dyn Error>> {
+    let code = match args.seed {
+        Some(seed) => args
+            .code
+            .try_generate_with_rng(&mut ChaCha8Rng::seed_from_u64(seed)),
+        None => args.code.try_generate_with_rng(&mut thread_rng()),
+    }?;
+    write_code(&code, args.output.as_deref())
+}
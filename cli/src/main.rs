@@ -0,0 +1,52 @@
+//! The command-line interface of believer.
+//!
+//! Each subcommand reads its codes in the alist or JSON format and writes its results as CSV or
+//! JSON to the standard output or to a file.
+
+use clap::{Parser, Subcommand};
+use std::error::Error;
+use std::process;
+
+mod analyze;
+mod codes;
+mod experiment;
+mod find_best;
+mod generate;
+mod output;
+mod simulate;
+
+/// Simulates the performance of error correcting codes.
+#[derive(Parser)]
+#[command(name = "believer", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates a code and writes it in the alist or JSON format.
+    Generate(generate::GenerateArgs),
+    /// Reports the rank, the degrees and the girth of codes.
+    Analyze(analyze::AnalyzeArgs),
+    /// Simulates a decoder over a sweep of error probabilities.
+    Simulate(simulate::SimulateArgs),
+    /// Finds the best of many generated codes on the erasure channel.
+    FindBest(find_best::FindBestArgs),
+    /// Runs the experiment described by a TOML or JSON file.
+    Run(experiment::RunArgs),
+}
+
+fn main() {
+    let outcome: Result<(), Box<dyn Error>> = match Cli::parse().command {
+        Command::Generate(args) => generate::run(args),
+        Command::Analyze(args) => analyze::run(args),
+        Command::Simulate(args) => simulate::run(args),
+        Command::FindBest(args) => find_best::run(args),
+        Command::Run(args) => experiment::run(args),
+    };
+    if let Err(error) = outcome {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
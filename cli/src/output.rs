@@ -0,0 +1,92 @@
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Args)]
+pub struct OutputArgs {
+    /// The format of the results.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub format: OutputFormat,
+
+    /// The file of the results. The results are written to the standard output if not given.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl OutputArgs {
+    pub fn write_records<T: Serialize>(&self, records: &[T]) -> Result<(), Box<dyn Error>> {
+        let writer: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(File::create(path)?),
+            None => Box::new(io::stdout()),
+        };
+        write_records(records, self.format, writer)
+    }
+}
+
+pub fn write_records<T: Serialize, W: Write>(
+    records: &[T],
+    format: OutputFormat,
+    mut writer: W,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, records)?;
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Record {
+        probability: f64,
+        n_failures: u64,
+    }
+
+    #[test]
+    fn records_as_csv_and_json() {
+        let records = [
+            Record {
+                probability: 0.1,
+                n_failures: 3,
+            },
+            Record {
+                probability: 0.2,
+                n_failures: 7,
+            },
+        ];
+
+        let mut csv = Vec::new();
+        write_records(&records, OutputFormat::Csv, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "probability,n_failures\n0.1,3\n0.2,7\n"
+        );
+
+        let mut json = Vec::new();
+        write_records(&records, OutputFormat::Json, &mut json).unwrap();
+        let values: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(values[1]["n_failures"], 7);
+    }
+}
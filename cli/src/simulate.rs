@@ -0,0 +1,511 @@
+use crate::codes::read_code;
+use crate::output::OutputArgs;
+use believer::*;
+use clap::{Args, ValueEnum};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// The number of iterations per probability if no stopping criterion is given.
+const DEFAULT_MAX_ITERATIONS: u64 = 10_000;
+
+const DEFAULT_BATCH_SIZE: u64 = 1000;
+
+// The limits of the maximum likelihood decoders on the number of checks and the rank of a code
+// for a syndrome table and on the dimension of a code for the codeword enumeration.
+const MAX_SYNDROME_TABLE_CHECKS: usize = 64;
+const MAX_SYNDROME_TABLE_RANK: usize = 24;
+const MAX_ENUMERATION_DIMENSION: usize = 24;
+
+/// The channels through which the all-zero codeword is sent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Channel {
+    /// Each bit is erased with the given probability.
+    Erasure,
+    /// Each bit is flipped with the given probability.
+    BinarySymmetric,
+    /// Each bit gets a gaussian noise of the given standard deviation.
+    Gaussian,
+}
+
+/// The decoders that can be simulated from the command line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecoderKind {
+    Erasure,
+    BitFlipping,
+    Gallager,
+    SyndromeTable,
+    CodewordEnumeration,
+    Matching,
+    WeightedBitFlipping,
+}
+
+impl DecoderKind {
+    fn get_channel(self) -> Channel {
+        match self {
+            Self::Erasure => Channel::Erasure,
+            Self::WeightedBitFlipping => Channel::Gaussian,
+            _ => Channel::BinarySymmetric,
+        }
+    }
+
+    // The decoders assuming that errors are unlikely only accept probabilities up to 0.5.
+    fn check_probability(self, prob: f64) -> Result<(), String> {
+        let is_valid = match self {
+            Self::Erasure | Self::BitFlipping | Self::Gallager => (0.0..=1.0).contains(&prob),
+            Self::SyndromeTable | Self::CodewordEnumeration | Self::Matching => {
+                (0.0..=0.5).contains(&prob)
+            }
+            Self::WeightedBitFlipping => prob >= 0.0 && prob.is_finite(),
+        };
+        if is_valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "invalid probability {} for the {:?} decoder",
+                prob, self
+            ))
+        }
+    }
+
+    fn is_iterative(self) -> bool {
+        match self {
+            Self::BitFlipping | Self::Gallager | Self::WeightedBitFlipping => true,
+            Self::Erasure | Self::SyndromeTable | Self::CodewordEnumeration | Self::Matching => {
+                false
+            }
+        }
+    }
+
+    // Checks that the decoder can be built for the code without panicking.
+    fn check_code(self, code: &ParityCheckMatrix) -> Result<(), String> {
+        match self {
+            Self::Matching
+                if code
+                    .get_transposed_matrix()
+                    .checks_iter()
+                    .any(|checks| checks.get_n_bits() > 2) =>
+            {
+                Err(String::from(
+                    "the Matching decoder needs every bit to be in at most 2 checks",
+                ))
+            }
+            Self::SyndromeTable
+                if code.get_n_checks() > MAX_SYNDROME_TABLE_CHECKS
+                    || code.get_rank() > MAX_SYNDROME_TABLE_RANK =>
+            {
+                Err(format!(
+                    "the SyndromeTable decoder needs at most {} checks and a rank of at most {}",
+                    MAX_SYNDROME_TABLE_CHECKS, MAX_SYNDROME_TABLE_RANK
+                ))
+            }
+            Self::CodewordEnumeration
+                if code.get_n_bits() - code.get_rank() > MAX_ENUMERATION_DIMENSION =>
+            {
+                Err(format!(
+                    "the CodewordEnumeration decoder needs a dimension of at most {}",
+                    MAX_ENUMERATION_DIMENSION
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The stopping criteria of the simulation at each probability.
+///
+/// In an experiment file, the missing criteria are not used and the batch size is 1000.
+#[derive(Clone, Debug, PartialEq, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StoppingArgs {
+    /// Stops after this number of iterations. This is 10000 if neither this nor a time budget
+    /// is given.
+    #[arg(long)]
+    max_iterations: Option<u64>,
+
+    /// Stops after this number of failures.
+    #[arg(long)]
+    n_failures: Option<u64>,
+
+    /// Stops when the standard error relative to the failure rate is at most this precision.
+    #[arg(long)]
+    relative_precision: Option<f64>,
+
+    /// Stops after this number of seconds for each probability. The results are then not
+    /// reproducible.
+    #[arg(long)]
+    time_budget: Option<f64>,
+
+    /// The number of iterations between two checks of the criteria.
+    #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+    batch_size: u64,
+}
+
+impl Default for StoppingArgs {
+    fn default() -> Self {
+        Self {
+            max_iterations: None,
+            n_failures: None,
+            relative_precision: None,
+            time_budget: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl StoppingArgs {
+    pub fn get_criteria(&self) -> Result<StoppingCriteria, String> {
+        if self.batch_size == 0 {
+            return Err(String::from("the batch size must be positive"));
+        }
+        let mut criteria = StoppingCriteria::new().with_batch_size(self.batch_size);
+        if let Some(max_iterations) = self.max_iterations {
+            criteria = criteria.with_max_iterations(max_iterations);
+        }
+        if let Some(n_failures) = self.n_failures {
+            criteria = criteria.with_n_failures(n_failures);
+        }
+        if let Some(relative_precision) = self.relative_precision {
+            if relative_precision.is_nan() || relative_precision <= 0.0 {
+                return Err(String::from("the relative precision must be positive"));
+            }
+            criteria = criteria.with_relative_precision(relative_precision);
+        }
+        if let Some(time_budget) = self.time_budget {
+            let time_budget = Duration::try_from_secs_f64(time_budget)
+                .map_err(|_| "the time budget must be a non negative number of seconds")?;
+            criteria = criteria.with_time_budget(time_budget);
+        }
+        if !criteria.is_bounded() {
+            criteria = criteria.with_max_iterations(DEFAULT_MAX_ITERATIONS);
+        }
+        Ok(criteria)
+    }
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    /// The file of the code in the alist or JSON format.
+    code: PathBuf,
+
+    /// The channel of the errors.
+    #[arg(long, value_enum)]
+    channel: Channel,
+
+    /// The decoder. It must match the channel.
+    #[arg(long, value_enum)]
+    decoder: DecoderKind,
+
+    /// The maximal number of iterations of the bit-flipping decoders. It is an error to give it
+    /// for the other decoders.
+    #[arg(long)]
+    decoder_iterations: Option<usize>,
+
+    /// The comma-separated error probabilities, or noise standard deviations for the gaussian
+    /// channel.
+    #[arg(long, required = true, value_delimiter = ',')]
+    probabilities: Vec<f64>,
+
+    #[command(flatten)]
+    stopping: StoppingArgs,
+
+    /// The seed of the simulation. A random seed is used if not given.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// The result of a simulation at a single probability.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SweepPoint {
+    pub probability: f64,
+    pub n_iterations: u64,
+    pub n_failures: u64,
+    pub failure_rate: f64,
+    pub standard_error: f64,
+    pub seed: u64,
+}
+
+pub fn run(args: SimulateArgs) -> Result<(), Box<dyn Error>> {
+    let code = read_code(&args.code)?;
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let points = simulate_sweep(
+        &code,
+        args.channel,
+        args.decoder,
+        args.decoder_iterations,
+        &args.probabilities,
+        &args.stopping.get_criteria()?,
+        seed,
+    )?;
+    args.output.write_records(&points)
+}
+
+/// Simulates `decoder` for `code` at each probability.
+///
+/// Returns an error if the decoder doesn't work on the channel, on the code or at one of the
+/// probabilities.
+///
+/// The simulation of the `i`-th probability uses the `i`-th stream of a generator seeded with
+/// `seed`. Thus, the result of a probability doesn't depend on the other probabilities.
+pub fn simulate_sweep(
+    code: &ParityCheckMatrix,
+    channel: Channel,
+    decoder: DecoderKind,
+    decoder_iterations: Option<usize>,
+    probabilities: &[f64],
+    criteria: &StoppingCriteria,
+    seed: u64,
+) -> Result<Vec<SweepPoint>, String> {
+    if decoder.get_channel() != channel {
+        return Err(format!(
+            "the {:?} decoder doesn't work on the {:?} channel",
+            decoder, channel
+        ));
+    }
+    if decoder_iterations.is_some() && !decoder.is_iterative() {
+        return Err(format!(
+            "the {:?} decoder doesn't have a maximal number of iterations",
+            decoder
+        ));
+    }
+    decoder.check_code(code)?;
+    for prob in probabilities {
+        decoder.check_probability(*prob)?;
+    }
+    let sweep = Sweep {
+        probabilities,
+        criteria,
+        seed,
+    };
+    let points = match decoder {
+        DecoderKind::Erasure => {
+            sweep.run(|prob| ErasureDecoder::with_prob(prob).for_code(code.clone()))
+        }
+        DecoderKind::BitFlipping => sweep.run(|prob| {
+            let decoder = BitFlippingDecoder::with_prob(prob);
+            match decoder_iterations {
+                Some(iterations) => decoder.with_max_iterations(iterations),
+                None => decoder,
+            }
+            .for_code(code.clone())
+        }),
+        DecoderKind::Gallager => sweep.run(|prob| {
+            let decoder = GallagerDecoder::with_prob(prob);
+            match decoder_iterations {
+                Some(iterations) => decoder.with_max_iterations(iterations),
+                None => decoder,
+            }
+            .for_code(code.clone())
+        }),
+        DecoderKind::SyndromeTable => {
+            sweep.run(|prob| SyndromeTableDecoder::with_prob(prob).for_code(code.clone()))
+        }
+        DecoderKind::CodewordEnumeration => {
+            sweep.run(|prob| CodewordEnumerationDecoder::with_prob(prob).for_code(code.clone()))
+        }
+        DecoderKind::Matching => {
+            sweep.run(|prob| MatchingDecoder::with_prob(prob).for_code(code.clone()))
+        }
+        DecoderKind::WeightedBitFlipping => sweep.run(|noise| {
+            let decoder = WeightedBitFlippingDecoder::with_noise(noise);
+            match decoder_iterations {
+                Some(iterations) => decoder.with_max_iterations(iterations),
+                None => decoder,
+            }
+            .for_code(code.clone())
+        }),
+    };
+    Ok(points)
+}
+
+struct Sweep<'a> {
+    probabilities: &'a [f64],
+    criteria: &'a StoppingCriteria,
+    seed: u64,
+}
+
+impl<'a> Sweep<'a> {
+    fn run<D: Decoder, F: Fn(f64) -> D>(&self, decoder_builder: F) -> Vec<SweepPoint> {
+        self.probabilities
+            .iter()
+            .enumerate()
+            .map(|(index, prob)| {
+                let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+                rng.set_stream(index as u64);
+                let result =
+                    decoder_builder(*prob).simulate_until_with_rng(self.criteria, &mut rng);
+                SweepPoint {
+                    probability: *prob,
+                    n_iterations: result.get_n_iterations(),
+                    n_failures: result.get_n_failures(),
+                    failure_rate: result.get_failure_rate(),
+                    standard_error: result.get_standard_error(),
+                    seed: self.seed,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sweep_is_reproducible() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let criteria = StoppingCriteria::new().with_max_iterations(500);
+        let sweep = |probabilities: &[f64]| {
+            simulate_sweep(
+                &code,
+                Channel::BinarySymmetric,
+                DecoderKind::SyndromeTable,
+                None,
+                probabilities,
+                &criteria,
+                3,
+            )
+            .unwrap()
+        };
+        let points = sweep(&[0.05, 0.1]);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].n_iterations, 500);
+        assert_eq!(points, sweep(&[0.05, 0.1]));
+        assert_eq!(points[0], sweep(&[0.05])[0]);
+    }
+
+    #[test]
+    fn decoder_must_match_the_channel() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let criteria = StoppingCriteria::new().with_max_iterations(10);
+        let points = simulate_sweep(
+            &code,
+            Channel::Erasure,
+            DecoderKind::Gallager,
+            None,
+            &[0.1],
+            &criteria,
+            0,
+        );
+        assert!(points.is_err());
+    }
+
+    #[test]
+    fn probabilities_must_match_the_decoder() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let criteria = StoppingCriteria::new().with_max_iterations(10);
+        let sweep = |decoder, prob| {
+            simulate_sweep(
+                &code,
+                Channel::BinarySymmetric,
+                decoder,
+                None,
+                &[0.1, prob],
+                &criteria,
+                0,
+            )
+        };
+        assert!(sweep(DecoderKind::SyndromeTable, 0.7).is_err());
+        assert!(sweep(DecoderKind::Gallager, 0.7).is_ok());
+        assert!(sweep(DecoderKind::BitFlipping, 1.5).is_err());
+    }
+
+    #[test]
+    fn matching_needs_bits_in_at_most_2_checks() {
+        let criteria = StoppingCriteria::new().with_max_iterations(10);
+        let sweep = |code| {
+            simulate_sweep(
+                &code,
+                Channel::BinarySymmetric,
+                DecoderKind::Matching,
+                None,
+                &[0.1],
+                &criteria,
+                0,
+            )
+        };
+        assert!(sweep(ParityCheckMatrix::hamming_code(3)).is_err());
+        assert!(sweep(ParityCheckMatrix::repetition_code(5)).is_ok());
+    }
+
+    #[test]
+    fn maximum_likelihood_decoders_need_small_codes() {
+        let criteria = StoppingCriteria::new().with_max_iterations(10);
+        let sweep = |code, decoder| {
+            simulate_sweep(
+                &code,
+                Channel::BinarySymmetric,
+                decoder,
+                None,
+                &[0.1],
+                &criteria,
+                0,
+            )
+        };
+        let ldpc_code = RegularLDPCCodeGenerator::new(3, 4, 30, 0)
+            .generate_with_rng(&mut ChaCha8Rng::seed_from_u64(1));
+        assert!(sweep(ldpc_code.clone(), DecoderKind::SyndromeTable).is_err());
+        assert!(sweep(ldpc_code, DecoderKind::CodewordEnumeration).is_err());
+        assert!(sweep(
+            ParityCheckMatrix::hamming_code(4),
+            DecoderKind::SyndromeTable
+        )
+        .is_ok());
+        assert!(sweep(
+            ParityCheckMatrix::repetition_code(40),
+            DecoderKind::CodewordEnumeration
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn decoder_iterations_need_an_iterative_decoder() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let criteria = StoppingCriteria::new().with_max_iterations(10);
+        let sweep = |decoder| {
+            simulate_sweep(
+                &code,
+                Channel::BinarySymmetric,
+                decoder,
+                Some(5),
+                &[0.1],
+                &criteria,
+                0,
+            )
+        };
+        assert!(sweep(DecoderKind::SyndromeTable).is_err());
+        assert!(sweep(DecoderKind::Gallager).is_ok());
+    }
+
+    #[test]
+    fn invalid_stopping_criteria_are_rejected() {
+        let stopping = |batch_size, relative_precision, time_budget| {
+            StoppingArgs {
+                relative_precision,
+                time_budget,
+                batch_size,
+                ..StoppingArgs::default()
+            }
+            .get_criteria()
+        };
+        assert!(stopping(0, None, None).is_err());
+        assert!(stopping(10, Some(0.0), None).is_err());
+        assert!(stopping(10, None, Some(-1.0)).is_err());
+        assert!(stopping(10, None, Some(f64::NAN)).is_err());
+        assert_eq!(
+            stopping(10, Some(0.1), None).unwrap(),
+            StoppingCriteria::new()
+                .with_relative_precision(0.1)
+                .with_max_iterations(DEFAULT_MAX_ITERATIONS)
+                .with_batch_size(10)
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Noisy Clifford circuits with detectors and observables.
+
+use crate::{Pauli, PauliString, StabilizerTableau, GF2};
+use rand::{thread_rng, Rng};
+
+/// An operation of a `Circuit`.
+///
+/// Noise operations apply a random Pauli with the given probability. A depolarizing operation
+/// applies each non-identity Pauli with the same probability.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    /// Resets a qubit in the zero state.
+    Reset(usize),
+    /// Hadamard gate.
+    H(usize),
+    /// Controlled not gate from a control to a target.
+    CNOT(usize, usize),
+    /// Controlled Z gate.
+    CZ(usize, usize),
+    /// Controlled Y gate from a control to a target.
+    CY(usize, usize),
+    /// Measures a qubit in the Z basis.
+    MeasureZ(usize),
+    /// Applies X with the given probability.
+    XError(usize, f64),
+    /// Applies Z with the given probability.
+    ZError(usize, f64),
+    /// Applies one of X, Y or Z with the given total probability.
+    Depolarize1(usize, f64),
+    /// Applies one of the 15 non-identity two-qubit Paulis with the given total probability.
+    Depolarize2(usize, usize, f64),
+    /// Separates layers of operations.
+    Tick,
+}
+
+impl Operation {
+    /// Returns `true` if `self` is a noise operation.
+    pub fn is_noise(&self) -> bool {
+        matches!(
+            self,
+            Self::XError(..) | Self::ZError(..) | Self::Depolarize1(..) | Self::Depolarize2(..)
+        )
+    }
+}
+
+/// A Clifford circuit with Pauli noise.
+///
+/// A detector is a set of measurements with a deterministic parity in the absence of noise.
+/// An observable is a set of measurements whose parity is the outcome of a logical measurement.
+/// Measurements are indexed in the order they are performed.
+///
+/// # Example
+///
+/// ```
+/// use believer::{Circuit, Operation, GF2};
+///
+/// // Measure the parity of two qubits with an ancilla.
+/// let mut circuit = Circuit::new(3);
+/// circuit.push(Operation::XError(0, 1.0));
+/// circuit.push(Operation::CNOT(0, 2));
+/// circuit.push(Operation::CNOT(1, 2));
+/// circuit.push(Operation::MeasureZ(2));
+/// circuit.add_detector(vec![0]);
+///
+/// let measurements = circuit.sample_measurements();
+/// assert_eq!(circuit.get_detector_values(&measurements), vec![GF2::B1]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circuit {
+    n_qubits: usize,
+    operations: Vec<Operation>,
+    n_measurements: usize,
+    detectors: Vec<Vec<usize>>,
+    observables: Vec<Vec<usize>>,
+}
+
+impl Circuit {
+    // ***** Construction *****
+
+    /// Creates an empty circuit over `n_qubits` initialized in the zero state.
+    pub fn new(n_qubits: usize) -> Self {
+        Self {
+            n_qubits,
+            operations: Vec::new(),
+            n_measurements: 0,
+            detectors: Vec::new(),
+            observables: Vec::new(),
+        }
+    }
+
+    /// Adds `operation` at the end of `self`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `operation` acts on a qubit out of bound, if a two-qubit operation acts twice on
+    /// the same qubit or if a probability is not between 0 and 1.
+    pub fn push(&mut self, operation: Operation) -> &mut Self {
+        let (qubits, probability) = get_qubits_and_probability(&operation);
+        if qubits.iter().any(|qubit| *qubit >= self.n_qubits) {
+            panic!("qubit out of bound");
+        }
+        if qubits.len() == 2 && qubits[0] == qubits[1] {
+            panic!("two-qubit operations must act on different qubits");
+        }
+        if !(0.0..=1.0).contains(&probability) {
+            panic!("invalid probability");
+        }
+        if let Operation::MeasureZ(_) = operation {
+            self.n_measurements += 1;
+        }
+        self.operations.push(operation);
+        self
+    }
+
+    /// Adds a detector over the given `measurements`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some measurements are not yet in the circuit.
+    pub fn add_detector(&mut self, measurements: Vec<usize>) -> &mut Self {
+        self.assert_measurements_exist(&measurements);
+        self.detectors.push(measurements);
+        self
+    }
+
+    /// Adds an observable over the given `measurements`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some measurements are not yet in the circuit.
+    pub fn add_observable(&mut self, measurements: Vec<usize>) -> &mut Self {
+        self.assert_measurements_exist(&measurements);
+        self.observables.push(measurements);
+        self
+    }
+
+    /// Returns a copy of `self` without the noise operations.
+    pub fn without_noise(&self) -> Self {
+        Self {
+            operations: self
+                .operations
+                .iter()
+                .filter(|operation| !operation.is_noise())
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of qubits of `self`.
+    pub fn get_n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    /// Returns the operations of `self`.
+    pub fn get_operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Returns the number of measurements of `self`.
+    pub fn get_n_measurements(&self) -> usize {
+        self.n_measurements
+    }
+
+    /// Returns the measurements of each detector.
+    pub fn get_detectors(&self) -> &[Vec<usize>] {
+        &self.detectors
+    }
+
+    /// Returns the measurements of each observable.
+    pub fn get_observables(&self) -> &[Vec<usize>] {
+        &self.observables
+    }
+
+    /// Returns the parity of each detector for the given `measurements`.
+    pub fn get_detector_values(&self, measurements: &[GF2]) -> Vec<GF2> {
+        get_parities(&self.detectors, measurements)
+    }
+
+    /// Returns the parity of each observable for the given `measurements`.
+    pub fn get_observable_values(&self, measurements: &[GF2]) -> Vec<GF2> {
+        get_parities(&self.observables, measurements)
+    }
+
+    // ***** Simulation *****
+
+    /// Simulates `self` with a stabilizer tableau and returns the measurement outcomes using the
+    /// random number generator `rng`.
+    pub fn sample_measurements_with_rng<R: Rng>(&self, rng: &mut R) -> Vec<GF2> {
+        let mut tableau = StabilizerTableau::new(self.n_qubits);
+        let mut measurements = Vec::with_capacity(self.n_measurements);
+        for operation in self.operations.iter() {
+            match *operation {
+                Operation::Reset(qubit) => {
+                    if tableau.measure_z_with_rng(qubit, rng).get_outcome() == GF2::B1 {
+                        tableau.x(qubit);
+                    }
+                }
+                Operation::H(qubit) => tableau.h(qubit),
+                Operation::CNOT(control, target) => tableau.cnot(control, target),
+                Operation::CZ(first, second) => tableau.cz(first, second),
+                Operation::CY(control, target) => {
+                    tableau.s(target);
+                    tableau.s(target);
+                    tableau.s(target);
+                    tableau.cnot(control, target);
+                    tableau.s(target);
+                }
+                Operation::MeasureZ(qubit) => {
+                    measurements.push(tableau.measure_z_with_rng(qubit, rng).get_outcome())
+                }
+                Operation::XError(qubit, probability) => {
+                    if rng.gen::<f64>() < probability {
+                        tableau.x(qubit);
+                    }
+                }
+                Operation::ZError(qubit, probability) => {
+                    if rng.gen::<f64>() < probability {
+                        tableau.z(qubit);
+                    }
+                }
+                Operation::Depolarize1(qubit, probability) => {
+                    if rng.gen::<f64>() < probability {
+                        let pauli = [Pauli::X, Pauli::Y, Pauli::Z][rng.gen_range(0, 3)];
+                        let error =
+                            PauliString::from_sparse_paulis(&[(pauli, qubit)], self.n_qubits);
+                        tableau.apply_pauli(&error);
+                    }
+                }
+                Operation::Depolarize2(first, second, probability) => {
+                    if rng.gen::<f64>() < probability {
+                        let paulis = get_two_qubit_paulis()[rng.gen_range(0, 15)];
+                        let error = PauliString::from_sparse_paulis(
+                            &[(paulis.0, first), (paulis.1, second)],
+                            self.n_qubits,
+                        );
+                        tableau.apply_pauli(&error);
+                    }
+                }
+                Operation::Tick => (),
+            }
+        }
+        measurements
+    }
+
+    /// Simulates `self` and returns the measurement outcomes using the thread random number
+    /// generator.
+    pub fn sample_measurements(&self) -> Vec<GF2> {
+        self.sample_measurements_with_rng(&mut thread_rng())
+    }
+
+    fn assert_measurements_exist(&self, measurements: &[usize]) {
+        if measurements
+            .iter()
+            .any(|measurement| *measurement >= self.n_measurements)
+        {
+            panic!("some measurements are not in the circuit");
+        }
+    }
+}
+
+// Returns the 15 non-identity two-qubit Paulis.
+pub(super) fn get_two_qubit_paulis() -> Vec<(Pauli, Pauli)> {
+    let paulis = [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z];
+    paulis
+        .iter()
+        .flat_map(|first| paulis.iter().map(move |second| (*first, *second)))
+        .skip(1)
+        .collect()
+}
+
+fn get_qubits_and_probability(operation: &Operation) -> (Vec<usize>, f64) {
+    match *operation {
+        Operation::Reset(qubit) | Operation::H(qubit) | Operation::MeasureZ(qubit) => {
+            (vec![qubit], 0.0)
+        }
+        Operation::CNOT(first, second)
+        | Operation::CZ(first, second)
+        | Operation::CY(first, second) => (vec![first, second], 0.0),
+        Operation::XError(qubit, probability)
+        | Operation::ZError(qubit, probability)
+        | Operation::Depolarize1(qubit, probability) => (vec![qubit], probability),
+        Operation::Depolarize2(first, second, probability) => (vec![first, second], probability),
+        Operation::Tick => (Vec::new(), 0.0),
+    }
+}
+
+fn get_parities(sets: &[Vec<usize>], measurements: &[GF2]) -> Vec<GF2> {
+    sets.iter()
+        .map(|set| {
+            set.iter().fold(GF2::B0, |parity, measurement| {
+                parity + measurements[*measurement]
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn controlled_paulis() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for (gate, expected) in &[
+            (Operation::CNOT(0, 1), GF2::B1),
+            (Operation::CY(0, 1), GF2::B1),
+            (Operation::CZ(0, 1), GF2::B0),
+        ] {
+            let mut circuit = Circuit::new(2);
+            circuit
+                .push(Operation::XError(0, 1.0))
+                .push(*gate)
+                .push(Operation::MeasureZ(1));
+            assert_eq!(
+                circuit.sample_measurements_with_rng(&mut rng),
+                vec![*expected]
+            );
+        }
+    }
+
+    #[test]
+    fn reset_sets_qubits_to_zero() {
+        let mut circuit = Circuit::new(1);
+        circuit
+            .push(Operation::H(0))
+            .push(Operation::Reset(0))
+            .push(Operation::MeasureZ(0));
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..10 {
+            assert_eq!(
+                circuit.sample_measurements_with_rng(&mut rng),
+                vec![GF2::B0]
+            );
+        }
+    }
+
+    #[test]
+    fn noise_is_removed() {
+        let mut circuit = Circuit::new(2);
+        circuit
+            .push(Operation::Depolarize2(0, 1, 0.5))
+            .push(Operation::Tick)
+            .push(Operation::MeasureZ(0));
+        circuit.add_observable(vec![0]);
+        let noiseless = circuit.without_noise();
+        assert_eq!(
+            noiseless.get_operations(),
+            &[Operation::Tick, Operation::MeasureZ(0)]
+        );
+        assert_eq!(noiseless.get_observables(), &[vec![0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn detectors_must_use_existing_measurements() {
+        let mut circuit = Circuit::new(1);
+        circuit.push(Operation::MeasureZ(0));
+        circuit.add_detector(vec![1]);
+    }
+
+    #[test]
+    fn there_are_15_two_qubit_paulis() {
+        let paulis = get_two_qubit_paulis();
+        assert_eq!(paulis.len(), 15);
+        assert!(!paulis.contains(&(Pauli::I, Pauli::I)));
+    }
+}
@@ -0,0 +1,334 @@
+//! Detector error models of noisy circuits.
+
+use super::circuit::get_two_qubit_paulis;
+use super::{Circuit, Operation};
+use crate::{ParityCheckMatrix, Pauli, GF2};
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+
+/// The independent fault mechanisms of a circuit together with the detectors and observables
+/// they flip.
+///
+/// The detectors matrix has a check for each detector and a bit for each mechanism. Thus, the
+/// syndrome of a set of mechanisms is the value of the detectors and decoders working on a
+/// `ParityCheckMatrix` can be used directly with the priors of the mechanisms.
+///
+/// Mechanisms with the same effect are merged and mechanisms without any effect are dropped.
+///
+/// # Example
+///
+/// ```
+/// use believer::{DetectorErrorModel, GF4Stabilizers, NoiseModel, SyndromeExtraction};
+///
+/// let circuit = SyndromeExtraction::from_code(&GF4Stabilizers::steane_code())
+///     .with_n_rounds(2)
+///     .with_noise_model(NoiseModel::uniform(0.001))
+///     .build_circuit();
+/// let model = DetectorErrorModel::from_circuit(&circuit);
+///
+/// assert_eq!(model.get_detectors().get_n_checks(), circuit.get_detectors().len());
+/// assert_eq!(model.get_detectors().get_n_bits(), model.get_n_mechanisms());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectorErrorModel {
+    detectors: ParityCheckMatrix,
+    observables: ParityCheckMatrix,
+    priors: Vec<f64>,
+}
+
+impl DetectorErrorModel {
+    // ***** Construction *****
+
+    /// Compiles the detector error model of `circuit` by propagating each Pauli component of
+    /// each noise operation through the rest of the circuit.
+    ///
+    /// A depolarizing operation of probability `p` is decomposed into 3 or 15 independent
+    /// mechanisms of probability `p / 3` or `p / 15`.
+    pub fn from_circuit(circuit: &Circuit) -> Self {
+        let mut detectors_of_measurement = vec![Vec::new(); circuit.get_n_measurements()];
+        for (detector, measurements) in circuit.get_detectors().iter().enumerate() {
+            measurements
+                .iter()
+                .for_each(|measurement| detectors_of_measurement[*measurement].push(detector));
+        }
+        let mut observables_of_measurement = vec![Vec::new(); circuit.get_n_measurements()];
+        for (observable, measurements) in circuit.get_observables().iter().enumerate() {
+            measurements
+                .iter()
+                .for_each(|measurement| observables_of_measurement[*measurement].push(observable));
+        }
+
+        let mut mechanisms: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+        let mut priors: Vec<f64> = Vec::new();
+        let mut indices: HashMap<(Vec<usize>, Vec<usize>), usize> = HashMap::new();
+        let mut n_previous_measurements = 0;
+        for (position, operation) in circuit.get_operations().iter().enumerate() {
+            if let Operation::MeasureZ(_) = operation {
+                n_previous_measurements += 1;
+            }
+            for (paulis, probability) in get_components(operation) {
+                let flipped_measurements =
+                    propagate(circuit, &paulis, position + 1, n_previous_measurements);
+                let effect = (
+                    get_flipped_sets(&flipped_measurements, &detectors_of_measurement),
+                    get_flipped_sets(&flipped_measurements, &observables_of_measurement),
+                );
+                if effect.0.is_empty() && effect.1.is_empty() {
+                    continue;
+                }
+                match indices.get(&effect) {
+                    Some(index) => {
+                        let prior = priors[*index];
+                        priors[*index] = prior * (1.0 - probability) + probability * (1.0 - prior);
+                    }
+                    None => {
+                        indices.insert(effect.clone(), mechanisms.len());
+                        mechanisms.push(effect);
+                        priors.push(probability);
+                    }
+                }
+            }
+        }
+
+        let n_mechanisms = mechanisms.len();
+        let mut detector_checks = vec![Vec::new(); circuit.get_detectors().len()];
+        let mut observable_checks = vec![Vec::new(); circuit.get_observables().len()];
+        for (mechanism, (detectors, observables)) in mechanisms.into_iter().enumerate() {
+            detectors
+                .into_iter()
+                .for_each(|detector| detector_checks[detector].push(mechanism));
+            observables
+                .into_iter()
+                .for_each(|observable| observable_checks[observable].push(mechanism));
+        }
+        Self {
+            detectors: ParityCheckMatrix::with_n_bits(n_mechanisms).with_checks(detector_checks),
+            observables: ParityCheckMatrix::with_n_bits(n_mechanisms)
+                .with_checks(observable_checks),
+            priors,
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of fault mechanisms.
+    pub fn get_n_mechanisms(&self) -> usize {
+        self.priors.len()
+    }
+
+    /// Returns the matrix of detectors versus mechanisms.
+    pub fn get_detectors(&self) -> &ParityCheckMatrix {
+        &self.detectors
+    }
+
+    /// Returns the matrix of observables versus mechanisms.
+    pub fn get_observables(&self) -> &ParityCheckMatrix {
+        &self.observables
+    }
+
+    /// Returns the probability of each mechanism.
+    pub fn get_priors(&self) -> &[f64] {
+        &self.priors
+    }
+
+    // ***** Sampling *****
+
+    /// Samples each mechanism independently according to its prior using the random number
+    /// generator `rng`.
+    pub fn sample_mechanisms_with_rng<R: Rng>(&self, rng: &mut R) -> Vec<GF2> {
+        self.priors
+            .iter()
+            .map(|prior| {
+                if rng.gen::<f64>() < *prior {
+                    GF2::B1
+                } else {
+                    GF2::B0
+                }
+            })
+            .collect()
+    }
+
+    /// Samples each mechanism independently according to its prior using the thread random
+    /// number generator.
+    pub fn sample_mechanisms(&self) -> Vec<GF2> {
+        self.sample_mechanisms_with_rng(&mut thread_rng())
+    }
+
+    /// Returns the values of the detectors flipped by the given `mechanisms`.
+    pub fn get_detector_values(&self, mechanisms: &[GF2]) -> Vec<GF2> {
+        self.detectors.get_syndrome_of(mechanisms)
+    }
+
+    /// Returns the values of the observables flipped by the given `mechanisms`.
+    pub fn get_observable_values(&self, mechanisms: &[GF2]) -> Vec<GF2> {
+        self.observables.get_syndrome_of(mechanisms)
+    }
+}
+
+// Returns the Pauli errors of a noise operation with their probabilities.
+fn get_components(operation: &Operation) -> Vec<(Vec<(Pauli, usize)>, f64)> {
+    match *operation {
+        Operation::XError(qubit, probability) => vec![(vec![(Pauli::X, qubit)], probability)],
+        Operation::ZError(qubit, probability) => vec![(vec![(Pauli::Z, qubit)], probability)],
+        Operation::Depolarize1(qubit, probability) => [Pauli::X, Pauli::Y, Pauli::Z]
+            .iter()
+            .map(|pauli| (vec![(*pauli, qubit)], probability / 3.0))
+            .collect(),
+        Operation::Depolarize2(first, second, probability) => get_two_qubit_paulis()
+            .into_iter()
+            .map(|(first_pauli, second_pauli)| {
+                (
+                    vec![(first_pauli, first), (second_pauli, second)],
+                    probability / 15.0,
+                )
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Propagates a Pauli frame from the operation at `start` to the end of the circuit and returns
+// the flipped measurements.
+fn propagate(
+    circuit: &Circuit,
+    paulis: &[(Pauli, usize)],
+    start: usize,
+    mut n_measurements: usize,
+) -> Vec<usize> {
+    let mut x_frame = vec![false; circuit.get_n_qubits()];
+    let mut z_frame = vec![false; circuit.get_n_qubits()];
+    for (pauli, qubit) in paulis.iter() {
+        let (x, z) = pauli.as_gf4();
+        x_frame[*qubit] ^= x == 1;
+        z_frame[*qubit] ^= z == 1;
+    }
+    let mut flipped = Vec::new();
+    for operation in circuit.get_operations()[start..].iter() {
+        match *operation {
+            Operation::Reset(qubit) => {
+                x_frame[qubit] = false;
+                z_frame[qubit] = false;
+            }
+            Operation::H(qubit) => {
+                std::mem::swap(&mut x_frame[qubit], &mut z_frame[qubit]);
+            }
+            Operation::CNOT(control, target) => {
+                x_frame[target] ^= x_frame[control];
+                z_frame[control] ^= z_frame[target];
+            }
+            Operation::CZ(first, second) => {
+                z_frame[first] ^= x_frame[second];
+                z_frame[second] ^= x_frame[first];
+            }
+            Operation::CY(control, target) => {
+                z_frame[target] ^= x_frame[target];
+                x_frame[target] ^= x_frame[control];
+                z_frame[control] ^= z_frame[target];
+                z_frame[target] ^= x_frame[target];
+            }
+            Operation::MeasureZ(qubit) => {
+                if x_frame[qubit] {
+                    flipped.push(n_measurements);
+                }
+                n_measurements += 1;
+            }
+            _ => (),
+        }
+    }
+    flipped
+}
+
+// Returns the sorted sets containing an odd number of the flipped measurements.
+fn get_flipped_sets(
+    flipped_measurements: &[usize],
+    sets_of_measurement: &[Vec<usize>],
+) -> Vec<usize> {
+    let mut sets: Vec<usize> = flipped_measurements
+        .iter()
+        .flat_map(|measurement| sets_of_measurement[*measurement].iter().cloned())
+        .collect();
+    sets.sort_unstable();
+    let mut odd_sets = Vec::with_capacity(sets.len());
+    let mut index = 0;
+    while index < sets.len() {
+        let n_copies = sets[index..]
+            .iter()
+            .take_while(|set| **set == sets[index])
+            .count();
+        if n_copies % 2 == 1 {
+            odd_sets.push(sets[index]);
+        }
+        index += n_copies;
+    }
+    odd_sets
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{GF4Stabilizers, NoiseModel, SyndromeExtraction};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn merged_mechanisms_combine_probabilities() {
+        let mut circuit = Circuit::new(1);
+        circuit
+            .push(Operation::XError(0, 0.1))
+            .push(Operation::XError(0, 0.2))
+            .push(Operation::ZError(0, 0.3))
+            .push(Operation::MeasureZ(0));
+        circuit.add_detector(vec![0]);
+        let model = DetectorErrorModel::from_circuit(&circuit);
+        assert_eq!(model.get_n_mechanisms(), 1);
+        assert!((model.get_priors()[0] - 0.26).abs() < 1e-12);
+        assert_eq!(
+            model.get_detectors().get_check(0).unwrap().to_vec(),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn mechanisms_match_sampled_faults() {
+        let circuit = SyndromeExtraction::from_code(&GF4Stabilizers::steane_code())
+            .with_n_rounds(2)
+            .with_noise_model(NoiseModel::noiseless())
+            .build_circuit();
+        let n_operations = circuit.get_operations().len();
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+        // Insert a deterministic fault in the middle of the circuit.
+        for position in (0..n_operations).step_by(7) {
+            for pauli in [Pauli::X, Pauli::Z].iter() {
+                for qubit in 0..circuit.get_n_qubits() {
+                    let noise = match pauli {
+                        Pauli::X => Operation::XError(qubit, 1.0),
+                        _ => Operation::ZError(qubit, 1.0),
+                    };
+                    let mut faulty = Circuit::new(circuit.get_n_qubits());
+                    for operation in circuit.get_operations()[..position].iter() {
+                        faulty.push(*operation);
+                    }
+                    faulty.push(noise);
+                    for operation in circuit.get_operations()[position..].iter() {
+                        faulty.push(*operation);
+                    }
+                    circuit.get_detectors().iter().for_each(|detector| {
+                        faulty.add_detector(detector.clone());
+                    });
+                    circuit.get_observables().iter().for_each(|observable| {
+                        faulty.add_observable(observable.clone());
+                    });
+
+                    let model = DetectorErrorModel::from_circuit(&faulty);
+                    let measurements = faulty.sample_measurements_with_rng(&mut rng);
+                    let detectors = faulty.get_detector_values(&measurements);
+                    let observables = faulty.get_observable_values(&measurements);
+                    let mechanisms = vec![GF2::B1; model.get_n_mechanisms()];
+                    assert_eq!(model.get_detector_values(&mechanisms), detectors);
+                    assert_eq!(model.get_observable_values(&mechanisms), observables);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Circuit-level noise for stabilizer codes.
+//!
+//! A `SyndromeExtraction` builds a noisy `Circuit` measuring the stabilizers of a code during
+//! many rounds. The circuit can be simulated with a stabilizer tableau or compiled into a
+//! `DetectorErrorModel` that decoders can use as a parity check matrix.
+
+mod circuit;
+pub use circuit::{Circuit, Operation};
+
+mod detector_error_model;
+pub use detector_error_model::DetectorErrorModel;
+
+mod noise_model;
+pub use noise_model::NoiseModel;
+
+mod syndrome_extraction;
+pub use syndrome_extraction::{ScheduleError, SyndromeExtraction};
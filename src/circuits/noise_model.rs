@@ -0,0 +1,138 @@
+//! Circuit-level noise models.
+
+/// The probabilities of the faults added to a circuit.
+///
+/// Gates are followed by depolarizing noise over the qubits they act on, measurements are
+/// flipped, resets are followed by a bit flip and qubits that are idle during a layer suffer
+/// depolarizing noise.
+///
+/// # Example
+///
+/// ```
+/// use believer::NoiseModel;
+///
+/// let noise = NoiseModel::uniform(0.001).with_idle_error(0.0);
+/// assert_eq!(noise.get_two_qubit_gate_error(), 0.001);
+/// assert_eq!(noise.get_idle_error(), 0.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseModel {
+    single_qubit_gate_error: f64,
+    two_qubit_gate_error: f64,
+    measurement_error: f64,
+    reset_error: f64,
+    idle_error: f64,
+}
+
+impl NoiseModel {
+    // ***** Construction *****
+
+    /// Creates a noise model without any fault.
+    pub fn noiseless() -> Self {
+        Self::uniform(0.0)
+    }
+
+    /// Creates a noise model with the same `probability` for every kind of fault.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn uniform(probability: f64) -> Self {
+        assert_probability(probability);
+        Self {
+            single_qubit_gate_error: probability,
+            two_qubit_gate_error: probability,
+            measurement_error: probability,
+            reset_error: probability,
+            idle_error: probability,
+        }
+    }
+
+    /// Sets the probability of depolarizing noise after single-qubit gates.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_single_qubit_gate_error(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.single_qubit_gate_error = probability;
+        self
+    }
+
+    /// Sets the probability of two-qubit depolarizing noise after two-qubit gates.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_two_qubit_gate_error(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.two_qubit_gate_error = probability;
+        self
+    }
+
+    /// Sets the probability of flipping a measurement outcome.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_measurement_error(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.measurement_error = probability;
+        self
+    }
+
+    /// Sets the probability of preparing the one state instead of the zero state.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_reset_error(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.reset_error = probability;
+        self
+    }
+
+    /// Sets the probability of depolarizing noise on idle qubits during each layer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `probability` is not between 0 and 1.
+    pub fn with_idle_error(mut self, probability: f64) -> Self {
+        assert_probability(probability);
+        self.idle_error = probability;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the probability of depolarizing noise after single-qubit gates.
+    pub fn get_single_qubit_gate_error(&self) -> f64 {
+        self.single_qubit_gate_error
+    }
+
+    /// Returns the probability of two-qubit depolarizing noise after two-qubit gates.
+    pub fn get_two_qubit_gate_error(&self) -> f64 {
+        self.two_qubit_gate_error
+    }
+
+    /// Returns the probability of flipping a measurement outcome.
+    pub fn get_measurement_error(&self) -> f64 {
+        self.measurement_error
+    }
+
+    /// Returns the probability of preparing the one state instead of the zero state.
+    pub fn get_reset_error(&self) -> f64 {
+        self.reset_error
+    }
+
+    /// Returns the probability of depolarizing noise on idle qubits during each layer.
+    pub fn get_idle_error(&self) -> f64 {
+        self.idle_error
+    }
+}
+
+fn assert_probability(probability: f64) {
+    if !(0.0..=1.0).contains(&probability) {
+        panic!("probability must be between 0 and 1");
+    }
+}
@@ -0,0 +1,508 @@
+//! Syndrome extraction circuits for stabilizer codes.
+
+use super::{Circuit, NoiseModel, Operation};
+use crate::{GF4Stabilizers, Pauli, PauliString};
+use std::fmt;
+
+/// A builder of circuits measuring the stabilizers of a code during many rounds.
+///
+/// The data qubits are the qubits `0` to `n - 1` and the stabilizer `s` is measured with the
+/// ancilla `n + s`. During each round, the ancillas are reset, rotated with a Hadamard gate,
+/// used as controls of the controlled Paulis given by the schedule, rotated back and measured.
+/// After the last round, the data qubits are measured in the Z basis.
+///
+/// The data qubits start in the zero state. Thus, the stabilizers with no X or Y components are
+/// detectors in the first round and after the final measurements, while all stabilizers are
+/// detectors between consecutive rounds. The observables are the Z logical operators.
+///
+/// # Example
+///
+/// ```
+/// use believer::{GF2, GF4Stabilizers, NoiseModel, SyndromeExtraction};
+///
+/// let code = GF4Stabilizers::steane_code();
+/// let circuit = SyndromeExtraction::from_code(&code)
+///     .with_n_rounds(2)
+///     .with_noise_model(NoiseModel::noiseless())
+///     .build_circuit();
+///
+/// let measurements = circuit.sample_measurements();
+/// assert!(circuit
+///     .get_detector_values(&measurements)
+///     .iter()
+///     .all(|value| *value == GF2::B0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyndromeExtraction {
+    stabilizers: Vec<PauliString>,
+    z_logicals: Vec<Vec<usize>>,
+    schedule: Vec<Vec<(usize, usize)>>,
+    noise_model: NoiseModel,
+    n_rounds: usize,
+}
+
+/// The reasons why a schedule doesn't measure the stabilizers of a code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// A gate of a stabilizer on a qubit outside its support.
+    InvalidGate { stabilizer: usize, qubit: usize },
+    /// A gate that appears more than once.
+    RepeatedGate { stabilizer: usize, qubit: usize },
+    /// A qubit of the support of a stabilizer that is never used.
+    MissingGate { stabilizer: usize, qubit: usize },
+    /// A qubit or an ancilla used more than once in a layer.
+    Conflict { layer: usize },
+    /// A pair of stabilizers whose gates are ordered such that the measurement of one stabilizer
+    /// disturbs the other.
+    InvalidOrdering { first: usize, second: usize },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidGate { stabilizer, qubit } => write!(
+                f,
+                "qubit {} is not in the support of stabilizer {}",
+                qubit, stabilizer
+            ),
+            Self::RepeatedGate { stabilizer, qubit } => write!(
+                f,
+                "gate of stabilizer {} on qubit {} is repeated",
+                stabilizer, qubit
+            ),
+            Self::MissingGate { stabilizer, qubit } => write!(
+                f,
+                "gate of stabilizer {} on qubit {} is missing",
+                stabilizer, qubit
+            ),
+            Self::Conflict { layer } => write!(f, "a qubit is used twice in layer {}", layer),
+            Self::InvalidOrdering { first, second } => write!(
+                f,
+                "gates of stabilizers {} and {} are not ordered properly",
+                first, second
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+impl SyndromeExtraction {
+    // ***** Construction *****
+
+    /// Creates a builder measuring the stabilizers of `code` once without noise.
+    ///
+    /// The default schedule places each gate in the earliest possible layer processing the
+    /// stabilizers in order. Therefore, the gates acting on a data qubit are ordered by
+    /// stabilizer which is always a valid ordering.
+    pub fn from_code(code: &GF4Stabilizers) -> Self {
+        let stabilizers: Vec<PauliString> = (0..code.n_stabilizers())
+            .filter_map(|stabilizer| code.get_stabilizer(stabilizer))
+            .collect();
+        let schedule = get_default_schedule(&stabilizers, code.n_qubits());
+        Self {
+            stabilizers,
+            z_logicals: code.get_z_logicals(),
+            schedule,
+            noise_model: NoiseModel::noiseless(),
+            n_rounds: 1,
+        }
+    }
+
+    /// Sets the layers of gates used to measure the stabilizers. Each gate is a pair
+    /// `(stabilizer, qubit)`.
+    ///
+    /// Returns an error if the schedule doesn't measure each stabilizer exactly.
+    pub fn with_schedule(
+        mut self,
+        schedule: Vec<Vec<(usize, usize)>>,
+    ) -> Result<Self, ScheduleError> {
+        self.validate_schedule(&schedule)?;
+        self.schedule = schedule;
+        Ok(self)
+    }
+
+    /// Sets the noise model of the circuit.
+    pub fn with_noise_model(mut self, noise_model: NoiseModel) -> Self {
+        self.noise_model = noise_model;
+        self
+    }
+
+    /// Sets the number of rounds of stabilizer measurements.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_rounds` is 0.
+    pub fn with_n_rounds(mut self, n_rounds: usize) -> Self {
+        if n_rounds == 0 {
+            panic!("there must be at least one round");
+        }
+        self.n_rounds = n_rounds;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of data qubits.
+    pub fn get_n_data_qubits(&self) -> usize {
+        self.stabilizers
+            .first()
+            .map(|stabilizer| stabilizer.get_n_qubits())
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of stabilizers.
+    pub fn get_n_stabilizers(&self) -> usize {
+        self.stabilizers.len()
+    }
+
+    /// Returns the layers of gates used to measure the stabilizers.
+    pub fn get_schedule(&self) -> &[Vec<(usize, usize)>] {
+        &self.schedule
+    }
+
+    /// Returns the noise model of the circuit.
+    pub fn get_noise_model(&self) -> NoiseModel {
+        self.noise_model
+    }
+
+    /// Returns the number of rounds of stabilizer measurements.
+    pub fn get_n_rounds(&self) -> usize {
+        self.n_rounds
+    }
+
+    /// Returns the Z logical operators measured as observables.
+    ///
+    /// Each logical is given by the positions of its Z Paulis.
+    pub fn get_z_logicals(&self) -> Vec<Vec<usize>> {
+        self.z_logicals.clone()
+    }
+
+    // ***** Circuit *****
+
+    /// Builds the syndrome extraction circuit with its detectors and observables.
+    pub fn build_circuit(&self) -> Circuit {
+        let n_data = self.get_n_data_qubits();
+        let n_stabilizers = self.get_n_stabilizers();
+        let ancillas: Vec<usize> = (n_data..n_data + n_stabilizers).collect();
+        let mut builder = NoisyCircuitBuilder {
+            circuit: Circuit::new(n_data + n_stabilizers),
+            noise_model: self.noise_model,
+            touched: vec![false; n_data + n_stabilizers],
+        };
+
+        for _ in 0..self.n_rounds {
+            ancillas.iter().for_each(|ancilla| builder.reset(*ancilla));
+            builder.end_layer();
+            ancillas.iter().for_each(|ancilla| builder.h(*ancilla));
+            builder.end_layer();
+            for layer in self.schedule.iter() {
+                for (stabilizer, qubit) in layer.iter() {
+                    let ancilla = n_data + stabilizer;
+                    let gate = match self.stabilizers[*stabilizer].get_pauli(*qubit) {
+                        Some(Pauli::X) => Operation::CNOT(ancilla, *qubit),
+                        Some(Pauli::Y) => Operation::CY(ancilla, *qubit),
+                        _ => Operation::CZ(ancilla, *qubit),
+                    };
+                    builder.two_qubit_gate(gate, ancilla, *qubit);
+                }
+                builder.end_layer();
+            }
+            ancillas.iter().for_each(|ancilla| builder.h(*ancilla));
+            builder.end_layer();
+            ancillas
+                .iter()
+                .for_each(|ancilla| builder.measure(*ancilla));
+            builder.end_layer();
+        }
+        (0..n_data).for_each(|qubit| builder.measure(qubit));
+
+        let mut circuit = builder.circuit;
+        self.add_detectors_and_observables(&mut circuit);
+        circuit
+    }
+
+    fn add_detectors_and_observables(&self, circuit: &mut Circuit) {
+        let n_stabilizers = self.get_n_stabilizers();
+        let final_measurements = self.n_rounds * n_stabilizers;
+        let z_stabilizers: Vec<usize> = (0..n_stabilizers)
+            .filter(|stabilizer| {
+                self.stabilizers[*stabilizer]
+                    .get_paulis()
+                    .iter()
+                    .all(|pauli| *pauli == Pauli::I || *pauli == Pauli::Z)
+            })
+            .collect();
+
+        for stabilizer in z_stabilizers.iter() {
+            circuit.add_detector(vec![*stabilizer]);
+        }
+        for round in 1..self.n_rounds {
+            for stabilizer in 0..n_stabilizers {
+                circuit.add_detector(vec![
+                    (round - 1) * n_stabilizers + stabilizer,
+                    round * n_stabilizers + stabilizer,
+                ]);
+            }
+        }
+        for stabilizer in z_stabilizers.iter() {
+            let mut measurements: Vec<usize> = self.stabilizers[*stabilizer]
+                .support()
+                .into_iter()
+                .map(|qubit| final_measurements + qubit)
+                .collect();
+            measurements.push((self.n_rounds - 1) * n_stabilizers + stabilizer);
+            circuit.add_detector(measurements);
+        }
+        for logical in self.z_logicals.iter() {
+            circuit.add_observable(
+                logical
+                    .iter()
+                    .map(|qubit| final_measurements + qubit)
+                    .collect(),
+            );
+        }
+    }
+
+    fn validate_schedule(&self, schedule: &[Vec<(usize, usize)>]) -> Result<(), ScheduleError> {
+        let n_data = self.get_n_data_qubits();
+        let mut layers: Vec<Vec<Option<usize>>> = self
+            .stabilizers
+            .iter()
+            .map(|_| vec![None; n_data])
+            .collect();
+        for (layer_index, layer) in schedule.iter().enumerate() {
+            let mut used = vec![false; n_data + self.stabilizers.len()];
+            for (stabilizer, qubit) in layer.iter() {
+                let is_in_support = self
+                    .stabilizers
+                    .get(*stabilizer)
+                    .and_then(|pauli_string| pauli_string.get_pauli(*qubit))
+                    .map(|pauli| pauli != Pauli::I)
+                    .unwrap_or(false);
+                if !is_in_support {
+                    return Err(ScheduleError::InvalidGate {
+                        stabilizer: *stabilizer,
+                        qubit: *qubit,
+                    });
+                }
+                if layers[*stabilizer][*qubit].is_some() {
+                    return Err(ScheduleError::RepeatedGate {
+                        stabilizer: *stabilizer,
+                        qubit: *qubit,
+                    });
+                }
+                if used[*qubit] || used[n_data + stabilizer] {
+                    return Err(ScheduleError::Conflict { layer: layer_index });
+                }
+                used[*qubit] = true;
+                used[n_data + stabilizer] = true;
+                layers[*stabilizer][*qubit] = Some(layer_index);
+            }
+        }
+        for (stabilizer, pauli_string) in self.stabilizers.iter().enumerate() {
+            if let Some(qubit) = pauli_string
+                .support()
+                .into_iter()
+                .find(|qubit| layers[stabilizer][*qubit].is_none())
+            {
+                return Err(ScheduleError::MissingGate { stabilizer, qubit });
+            }
+        }
+
+        // The first stabilizer must act first on an even number of the qubits where the two
+        // stabilizers anticommute.
+        for first in 0..self.stabilizers.len() {
+            for second in first + 1..self.stabilizers.len() {
+                let n_first_before_second = (0..n_data)
+                    .filter(|qubit| {
+                        let first_pauli = self.stabilizers[first].get_paulis()[*qubit];
+                        let second_pauli = self.stabilizers[second].get_paulis()[*qubit];
+                        first_pauli.commutator_with(second_pauli) == -1
+                            && layers[first][*qubit] < layers[second][*qubit]
+                    })
+                    .count();
+                if n_first_before_second % 2 == 1 {
+                    return Err(ScheduleError::InvalidOrdering { first, second });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_default_schedule(stabilizers: &[PauliString], n_qubits: usize) -> Vec<Vec<(usize, usize)>> {
+    let mut schedule: Vec<Vec<(usize, usize)>> = Vec::new();
+    let mut next_qubit_layers = vec![0; n_qubits];
+    for (stabilizer, pauli_string) in stabilizers.iter().enumerate() {
+        let mut next_ancilla_layer = 0;
+        for qubit in pauli_string.support() {
+            let layer = std::cmp::max(next_qubit_layers[qubit], next_ancilla_layer);
+            if layer == schedule.len() {
+                schedule.push(Vec::new());
+            }
+            schedule[layer].push((stabilizer, qubit));
+            next_qubit_layers[qubit] = layer + 1;
+            next_ancilla_layer = layer + 1;
+        }
+    }
+    schedule
+}
+
+// Adds operations to a circuit followed by their noise.
+struct NoisyCircuitBuilder {
+    circuit: Circuit,
+    noise_model: NoiseModel,
+    touched: Vec<bool>,
+}
+
+impl NoisyCircuitBuilder {
+    fn reset(&mut self, qubit: usize) {
+        self.circuit.push(Operation::Reset(qubit));
+        self.push_noise(Operation::XError(qubit, self.noise_model.get_reset_error()));
+        self.touched[qubit] = true;
+    }
+
+    fn h(&mut self, qubit: usize) {
+        self.circuit.push(Operation::H(qubit));
+        self.push_noise(Operation::Depolarize1(
+            qubit,
+            self.noise_model.get_single_qubit_gate_error(),
+        ));
+        self.touched[qubit] = true;
+    }
+
+    fn two_qubit_gate(&mut self, gate: Operation, first: usize, second: usize) {
+        self.circuit.push(gate);
+        self.push_noise(Operation::Depolarize2(
+            first,
+            second,
+            self.noise_model.get_two_qubit_gate_error(),
+        ));
+        self.touched[first] = true;
+        self.touched[second] = true;
+    }
+
+    fn measure(&mut self, qubit: usize) {
+        self.push_noise(Operation::XError(
+            qubit,
+            self.noise_model.get_measurement_error(),
+        ));
+        self.circuit.push(Operation::MeasureZ(qubit));
+        self.touched[qubit] = true;
+    }
+
+    // Adds idle noise on the untouched qubits and starts a new layer.
+    fn end_layer(&mut self) {
+        let idle_error = self.noise_model.get_idle_error();
+        for qubit in 0..self.touched.len() {
+            if !self.touched[qubit] {
+                self.push_noise(Operation::Depolarize1(qubit, idle_error));
+            }
+            self.touched[qubit] = false;
+        }
+        self.circuit.push(Operation::Tick);
+    }
+
+    fn push_noise(&mut self, noise: Operation) {
+        let probability = match noise {
+            Operation::XError(_, probability)
+            | Operation::ZError(_, probability)
+            | Operation::Depolarize1(_, probability)
+            | Operation::Depolarize2(_, _, probability) => probability,
+            _ => 0.0,
+        };
+        if probability > 0.0 {
+            self.circuit.push(noise);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GF2;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn assert_noiseless_circuit_is_silent(code: &GF4Stabilizers) {
+        let circuit = SyndromeExtraction::from_code(code)
+            .with_n_rounds(3)
+            .build_circuit();
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..5 {
+            let measurements = circuit.sample_measurements_with_rng(&mut rng);
+            assert!(circuit
+                .get_detector_values(&measurements)
+                .iter()
+                .all(|value| *value == GF2::B0));
+            assert!(circuit
+                .get_observable_values(&measurements)
+                .iter()
+                .all(|value| *value == GF2::B0));
+        }
+    }
+
+    #[test]
+    fn noiseless_circuits_have_no_detection_events() {
+        assert_noiseless_circuit_is_silent(&GF4Stabilizers::steane_code());
+        assert_noiseless_circuit_is_silent(&GF4Stabilizers::five_qubit_code());
+        assert_noiseless_circuit_is_silent(&GF4Stabilizers::shor_code());
+    }
+
+    #[test]
+    fn number_of_detectors_and_observables() {
+        let circuit = SyndromeExtraction::from_code(&GF4Stabilizers::steane_code())
+            .with_n_rounds(4)
+            .build_circuit();
+        assert_eq!(circuit.get_detectors().len(), 3 + 3 * 6 + 3);
+        assert_eq!(circuit.get_observables().len(), 1);
+        assert_eq!(circuit.get_n_measurements(), 4 * 6 + 7);
+    }
+
+    #[test]
+    fn logicals_of_the_five_qubit_code() {
+        let extraction = SyndromeExtraction::from_code(&GF4Stabilizers::five_qubit_code());
+        assert_eq!(extraction.get_z_logicals(), vec![vec![0, 1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn schedules_are_validated() {
+        let code = GF4Stabilizers::from_sparse_paulis(
+            vec![
+                vec![(Pauli::X, 0), (Pauli::X, 1)],
+                vec![(Pauli::Z, 0), (Pauli::Z, 1)],
+            ],
+            2,
+        )
+        .unwrap();
+        let extraction = SyndromeExtraction::from_code(&code);
+
+        let valid = vec![vec![(0, 0)], vec![(0, 1), (1, 0)], vec![(1, 1)]];
+        assert!(extraction.clone().with_schedule(valid).is_ok());
+
+        let bad_ordering = vec![vec![(0, 0), (1, 1)], vec![(0, 1), (1, 0)]];
+        assert_eq!(
+            extraction.clone().with_schedule(bad_ordering).err(),
+            Some(ScheduleError::InvalidOrdering {
+                first: 0,
+                second: 1
+            })
+        );
+
+        let conflict = vec![vec![(0, 0), (1, 0)], vec![(0, 1), (1, 1)]];
+        assert_eq!(
+            extraction.clone().with_schedule(conflict).err(),
+            Some(ScheduleError::Conflict { layer: 0 })
+        );
+
+        let missing = vec![vec![(0, 0), (1, 1)], vec![(1, 0)]];
+        assert_eq!(
+            extraction.with_schedule(missing).err(),
+            Some(ScheduleError::MissingGate {
+                stabilizer: 0,
+                qubit: 1
+            })
+        );
+    }
+}
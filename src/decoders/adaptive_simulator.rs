@@ -0,0 +1,372 @@
+use super::n_iterations_simulator::NIterationsSimulator;
+use super::{Decoder, SimulationResult};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+// The default number of iterations simulated between two checks of the stopping criteria.
+const DEFAULT_BATCH_SIZE: u64 = 1000;
+
+/// The conditions under which an adaptive simulation stops.
+///
+/// The simulation runs batches of iterations in parallel and stops after the first batch
+/// meeting any of the criteria. A maximal number of iterations or a time budget must be set.
+/// Otherwise, a decoder that never fails, for example with an error probability of 0, would
+/// never reach a target number of failures or a relative precision.
+///
+/// The missing fields of serialized criteria take their default values. Deserialization fails
+/// if the batch size is 0 or if the relative precision is not positive.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// # use std::time::Duration;
+/// let criteria = StoppingCriteria::new()
+///     .with_relative_precision(0.1)
+///     .with_n_failures(100)
+///     .with_time_budget(Duration::from_secs(60));
+///
+/// let code = ParityCheckMatrix::repetition_code(3);
+/// let decoder = ErasureDecoder::with_prob(0.25).for_code(code);
+/// let result = decoder.simulate_until(&criteria);
+///
+/// assert!(
+///     result.get_relative_precision() <= 0.1 || result.get_n_failures() >= 100
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SerializedStoppingCriteria")]
+pub struct StoppingCriteria {
+    relative_precision: Option<f64>,
+    n_failures: Option<u64>,
+    time_budget: Option<Duration>,
+    max_iterations: Option<u64>,
+    batch_size: u64,
+}
+
+impl StoppingCriteria {
+    // ***** Construction *****
+
+    /// Creates stopping criteria without any criterion and with a batch size of 1000.
+    pub fn new() -> Self {
+        Self {
+            relative_precision: None,
+            n_failures: None,
+            time_budget: None,
+            max_iterations: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Stops when the standard error of the failure rate relative to the failure rate is at
+    /// most `relative_precision`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `relative_precision` is not positive.
+    pub fn with_relative_precision(mut self, relative_precision: f64) -> Self {
+        if relative_precision <= 0.0 {
+            panic!("relative precision must be positive");
+        }
+        self.relative_precision = Some(relative_precision);
+        self
+    }
+
+    /// Stops when at least `n_failures` are found.
+    pub fn with_n_failures(mut self, n_failures: u64) -> Self {
+        self.n_failures = Some(n_failures);
+        self
+    }
+
+    /// Stops when the simulation ran for at least `time_budget`.
+    ///
+    /// The number of simulated iterations then depends on the speed of the machine. Thus, the
+    /// results are not reproducible even with a seeded random number generator.
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Stops when at least `max_iterations` are simulated.
+    pub fn with_max_iterations(mut self, max_iterations: u64) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    /// Sets the number of iterations simulated between two checks of the criteria. The
+    /// default is 1000.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        if batch_size == 0 {
+            panic!("batch size must be positive");
+        }
+        self.batch_size = batch_size;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the target relative precision of the failure rate if any.
+    pub fn get_relative_precision(&self) -> Option<f64> {
+        self.relative_precision
+    }
+
+    /// Returns the target number of failures if any.
+    pub fn get_n_failures(&self) -> Option<u64> {
+        self.n_failures
+    }
+
+    /// Returns the time budget if any.
+    pub fn get_time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
+    /// Returns the maximal number of iterations if any.
+    pub fn get_max_iterations(&self) -> Option<u64> {
+        self.max_iterations
+    }
+
+    /// Returns the number of iterations simulated between two checks of the criteria.
+    pub fn get_batch_size(&self) -> u64 {
+        self.batch_size
+    }
+
+    // ***** Checkers *****
+
+    /// Checks if at least one criterion is set.
+    pub fn has_a_criterion(&self) -> bool {
+        self.relative_precision.is_some()
+            || self.n_failures.is_some()
+            || self.time_budget.is_some()
+            || self.max_iterations.is_some()
+    }
+
+    /// Checks if a maximal number of iterations or a time budget is set. This ensures that any
+    /// simulation stops.
+    pub fn is_bounded(&self) -> bool {
+        self.time_budget.is_some() || self.max_iterations.is_some()
+    }
+
+    /// Checks if a simulation with the given `result` running for `elapsed` time should stop.
+    pub fn is_met_by(&self, result: &SimulationResult, elapsed: Duration) -> bool {
+        matches!(self.relative_precision, Some(precision) if result.get_relative_precision() <= precision)
+            || matches!(self.n_failures, Some(n_failures) if result.get_n_failures() >= n_failures)
+            || matches!(self.time_budget, Some(budget) if elapsed >= budget)
+            || matches!(self.max_iterations, Some(max) if result.get_n_iterations() >= max)
+    }
+}
+
+impl Default for StoppingCriteria {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The serialized form of stopping criteria, checked before being converted to criteria.
+#[derive(Deserialize)]
+#[serde(default)]
+struct SerializedStoppingCriteria {
+    relative_precision: Option<f64>,
+    n_failures: Option<u64>,
+    time_budget: Option<Duration>,
+    max_iterations: Option<u64>,
+    batch_size: u64,
+}
+
+impl Default for SerializedStoppingCriteria {
+    fn default() -> Self {
+        Self {
+            relative_precision: None,
+            n_failures: None,
+            time_budget: None,
+            max_iterations: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl TryFrom<SerializedStoppingCriteria> for StoppingCriteria {
+    type Error = String;
+
+    fn try_from(criteria: SerializedStoppingCriteria) -> Result<Self, Self::Error> {
+        if criteria.batch_size == 0 {
+            return Err(String::from("batch size must be positive"));
+        }
+        if matches!(criteria.relative_precision, Some(precision) if precision <= 0.0) {
+            return Err(String::from("relative precision must be positive"));
+        }
+        Ok(Self {
+            relative_precision: criteria.relative_precision,
+            n_failures: criteria.n_failures,
+            time_budget: criteria.time_budget,
+            max_iterations: criteria.max_iterations,
+            batch_size: criteria.batch_size,
+        })
+    }
+}
+
+pub(super) struct AdaptiveSimulator<'a, D: Decoder> {
+    decoder: &'a D,
+    result: SimulationResult,
+}
+
+impl<'a, D: Decoder> AdaptiveSimulator<'a, D> {
+    pub(super) fn from(decoder: &'a D) -> Self {
+        Self {
+            decoder,
+            result: SimulationResult::new(),
+        }
+    }
+
+    pub(super) fn simulate_until_with_rng<R: Rng>(
+        mut self,
+        criteria: &StoppingCriteria,
+        rng: &mut R,
+    ) -> Self {
+        if !criteria.is_bounded() {
+            panic!("no maximal number of iterations or time budget");
+        }
+        if criteria.get_batch_size() == 0 {
+            panic!("batch size must be positive");
+        }
+        let start = Instant::now();
+        while !criteria.is_met_by(&self.result, start.elapsed()) {
+            let batch_size = self.get_next_batch_size(criteria);
+            let batch = NIterationsSimulator::from(self.decoder)
+                .simulate_n_iterations_with_rng(batch_size as usize, rng)
+                .get_result();
+            self.result = self.result.combine_with(batch);
+        }
+        self
+    }
+
+    // The last batch is truncated to not exceed the maximal number of iterations.
+    fn get_next_batch_size(&self, criteria: &StoppingCriteria) -> u64 {
+        match criteria.get_max_iterations() {
+            Some(max) => criteria
+                .get_batch_size()
+                .min(max - self.result.get_n_iterations()),
+            None => criteria.get_batch_size(),
+        }
+    }
+
+    pub(super) fn get_result(&self) -> SimulationResult {
+        self.result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ErasureDecoder;
+    use super::*;
+    use crate::ParityCheckMatrix;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn get_decoder() -> ErasureDecoder {
+        ErasureDecoder::with_prob(0.3).for_code(ParityCheckMatrix::hamming_code(3))
+    }
+
+    #[test]
+    fn stops_at_the_target_number_of_failures() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let criteria = StoppingCriteria::new()
+            .with_n_failures(50)
+            .with_max_iterations(1_000_000)
+            .with_batch_size(10);
+        let result = AdaptiveSimulator::from(&get_decoder())
+            .simulate_until_with_rng(&criteria, &mut rng)
+            .get_result();
+        assert!(result.get_n_failures() >= 50);
+        assert_eq!(result.get_n_iterations() % 10, 0);
+    }
+
+    #[test]
+    fn stops_at_the_target_relative_precision() {
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let criteria = StoppingCriteria::new()
+            .with_relative_precision(0.05)
+            .with_max_iterations(1_000_000);
+        let result = AdaptiveSimulator::from(&get_decoder())
+            .simulate_until_with_rng(&criteria, &mut rng)
+            .get_result();
+        assert!(result.get_relative_precision() <= 0.05);
+    }
+
+    #[test]
+    fn never_exceeds_the_maximal_number_of_iterations() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let criteria = StoppingCriteria::new()
+            .with_n_failures(u64::MAX)
+            .with_max_iterations(2500);
+        let result = AdaptiveSimulator::from(&get_decoder())
+            .simulate_until_with_rng(&criteria, &mut rng)
+            .get_result();
+        assert_eq!(result.get_n_iterations(), 2500);
+    }
+
+    #[test]
+    fn stops_when_the_time_budget_is_exhausted() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let criteria = StoppingCriteria::new().with_time_budget(Duration::from_secs(0));
+        let result = AdaptiveSimulator::from(&get_decoder())
+            .simulate_until_with_rng(&criteria, &mut rng)
+            .get_result();
+        assert_eq!(result.get_n_iterations(), 0);
+    }
+
+    #[test]
+    fn is_reproducible_without_time_budget() {
+        let criteria = StoppingCriteria::new()
+            .with_n_failures(100)
+            .with_max_iterations(1_000_000);
+        let results: Vec<SimulationResult> = (0..2)
+            .map(|_| {
+                let mut rng = ChaCha8Rng::seed_from_u64(5);
+                AdaptiveSimulator::from(&get_decoder())
+                    .simulate_until_with_rng(&criteria, &mut rng)
+                    .get_result()
+            })
+            .collect();
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_without_criterion() {
+        let mut rng = ChaCha8Rng::seed_from_u64(6);
+        AdaptiveSimulator::from(&get_decoder())
+            .simulate_until_with_rng(&StoppingCriteria::new(), &mut rng);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_without_maximal_number_of_iterations_or_time_budget() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let criteria = StoppingCriteria::new()
+            .with_n_failures(10)
+            .with_relative_precision(0.1);
+        AdaptiveSimulator::from(&get_decoder()).simulate_until_with_rng(&criteria, &mut rng);
+    }
+
+    #[test]
+    fn deserialized_criteria_are_checked() {
+        let criteria: StoppingCriteria =
+            serde_json::from_str(r#"{"n_failures": 10, "max_iterations": 100}"#).unwrap();
+        assert_eq!(
+            criteria,
+            StoppingCriteria::new()
+                .with_n_failures(10)
+                .with_max_iterations(100)
+        );
+        assert!(serde_json::from_str::<StoppingCriteria>(r#"{"batch_size": 0}"#).is_err());
+        assert!(
+            serde_json::from_str::<StoppingCriteria>(r#"{"relative_precision": 0.0}"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use super::{BitErrorStatistics, Decoder};
+use rand::distributions::Standard;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+pub(super) struct BitErrorSimulator<'a, D: Decoder> {
+    decoder: &'a D,
+    statistics: BitErrorStatistics,
+    random_seeds: Vec<u64>,
+}
+
+impl<'a, D: Decoder> BitErrorSimulator<'a, D> {
+    pub(super) fn from(decoder: &'a D) -> Self {
+        Self {
+            decoder,
+            statistics: BitErrorStatistics::new(),
+            random_seeds: Vec::new(),
+        }
+    }
+
+    pub(super) fn simulate_n_iterations_with_rng<R: Rng>(
+        mut self,
+        n_iterations: usize,
+        rng: &mut R,
+    ) -> Self {
+        self.random_seeds = rng.sample_iter(Standard).take(n_iterations).collect();
+        self.run_the_simulation();
+        self
+    }
+
+    fn run_the_simulation(&mut self) {
+        let seeds = &self.random_seeds;
+        self.statistics = (0..seeds.len())
+            .into_par_iter()
+            .map_init(
+                || self.decoder.clone(),
+                |decoder, index| {
+                    let mut rng = ChaCha8Rng::seed_from_u64(seeds[index]);
+                    let error = decoder.get_random_error_with_rng(&mut rng);
+                    let result = decoder.decode(&error);
+                    let residual = decoder
+                        .get_residual_errors(&error, &result)
+                        .expect("the decoder doesn't report residual errors");
+                    let mut statistics = BitErrorStatistics::new();
+                    statistics.add_decoding_result(result, &residual);
+                    statistics
+                },
+            )
+            .reduce(BitErrorStatistics::new, |first, second| {
+                first.combine_with(&second)
+            });
+    }
+
+    pub(super) fn get_statistics(self) -> BitErrorStatistics {
+        self.statistics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ErasureDecoder;
+    use super::*;
+    use crate::ParityCheckMatrix;
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_threads() {
+        let code = ParityCheckMatrix::hamming_code(4);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let statistics: Vec<BitErrorStatistics> = [1, 4]
+            .iter()
+            .map(|n_threads| {
+                let mut rng = ChaCha8Rng::seed_from_u64(7);
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(*n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| {
+                        BitErrorSimulator::from(&decoder)
+                            .simulate_n_iterations_with_rng(500, &mut rng)
+                            .get_statistics()
+                    })
+            })
+            .collect();
+        assert_eq!(statistics[0], statistics[1]);
+        assert_eq!(statistics[0].get_result().get_n_iterations(), 500);
+        assert_eq!(statistics[0].get_n_bits(), 15);
+    }
+}
@@ -0,0 +1,222 @@
+use super::{DecodingResult, SimulationResult};
+use serde::Serialize;
+
+/// The bits that are still wrong or erased after a decoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResidualErrors {
+    n_bits: usize,
+    bits: Vec<usize>,
+}
+
+impl ResidualErrors {
+    /// Creates the residual errors of a word of `n_bits` given the positions of its wrong or
+    /// erased `bits`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if a bit is out of bound.
+    pub fn new(n_bits: usize, bits: Vec<usize>) -> Self {
+        if bits.iter().any(|bit| *bit >= n_bits) {
+            panic!("bit out of bound");
+        }
+        Self { n_bits, bits }
+    }
+
+    /// Returns the number of bits of the decoded word.
+    pub fn get_n_bits(&self) -> usize {
+        self.n_bits
+    }
+
+    /// Returns the positions of the wrong or erased bits.
+    pub fn get_bits(&self) -> &[usize] {
+        &self.bits
+    }
+
+    /// Returns the number of wrong or erased bits.
+    pub fn get_weight(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+/// Bit-level statistics of a simulation.
+///
+/// On top of the frame-level `SimulationResult`, it keeps the total number of residual bits,
+/// the number of failures of each residual weight and the number of failures involving each
+/// bit.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let mut statistics = BitErrorStatistics::new();
+/// statistics.add_decoding_result(ErasureResult::Success, &ResidualErrors::new(4, vec![]));
+/// statistics.add_decoding_result(ErasureResult::Failure, &ResidualErrors::new(4, vec![1, 3]));
+///
+/// assert_eq!(statistics.get_frame_error_rate(), 0.5);
+/// assert_eq!(statistics.get_bit_error_rate(), 0.25);
+/// assert_eq!(statistics.get_failure_weight_distribution(), &[0, 0, 1]);
+/// assert_eq!(statistics.get_bit_failure_counts(), &[0, 1, 0, 1]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct BitErrorStatistics {
+    result: SimulationResult,
+    n_residual_bits: u64,
+    failure_weights: Vec<u64>,
+    bit_failures: Vec<u64>,
+}
+
+impl BitErrorStatistics {
+    // ***** Construction *****
+
+    /// Creates empty statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // ***** Updaters *****
+
+    /// Adds a decoding `result` with its `residual` errors.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the number of bits of `residual` is not the one of the previous results.
+    pub fn add_decoding_result<D: DecodingResult>(&mut self, result: D, residual: &ResidualErrors) {
+        self.resize_for(residual.get_n_bits());
+        let is_failure = result.is_failure();
+        self.result.add_decoding_result(result);
+        self.n_residual_bits += residual.get_weight() as u64;
+        if is_failure {
+            if self.failure_weights.len() <= residual.get_weight() {
+                self.failure_weights.resize(residual.get_weight() + 1, 0);
+            }
+            self.failure_weights[residual.get_weight()] += 1;
+            residual
+                .get_bits()
+                .iter()
+                .for_each(|bit| self.bit_failures[*bit] += 1);
+        }
+    }
+
+    /// Returns the statistics of both `self` and `other`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the statistics are not for the same number of bits.
+    pub fn combine_with(&self, other: &Self) -> Self {
+        let mut combined = self.clone();
+        if other.result.get_n_iterations() > 0 {
+            combined.resize_for(other.get_n_bits());
+        }
+        combined.result = combined.result.combine_with(other.result);
+        combined.n_residual_bits += other.n_residual_bits;
+        if combined.failure_weights.len() < other.failure_weights.len() {
+            combined
+                .failure_weights
+                .resize(other.failure_weights.len(), 0);
+        }
+        add_counts(&mut combined.failure_weights, &other.failure_weights);
+        add_counts(&mut combined.bit_failures, &other.bit_failures);
+        combined
+    }
+
+    // Empty statistics take the number of bits of their first result.
+    fn resize_for(&mut self, n_bits: usize) {
+        if self.result.get_n_iterations() == 0 {
+            self.bit_failures = vec![0; n_bits];
+        } else if n_bits != self.get_n_bits() {
+            panic!("different number of bits");
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the frame-level result.
+    pub fn get_result(&self) -> SimulationResult {
+        self.result
+    }
+
+    /// Returns the number of bits of the decoded words.
+    pub fn get_n_bits(&self) -> usize {
+        self.bit_failures.len()
+    }
+
+    /// Returns the fraction of decodings that failed.
+    pub fn get_frame_error_rate(&self) -> f64 {
+        self.result.get_failure_rate()
+    }
+
+    /// Returns the fraction of decoded bits that are wrong or erased.
+    pub fn get_bit_error_rate(&self) -> f64 {
+        self.n_residual_bits as f64
+            / (self.result.get_n_iterations() as f64 * self.get_n_bits() as f64)
+    }
+
+    /// Returns the number of failures with `w` residual bits at index `w`.
+    pub fn get_failure_weight_distribution(&self) -> &[u64] {
+        &self.failure_weights
+    }
+
+    /// Returns the number of failures where each bit is wrong or erased.
+    pub fn get_bit_failure_counts(&self) -> &[u64] {
+        &self.bit_failures
+    }
+
+    /// Returns the bits sorted from the most to the least involved in failures.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use believer::*;
+    /// let mut statistics = BitErrorStatistics::new();
+    /// statistics.add_decoding_result(ErasureResult::Failure, &ResidualErrors::new(3, vec![1, 2]));
+    /// statistics.add_decoding_result(ErasureResult::Failure, &ResidualErrors::new(3, vec![2]));
+    ///
+    /// assert_eq!(statistics.get_weakest_bits(), vec![2, 1, 0]);
+    /// ```
+    pub fn get_weakest_bits(&self) -> Vec<usize> {
+        let mut bits: Vec<usize> = (0..self.get_n_bits()).collect();
+        bits.sort_by_key(|bit| std::cmp::Reverse(self.bit_failures[*bit]));
+        bits
+    }
+}
+
+fn add_counts(counts: &mut [u64], other: &[u64]) {
+    counts
+        .iter_mut()
+        .zip(other.iter())
+        .for_each(|(count, other)| *count += other);
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::ErasureResult;
+    use super::*;
+
+    #[test]
+    fn combination_adds_all_counts() {
+        let mut first = BitErrorStatistics::new();
+        first.add_decoding_result(ErasureResult::Failure, &ResidualErrors::new(3, vec![0]));
+        let mut second = BitErrorStatistics::new();
+        second.add_decoding_result(ErasureResult::Failure, &ResidualErrors::new(3, vec![0, 2]));
+        second.add_decoding_result(ErasureResult::Success, &ResidualErrors::new(3, vec![]));
+
+        let combined = first.combine_with(&second);
+        assert_eq!(
+            combined.get_result(),
+            SimulationResult::with_n_successes_and_failures(1, 2)
+        );
+        assert_eq!(combined.get_failure_weight_distribution(), &[0, 1, 1]);
+        assert_eq!(combined.get_bit_failure_counts(), &[2, 0, 1]);
+        assert!((combined.get_bit_error_rate() - 3.0 / 9.0).abs() < 1e-12);
+        assert_eq!(combined, second.combine_with(&first));
+        assert_eq!(first.combine_with(&BitErrorStatistics::new()), first);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_different_number_of_bits() {
+        let mut statistics = BitErrorStatistics::new();
+        statistics.add_decoding_result(ErasureResult::Success, &ResidualErrors::new(3, vec![]));
+        statistics.add_decoding_result(ErasureResult::Success, &ResidualErrors::new(4, vec![]));
+    }
+}
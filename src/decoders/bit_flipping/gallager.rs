@@ -0,0 +1,247 @@
+//! Gallager's hard-decision message-passing decoders.
+
+use super::{
+    get_random_word_with_rng, get_word_log_probability, BitFlippingResult, DEFAULT_MAX_ITERATIONS,
+};
+use crate::decoders::{Decoder, ResidualErrors};
+use crate::{ParityCheckMatrix, GF2};
+use rand::Rng;
+
+/// The rule used by a bit of a `GallagerDecoder` to send a value that differs from its received
+/// value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GallagerAlgorithm {
+    /// A bit sends the opposite of its received value to a check if all its other checks
+    /// disagree with the received value.
+    A,
+    /// A bit sends the opposite of its received value to a check if at least `threshold` of its
+    /// other checks disagree with the received value.
+    B { threshold: usize },
+}
+
+/// Decoder for the binary symmetric channel passing binary messages between the bits and the
+/// checks.
+///
+/// Each check sends to each of its bits the value making the check satisfied given the
+/// messages of its other bits. Each bit sends to each of its checks either its received value
+/// or the opposite value according to the `GallagerAlgorithm`. After each iteration, the value
+/// of a bit is the majority between its received value and the messages of its checks with
+/// ties broken by the received value.
+///
+/// The decoding stops when the decoded word is a codeword or after the maximal number of
+/// iterations. The random errors are the words received when the all-zero codeword is sent
+/// through the channel.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::repetition_code(5);
+/// let mut decoder = GallagerDecoder::with_prob(0.05)
+///     .with_algorithm(GallagerAlgorithm::B { threshold: 1 })
+///     .for_code(code);
+///
+/// let mut word = vec![GF2::B0; 5];
+/// word[2] = GF2::B1;
+/// let result = decoder.decode(&word);
+/// assert!(result.is_success());
+/// assert_eq!(result.get_n_iterations(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GallagerDecoder {
+    code: ParityCheckMatrix,
+    edges_of_bits: Vec<Vec<usize>>,
+    prob: f64,
+    algorithm: GallagerAlgorithm,
+    max_iterations: usize,
+}
+
+impl GallagerDecoder {
+    // ***** Construction *****
+
+    /// Creates a Gallager A decoder for an empty code and a binary symmetric channel of error
+    /// probability `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 1.0.
+    pub fn with_prob(prob: f64) -> Self {
+        if !(0.0..=1.0).contains(&prob) {
+            panic!("invalid probability");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            edges_of_bits: Vec::new(),
+            prob,
+            algorithm: GallagerAlgorithm::A,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Sets the rule used by the bits to send messages. The default is Gallager A.
+    pub fn with_algorithm(mut self, algorithm: GallagerAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the maximal number of iterations. The default is 50.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the rule used by the bits to send messages.
+    pub fn get_algorithm(&self) -> GallagerAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the maximal number of iterations.
+    pub fn get_max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    // Checks if a bit with `n_disagreements` among its `n_others` other checks sends the
+    // opposite of its received value.
+    fn flips_message(&self, n_disagreements: usize, n_others: usize) -> bool {
+        match self.algorithm {
+            GallagerAlgorithm::A => n_others > 0 && n_disagreements == n_others,
+            GallagerAlgorithm::B { threshold } => n_disagreements >= threshold.max(1),
+        }
+    }
+}
+
+impl Decoder for GallagerDecoder {
+    type Error = Vec<GF2>; // The received word.
+    type Result = BitFlippingResult;
+    type Code = ParityCheckMatrix;
+
+    fn for_code(mut self, code: Self::Code) -> Self {
+        // The edges are numbered check by check.
+        let mut edges_of_bits = vec![Vec::new(); code.get_n_bits()];
+        code.edges_iter()
+            .enumerate()
+            .for_each(|(edge, (_, bit))| edges_of_bits[bit].push(edge));
+        self.edges_of_bits = edges_of_bits;
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.edges_of_bits = Vec::new();
+        std::mem::take(&mut self.code)
+    }
+
+    /// # Panic
+    ///
+    /// Panics if the length of the received word is not the number of bits.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        if error.len() != self.code.get_n_bits() {
+            panic!("wrong word length");
+        }
+        let mut bit_messages: Vec<GF2> =
+            self.code.edges_iter().map(|(_, bit)| error[bit]).collect();
+        let mut check_messages = vec![GF2::B0; bit_messages.len()];
+        let mut word = error.clone();
+        let mut n_iterations = 0;
+
+        while n_iterations < self.max_iterations && !self.code.has_codeword(&word) {
+            n_iterations += 1;
+
+            // Check update.
+            let mut edge = 0;
+            for check in self.code.checks_iter() {
+                let degree = check.get_n_bits();
+                let total = bit_messages[edge..edge + degree]
+                    .iter()
+                    .fold(GF2::B0, |total, message| total + *message);
+                for offset in edge..edge + degree {
+                    check_messages[offset] = total + bit_messages[offset];
+                }
+                edge += degree;
+            }
+
+            // Bit update and decision.
+            for (bit, edges) in self.edges_of_bits.iter().enumerate() {
+                let disagrees = |edge: &usize| check_messages[*edge] != error[bit];
+                let n_disagreements = edges.iter().filter(|edge| disagrees(edge)).count();
+                for edge in edges.iter() {
+                    let n_others = edges.len() - 1;
+                    let n_other_disagreements = n_disagreements - disagrees(edge) as usize;
+                    bit_messages[*edge] = if self.flips_message(n_other_disagreements, n_others) {
+                        error[bit] + GF2::B1
+                    } else {
+                        error[bit]
+                    };
+                }
+                word[bit] = if 2 * n_disagreements > edges.len() + 1 {
+                    error[bit] + GF2::B1
+                } else {
+                    error[bit]
+                };
+            }
+        }
+        BitFlippingResult::new(&self.code, word, n_iterations)
+    }
+
+    fn get_residual_errors(
+        &self,
+        _error: &Self::Error,
+        result: &Self::Result,
+    ) -> Option<ResidualErrors> {
+        Some(result.get_residual_errors())
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(get_word_log_probability(error, self.prob))
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        get_random_word_with_rng(self.code.get_n_bits(), self.prob, rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{get_test_code, get_word_with_errors};
+    use super::*;
+    use crate::DecodingResult;
+
+    #[test]
+    fn single_errors_are_corrected_by_both_algorithms() {
+        let code = get_test_code();
+        for algorithm in [GallagerAlgorithm::A, GallagerAlgorithm::B { threshold: 2 }].iter() {
+            let mut decoder = GallagerDecoder::with_prob(0.01)
+                .with_algorithm(*algorithm)
+                .for_code(code.clone());
+            for bit in 0..code.get_n_bits() {
+                let result = decoder.decode(&get_word_with_errors(code.get_n_bits(), &[bit]));
+                assert!(result.is_success());
+                assert_eq!(result.get_syndrome_weight(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn codewords_need_no_iteration() {
+        let code = ParityCheckMatrix::repetition_code(4);
+        let mut decoder = GallagerDecoder::with_prob(0.1).for_code(code);
+        let result = decoder.decode(&vec![GF2::B1; 4]);
+        assert_eq!(result.get_n_iterations(), 0);
+        assert!(result.is_codeword());
+        assert!(result.is_failure());
+    }
+
+    #[test]
+    fn decoding_stops_after_max_iterations() {
+        // Bits of degree 1 never change their messages with Gallager A.
+        let code = ParityCheckMatrix::with_n_bits(2).with_checks(vec![vec![0, 1]]);
+        let mut decoder = GallagerDecoder::with_prob(0.1)
+            .with_max_iterations(3)
+            .for_code(code);
+        let result = decoder.decode(&vec![GF2::B1, GF2::B0]);
+        assert_eq!(result.get_n_iterations(), 3);
+        assert_eq!(result.get_syndrome_weight(), 1);
+    }
+}
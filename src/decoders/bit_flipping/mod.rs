@@ -0,0 +1,362 @@
+//! Hard-decision decoders flipping the bits involved in many unsatisfied checks.
+//!
+//! The implementations are based on "Low-Density Parity-Check Codes, Robert G. Gallager, 1963,
+//! MIT Press" and "Low-density parity-check codes based on finite geometries: a rediscovery and
+//! new results, Y. Kou, S. Lin and M. P. C. Fossorier, 2001, IEEE Transactions on Information
+//! Theory".
+
+use super::statistics::bernoulli_log_probability;
+use super::{Decoder, DecodingResult, ResidualErrors};
+use crate::{ParityCheckMatrix, GF2};
+use rand::Rng;
+
+mod gallager;
+pub use gallager::*;
+
+mod weighted;
+pub use weighted::*;
+
+// The default maximal number of iterations of the decoders.
+const DEFAULT_MAX_ITERATIONS: usize = 50;
+
+/// The order in which a `BitFlippingDecoder` flips bits.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BitFlippingSchedule {
+    /// Flips all bits involved in more unsatisfied checks than satisfied checks at each
+    /// iteration.
+    Parallel,
+    /// Flips a single bit maximizing the difference between its numbers of unsatisfied and
+    /// satisfied checks at each iteration.
+    Serial,
+}
+
+/// Decoder for the binary symmetric channel flipping the bits involved in a majority of
+/// unsatisfied checks.
+///
+/// The decoding stops when the syndrome is zero, when no bit is involved in a majority of
+/// unsatisfied checks or after the maximal number of iterations.
+///
+/// The random errors are the words received when the all-zero codeword is sent through the
+/// channel. Thus, the decoding succeeds if the word is decoded to the all-zero codeword.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::repetition_code(5);
+/// let mut decoder = BitFlippingDecoder::with_prob(0.1)
+///     .with_schedule(BitFlippingSchedule::Serial)
+///     .for_code(code);
+///
+/// let result = decoder.decode(&vec![GF2::B0, GF2::B1, GF2::B0, GF2::B0, GF2::B1]);
+/// assert!(result.is_success());
+/// assert_eq!(result.get_n_iterations(), 2);
+/// assert_eq!(result.get_syndrome_weight(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitFlippingDecoder {
+    code: ParityCheckMatrix,
+    checks_of_bits: ParityCheckMatrix,
+    prob: f64,
+    schedule: BitFlippingSchedule,
+    max_iterations: usize,
+}
+
+/// The result of a bit-flipping, Gallager or weighted bit-flipping decoder.
+///
+/// The decoding succeeds if the decoded word is the all-zero codeword.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BitFlippingResult {
+    word: Vec<GF2>,
+    n_iterations: usize,
+    syndrome_weight: usize,
+}
+
+impl BitFlippingResult {
+    fn new(code: &ParityCheckMatrix, word: Vec<GF2>, n_iterations: usize) -> Self {
+        let syndrome_weight = get_syndrome_weight(code, &word);
+        Self {
+            word,
+            n_iterations,
+            syndrome_weight,
+        }
+    }
+
+    /// Returns the decoded word.
+    pub fn get_word(&self) -> &[GF2] {
+        &self.word
+    }
+
+    /// Returns the number of iterations used by the decoder.
+    pub fn get_n_iterations(&self) -> usize {
+        self.n_iterations
+    }
+
+    /// Returns the number of unsatisfied checks of the decoded word.
+    pub fn get_syndrome_weight(&self) -> usize {
+        self.syndrome_weight
+    }
+
+    /// Checks if the decoded word is a codeword.
+    pub fn is_codeword(&self) -> bool {
+        self.syndrome_weight == 0
+    }
+
+    /// Returns the non-zero bits of the decoded word. These are the bits decoded wrongly since
+    /// the all-zero codeword is sent.
+    pub fn get_residual_errors(&self) -> ResidualErrors {
+        let bits = self
+            .word
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value == GF2::B1)
+            .map(|(bit, _)| bit)
+            .collect();
+        ResidualErrors::new(self.word.len(), bits)
+    }
+}
+
+impl DecodingResult for BitFlippingResult {
+    fn is_success(&self) -> bool {
+        self.word.iter().all(|bit| *bit == GF2::B0)
+    }
+}
+
+impl BitFlippingDecoder {
+    // ***** Construction *****
+
+    /// Creates a parallel bit-flipping decoder for an empty code and a binary symmetric channel
+    /// of error probability `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 1.0.
+    pub fn with_prob(prob: f64) -> Self {
+        if !(0.0..=1.0).contains(&prob) {
+            panic!("invalid probability");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            checks_of_bits: ParityCheckMatrix::new(),
+            prob,
+            schedule: BitFlippingSchedule::Parallel,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Sets the order in which the bits are flipped. The default is parallel.
+    pub fn with_schedule(mut self, schedule: BitFlippingSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Sets the maximal number of iterations. The default is 50.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the order in which the bits are flipped.
+    pub fn get_schedule(&self) -> BitFlippingSchedule {
+        self.schedule
+    }
+
+    /// Returns the maximal number of iterations.
+    pub fn get_max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    // Returns the bits to flip given the number of unsatisfied checks of each bit.
+    fn get_bits_to_flip(&self, unsatisfied: &[usize]) -> Vec<usize> {
+        let excesses = unsatisfied
+            .iter()
+            .zip(self.checks_of_bits.checks_iter())
+            .map(|(unsatisfied, checks)| 2 * *unsatisfied as i64 - checks.get_n_bits() as i64);
+        match self.schedule {
+            BitFlippingSchedule::Parallel => excesses
+                .enumerate()
+                .filter(|(_, excess)| *excess > 0)
+                .map(|(bit, _)| bit)
+                .collect(),
+            BitFlippingSchedule::Serial => excesses
+                .enumerate()
+                .filter(|(_, excess)| *excess > 0)
+                .fold(
+                    None,
+                    |best: Option<(usize, i64)>, (bit, excess)| match best {
+                        Some((_, best_excess)) if best_excess >= excess => best,
+                        _ => Some((bit, excess)),
+                    },
+                )
+                .map(|(bit, _)| bit)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl Decoder for BitFlippingDecoder {
+    type Error = Vec<GF2>; // The received word.
+    type Result = BitFlippingResult;
+    type Code = ParityCheckMatrix;
+
+    fn for_code(mut self, code: Self::Code) -> Self {
+        self.checks_of_bits = code.get_transposed_matrix();
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.checks_of_bits = ParityCheckMatrix::new();
+        std::mem::take(&mut self.code)
+    }
+
+    /// # Panic
+    ///
+    /// Panics if the length of the received word is not the number of bits.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        if error.len() != self.code.get_n_bits() {
+            panic!("wrong word length");
+        }
+        let mut word = error.clone();
+        let mut n_iterations = 0;
+        while n_iterations < self.max_iterations {
+            let syndrome = self.code.get_syndrome_of(&word);
+            if syndrome.iter().all(|value| *value == GF2::B0) {
+                break;
+            }
+            let unsatisfied = get_unsatisfied_checks_of_bits(&self.checks_of_bits, &syndrome);
+            let bits = self.get_bits_to_flip(&unsatisfied);
+            if bits.is_empty() {
+                break;
+            }
+            bits.into_iter()
+                .for_each(|bit| word[bit] = word[bit] + GF2::B1);
+            n_iterations += 1;
+        }
+        BitFlippingResult::new(&self.code, word, n_iterations)
+    }
+
+    fn get_residual_errors(
+        &self,
+        _error: &Self::Error,
+        result: &Self::Result,
+    ) -> Option<ResidualErrors> {
+        Some(result.get_residual_errors())
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(get_word_log_probability(error, self.prob))
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        get_random_word_with_rng(self.code.get_n_bits(), self.prob, rng)
+    }
+}
+
+// Returns the number of unsatisfied checks of each bit.
+fn get_unsatisfied_checks_of_bits(
+    checks_of_bits: &ParityCheckMatrix,
+    syndrome: &[GF2],
+) -> Vec<usize> {
+    checks_of_bits
+        .checks_iter()
+        .map(|checks| {
+            checks
+                .iter()
+                .filter(|check| syndrome[**check] == GF2::B1)
+                .count()
+        })
+        .collect()
+}
+
+fn get_syndrome_weight(code: &ParityCheckMatrix, word: &[GF2]) -> usize {
+    code.get_syndrome_of(word)
+        .iter()
+        .filter(|value| **value == GF2::B1)
+        .count()
+}
+
+// Returns the word received when the all-zero codeword is sent through a binary symmetric
+// channel of error probability `prob`.
+pub(super) fn get_random_word_with_rng<R: Rng>(n_bits: usize, prob: f64, rng: &mut R) -> Vec<GF2> {
+    (0..n_bits)
+        .map(|_| {
+            if rng.gen::<f64>() < prob {
+                GF2::B1
+            } else {
+                GF2::B0
+            }
+        })
+        .collect()
+}
+
+// Returns the logarithm of the probability to receive `word` when the all-zero codeword is
+// sent through a binary symmetric channel of error probability `prob`.
+pub(super) fn get_word_log_probability(word: &[GF2], prob: f64) -> f64 {
+    let weight = word.iter().filter(|bit| **bit == GF2::B1).count();
+    bernoulli_log_probability(weight, word.len(), prob)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CodeGenerator, PEGCodeGenerator};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // A code with bit degree 3 and girth at least 6.
+    pub(super) fn get_test_code() -> ParityCheckMatrix {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        PEGCodeGenerator::new(vec![3; 48], 24).generate_with_rng(&mut rng)
+    }
+
+    pub(super) fn get_word_with_errors(n_bits: usize, bits: &[usize]) -> Vec<GF2> {
+        let mut word = vec![GF2::B0; n_bits];
+        bits.iter().for_each(|bit| word[*bit] = GF2::B1);
+        word
+    }
+
+    #[test]
+    fn single_errors_are_corrected_by_both_schedules() {
+        let code = get_test_code();
+        for schedule in [BitFlippingSchedule::Parallel, BitFlippingSchedule::Serial].iter() {
+            let mut decoder = BitFlippingDecoder::with_prob(0.01)
+                .with_schedule(*schedule)
+                .for_code(code.clone());
+            for bit in 0..code.get_n_bits() {
+                let result = decoder.decode(&get_word_with_errors(code.get_n_bits(), &[bit]));
+                assert!(result.is_success());
+                assert_eq!(result.get_n_iterations(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn decoder_stops_when_stuck() {
+        // Each bit of a cycle is in 1 satisfied and 1 unsatisfied check.
+        let code = ParityCheckMatrix::with_n_bits(4).with_checks(vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![2, 3],
+            vec![0, 3],
+        ]);
+        let mut decoder = BitFlippingDecoder::with_prob(0.1).for_code(code);
+        let result = decoder.decode(&vec![GF2::B1, GF2::B1, GF2::B0, GF2::B0]);
+        assert_eq!(result.get_n_iterations(), 0);
+        assert_eq!(result.get_syndrome_weight(), 2);
+        assert!(!result.is_codeword());
+        assert!(result.is_failure());
+        assert_eq!(result.get_residual_errors().get_bits(), &[0, 1]);
+    }
+
+    #[test]
+    fn simulation_on_the_binary_symmetric_channel() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let decoder = BitFlippingDecoder::with_prob(0.01).for_code(get_test_code());
+        let result = decoder.simulate_n_iterations_with_rng(200, &mut rng);
+        assert!(result.get_failure_rate() < 0.5);
+    }
+}
@@ -0,0 +1,230 @@
+//! Weighted bit flipping for soft channel outputs.
+
+use super::{BitFlippingResult, DEFAULT_MAX_ITERATIONS};
+use crate::decoders::{Decoder, ResidualErrors};
+use crate::{ParityCheckMatrix, GF2};
+use rand::Rng;
+
+/// Decoder for a BPSK modulation over an additive white gaussian noise channel flipping the
+/// least reliable bits of the unsatisfied checks.
+///
+/// A bit of value 0 is sent as `1.0` and a bit of value 1 as `-1.0`. The received values are
+/// the sent values plus a gaussian noise of standard deviation `noise`. The hard decision of a
+/// bit is 1 if its received value is negative.
+///
+/// The reliability of a check is the smallest magnitude of the received values of its bits. At
+/// each iteration, the decoder flips the bit maximizing the sum of the reliabilities of its
+/// unsatisfied checks minus the sum of the reliabilities of its satisfied checks. The decoding
+/// stops when the decoded word is a codeword or after the maximal number of iterations.
+///
+/// The random errors are the values received when the all-zero codeword is sent. Thus, the
+/// decoding succeeds if the word is decoded to the all-zero codeword.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::repetition_code(3);
+/// let mut decoder = WeightedBitFlippingDecoder::with_noise(0.5).for_code(code);
+///
+/// // The middle bit is the least reliable.
+/// let result = decoder.decode(&vec![0.9, -0.1, 1.2]);
+/// assert!(result.is_success());
+/// assert_eq!(result.get_n_iterations(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeightedBitFlippingDecoder {
+    code: ParityCheckMatrix,
+    checks_of_bits: ParityCheckMatrix,
+    noise: f64,
+    max_iterations: usize,
+}
+
+impl WeightedBitFlippingDecoder {
+    // ***** Construction *****
+
+    /// Creates a weighted bit-flipping decoder for an empty code and a channel with a gaussian
+    /// noise of standard deviation `noise`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `noise` is negative.
+    pub fn with_noise(noise: f64) -> Self {
+        if noise < 0.0 {
+            panic!("negative noise");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            checks_of_bits: ParityCheckMatrix::new(),
+            noise,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    /// Sets the maximal number of iterations. The default is 50.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the standard deviation of the channel noise.
+    pub fn get_noise(&self) -> f64 {
+        self.noise
+    }
+
+    /// Returns the maximal number of iterations.
+    pub fn get_max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    fn get_check_reliabilities(&self, values: &[f64]) -> Vec<f64> {
+        self.code
+            .checks_iter()
+            .map(|check| {
+                check
+                    .iter()
+                    .map(|bit| values[*bit].abs())
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect()
+    }
+}
+
+impl Decoder for WeightedBitFlippingDecoder {
+    type Error = Vec<f64>; // The received values.
+    type Result = BitFlippingResult;
+    type Code = ParityCheckMatrix;
+
+    fn for_code(mut self, code: Self::Code) -> Self {
+        self.checks_of_bits = code.get_transposed_matrix();
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.checks_of_bits = ParityCheckMatrix::new();
+        std::mem::take(&mut self.code)
+    }
+
+    /// # Panic
+    ///
+    /// Panics if the number of received values is not the number of bits.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        if error.len() != self.code.get_n_bits() {
+            panic!("wrong number of values");
+        }
+        let reliabilities = self.get_check_reliabilities(error);
+        let mut word: Vec<GF2> = error
+            .iter()
+            .map(|value| if *value < 0.0 { GF2::B1 } else { GF2::B0 })
+            .collect();
+        let mut n_iterations = 0;
+        while n_iterations < self.max_iterations {
+            let syndrome = self.code.get_syndrome_of(&word);
+            if syndrome.iter().all(|value| *value == GF2::B0) {
+                break;
+            }
+            let flipped = self
+                .checks_of_bits
+                .checks_iter()
+                .map(|checks| {
+                    checks
+                        .iter()
+                        .map(|check| match syndrome[*check] {
+                            GF2::B1 => reliabilities[*check],
+                            GF2::B0 => -reliabilities[*check],
+                        })
+                        .sum::<f64>()
+                })
+                .enumerate()
+                .fold(
+                    None,
+                    |best: Option<(usize, f64)>, (bit, metric)| match best {
+                        Some((_, best_metric)) if best_metric >= metric => best,
+                        _ => Some((bit, metric)),
+                    },
+                );
+            match flipped {
+                Some((bit, _)) => word[bit] = word[bit] + GF2::B1,
+                None => break,
+            }
+            n_iterations += 1;
+        }
+        BitFlippingResult::new(&self.code, word, n_iterations)
+    }
+
+    fn get_residual_errors(
+        &self,
+        _error: &Self::Error,
+        result: &Self::Result,
+    ) -> Option<ResidualErrors> {
+        Some(result.get_residual_errors())
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        let variance = self.noise * self.noise;
+        Some(
+            error
+                .iter()
+                .map(|value| {
+                    -(value - 1.0).powi(2) / (2.0 * variance)
+                        - 0.5 * (2.0 * std::f64::consts::PI * variance).ln()
+                })
+                .sum(),
+        )
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        (0..self.code.get_n_bits())
+            .map(|_| 1.0 + self.noise * sample_standard_gaussian(rng))
+            .collect()
+    }
+}
+
+// Samples a gaussian of mean 0 and variance 1 with the Box-Muller transform.
+fn sample_standard_gaussian<R: Rng>(rng: &mut R) -> f64 {
+    let radius = (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt();
+    let angle = 2.0 * std::f64::consts::PI * rng.gen::<f64>();
+    radius * angle.cos()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::get_test_code;
+    use super::*;
+    use crate::DecodingResult;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn single_unreliable_errors_are_corrected() {
+        let code = get_test_code();
+        let mut decoder = WeightedBitFlippingDecoder::with_noise(0.5).for_code(code.clone());
+        for bit in 0..code.get_n_bits() {
+            let mut values = vec![1.0; code.get_n_bits()];
+            values[bit] = -0.2;
+            let result = decoder.decode(&values);
+            assert!(result.is_success());
+            assert_eq!(result.get_n_iterations(), 1);
+        }
+    }
+
+    #[test]
+    fn random_values_have_the_right_noise() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let decoder = WeightedBitFlippingDecoder::with_noise(0.5).for_code(get_test_code());
+        let values: Vec<f64> = (0..200)
+            .flat_map(|_| decoder.get_random_error_with_rng(&mut rng))
+            .collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / values.len() as f64;
+        assert!((mean - 1.0).abs() < 0.05);
+        assert!((variance.sqrt() - 0.5).abs() < 0.05);
+    }
+}
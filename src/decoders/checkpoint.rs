@@ -0,0 +1,269 @@
+//! Checkpoints to save and resume long simulations.
+
+use super::n_iterations_simulator::NIterationsSimulator;
+use super::{Decoder, SimulationResult};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The state of a simulation running by batches of iterations.
+///
+/// The `parameters` describe the simulation, for example the code and the error probability of
+/// the decoder. The `k`-th batch is simulated with a random number generator depending only on
+/// the seed and on `k`. Thus, a simulation resumed from a checkpoint never simulates a batch
+/// twice and gives the same result as an uninterrupted simulation.
+///
+/// # Warning
+///
+/// The parameters are only a description of the simulation. They are never checked against
+/// the decoders given to [`simulate_batches`](#method.simulate_batches) and
+/// [`simulate_until_n_batches_and_save`](#method.simulate_until_n_batches_and_save). Resuming
+/// a checkpoint with a decoder not matching its parameters silently combines the results of
+/// different simulations. The decoder should thus always be built from the parameters.
+///
+/// A checkpoint is saved as JSON.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// # let path = std::env::temp_dir().join("believer_checkpoint_example.json");
+/// # std::fs::remove_file(&path).ok();
+/// let code = ParityCheckMatrix::repetition_code(3);
+/// let decoder = ErasureDecoder::with_prob(0.25).for_code(code.clone());
+///
+/// // Simulates 5 batches of 100 iterations, saving after each batch.
+/// let checkpoint = SimulationCheckpoint::load_or_new(&path, (code.clone(), 0.25), 10, 100)
+///     .unwrap()
+///     .simulate_until_n_batches_and_save(&decoder, 5, &path)
+///     .unwrap();
+///
+/// // After an interruption, the simulation resumes from the saved batches.
+/// let resumed = SimulationCheckpoint::load_or_new(&path, (code, 0.25), 10, 100)
+///     .unwrap()
+///     .simulate_until_n_batches_and_save(&decoder, 8, &path)
+///     .unwrap();
+///
+/// assert_eq!(resumed.get_n_batches(), 8);
+/// assert_eq!(resumed.get_result().get_n_iterations(), 800);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationCheckpoint<P> {
+    parameters: P,
+    seed: u64,
+    batch_size: u64,
+    n_batches: u64,
+    result: SimulationResult,
+}
+
+impl<P> SimulationCheckpoint<P> {
+    // ***** Construction *****
+
+    /// Creates a checkpoint of a simulation described by `parameters` without any simulated
+    /// batch. The batches of `batch_size` iterations are generated from `seed`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `batch_size` is 0.
+    pub fn new(parameters: P, seed: u64, batch_size: u64) -> Self {
+        if batch_size == 0 {
+            panic!("batch size must be positive");
+        }
+        Self {
+            parameters,
+            seed,
+            batch_size,
+            n_batches: 0,
+            result: SimulationResult::new(),
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the parameters of the simulation.
+    pub fn get_parameters(&self) -> &P {
+        &self.parameters
+    }
+
+    /// Returns the seed of the simulation.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the number of iterations of each batch.
+    pub fn get_batch_size(&self) -> u64 {
+        self.batch_size
+    }
+
+    /// Returns the number of simulated batches.
+    pub fn get_n_batches(&self) -> u64 {
+        self.n_batches
+    }
+
+    /// Returns the combined result of the simulated batches.
+    pub fn get_result(&self) -> SimulationResult {
+        self.result
+    }
+
+    // ***** Simulation *****
+
+    /// Simulates the next `n_batches` batches of iterations with `decoder` and adds their
+    /// results to `self`.
+    ///
+    /// The decoder is not checked against the parameters of `self`. It should be built from
+    /// them.
+    pub fn simulate_batches<D: Decoder>(mut self, decoder: &D, n_batches: u64) -> Self {
+        for _ in 0..n_batches {
+            let batch = self.simulate_batch(decoder, self.n_batches);
+            self.result = self.result.combine_with(batch);
+            self.n_batches += 1;
+        }
+        self
+    }
+
+    fn simulate_batch<D: Decoder>(&self, decoder: &D, batch: u64) -> SimulationResult {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(batch);
+        NIterationsSimulator::from(decoder)
+            .simulate_n_iterations_with_rng(self.batch_size as usize, &mut rng)
+            .get_result()
+    }
+}
+
+impl<P: Serialize + DeserializeOwned + PartialEq> SimulationCheckpoint<P> {
+    /// Loads the checkpoint saved at `path` or creates a new one if there is no file at `path`.
+    ///
+    /// # Error
+    ///
+    /// Returns an error of kind `InvalidData` if the saved checkpoint has different
+    /// parameters, seed or batch size.
+    pub fn load_or_new<A: AsRef<Path>>(
+        path: A,
+        parameters: P,
+        seed: u64,
+        batch_size: u64,
+    ) -> io::Result<Self> {
+        let new = Self::new(parameters, seed, batch_size);
+        if !path.as_ref().exists() {
+            return Ok(new);
+        }
+        let saved = Self::load(path)?;
+        if saved.parameters != new.parameters
+            || saved.seed != new.seed
+            || saved.batch_size != new.batch_size
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the checkpoint doesn't match the simulation",
+            ));
+        }
+        Ok(saved)
+    }
+
+    /// Simulates batches with `decoder` until `n_batches` batches are simulated, saving `self`
+    /// at `path` after each batch.
+    ///
+    /// If the simulation is interrupted, at most the running batch is lost. The decoder is not
+    /// checked against the parameters of `self`. It should be built from them.
+    pub fn simulate_until_n_batches_and_save<D: Decoder, A: AsRef<Path>>(
+        mut self,
+        decoder: &D,
+        n_batches: u64,
+        path: A,
+    ) -> io::Result<Self> {
+        while self.n_batches < n_batches {
+            self = self.simulate_batches(decoder, 1);
+            self.save(&path)?;
+        }
+        Ok(self)
+    }
+
+    // ***** Input / Output *****
+
+    /// Returns the JSON representation of `self`.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Reads a checkpoint from its JSON representation.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Saves `self` at `path` as JSON.
+    ///
+    /// The checkpoint is first written to a temporary file next to `path` and then renamed
+    /// such that an interruption never leaves a partial checkpoint.
+    pub fn save<A: AsRef<Path>>(&self, path: A) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, self.to_json()?)?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Loads the checkpoint saved at `path`.
+    pub fn load<A: AsRef<Path>>(path: A) -> io::Result<Self> {
+        Ok(Self::from_json(&fs::read_to_string(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ErasureDecoder, ParityCheckMatrix};
+
+    fn temporary_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("believer_checkpoint_{}.json", name));
+        fs::remove_file(&path).ok();
+        path
+    }
+
+    #[test]
+    fn resumed_simulation_equals_uninterrupted_simulation() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let uninterrupted = SimulationCheckpoint::new(0.3, 5, 200).simulate_batches(&decoder, 6);
+
+        let path = temporary_path("resume");
+        SimulationCheckpoint::load_or_new(&path, 0.3, 5, 200)
+            .unwrap()
+            .simulate_until_n_batches_and_save(&decoder, 4, &path)
+            .unwrap();
+        let resumed = SimulationCheckpoint::load_or_new(&path, 0.3, 5, 200)
+            .unwrap()
+            .simulate_until_n_batches_and_save(&decoder, 6, &path)
+            .unwrap();
+
+        assert_eq!(resumed, uninterrupted);
+        assert_eq!(resumed.get_result().get_n_iterations(), 1200);
+        assert_eq!(SimulationCheckpoint::load(&path).unwrap(), resumed);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mismatching_checkpoint_is_rejected() {
+        let path = temporary_path("mismatch");
+        SimulationCheckpoint::new(0.3, 5, 200).save(&path).unwrap();
+        let error = SimulationCheckpoint::load_or_new(&path, 0.2, 5, 200).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(SimulationCheckpoint::load_or_new(&path, 0.3, 6, 200).is_err());
+        assert!(SimulationCheckpoint::load_or_new(&path, 0.3, 5, 100).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let code = ParityCheckMatrix::repetition_code(3);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code.clone());
+        let checkpoint =
+            SimulationCheckpoint::new((code, 0.5), 1, 10).simulate_batches(&decoder, 3);
+        let json = checkpoint.to_json().unwrap();
+        assert_eq!(SimulationCheckpoint::from_json(&json).unwrap(), checkpoint);
+    }
+}
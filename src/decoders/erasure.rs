@@ -1,10 +1,16 @@
 //! A classical erasure decoder.
 
-use super::{Decoder, DecodingResult};
+use super::statistics::bernoulli_log_probability;
+use super::{Decoder, DecodingResult, ErasureFailurePolynomial, ResidualErrors};
 use crate::ErasureResult;
 use crate::Ressources;
 use crate::ParityCheckMatrix;
-use rand::Rng;
+use itertools::Itertools;
+use rand::distributions::Standard;
+use rand::seq::index::sample;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
 
 /// Decoder for classical erasure channel.
 ///
@@ -12,11 +18,11 @@ use rand::Rng;
 ///
 /// ```
 /// # use believer::*;
-/// let code = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
-/// let decoder = ErasureDecoder::with_prob(0.25).for_code(code);
+/// let code = ParityCheckMatrix::repetition_code(3);
+/// let mut decoder = ErasureDecoder::with_prob(0.25).for_code(code);
 /// decoder.decode(&decoder.get_random_error());
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ErasureDecoder {
     code: ParityCheckMatrix,
     erasure_prob: f64,
@@ -30,7 +36,7 @@ impl ErasureDecoder {
     ///
     /// Panics if `erasure_prob` is not between 0.0 and 1.0.
     pub fn with_prob(erasure_prob: f64) -> Self {
-        if erasure_prob < 0.0 || erasure_prob > 1.0 {
+        if !(0.0..=1.0).contains(&erasure_prob) {
             panic!("invalid probability");
         }
 
@@ -48,6 +54,106 @@ impl ErasureDecoder {
     fn next_bit_is_erased<R: Rng>(&self, rng: &mut R) -> bool {
         rng.gen::<f64>() < self.erasure_prob
     }
+
+    /// Computes the number of uncorrectable erasure patterns of each weight with random number
+    /// generator `rng`. This gives the failure probability for any erasure probability.
+    ///
+    /// All the patterns of a weight are decoded if there are at most `n_samples_per_weight` of
+    /// them. Otherwise, `n_samples_per_weight` random patterns of that weight are decoded. The
+    /// patterns with more erased bits than the rank of the code are never decoded since they
+    /// always fail.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_samples_per_weight` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use believer::*;
+    /// let code = ParityCheckMatrix::hamming_code(3);
+    /// let decoder = ErasureDecoder::with_prob(0.1).for_code(code);
+    /// let polynomial = decoder.get_failure_polynomial(100);
+    ///
+    /// // The 7 weight-3 codewords are the only uncorrectable patterns of weight 3.
+    /// assert!(polynomial.is_exact());
+    /// assert!((polynomial.get_n_uncorrectable_patterns(3) - 7.0).abs() < 1e-9);
+    /// assert!(polynomial.evaluate(0.01) < polynomial.evaluate(0.1));
+    /// ```
+    pub fn get_failure_polynomial_with_rng<R: Rng>(
+        &self,
+        n_samples_per_weight: usize,
+        rng: &mut R,
+    ) -> ErasureFailurePolynomial {
+        if n_samples_per_weight == 0 {
+            panic!("number of samples per weight must be positive");
+        }
+        let n_bits = self.code.get_n_bits();
+        let rank = self.code.get_rank();
+        let weights = (0..=n_bits)
+            .map(|weight| {
+                if weight == 0 {
+                    (0.0, 0, true)
+                } else if weight > rank {
+                    (1.0, 0, true)
+                } else if has_at_most_n_patterns(n_bits, weight, n_samples_per_weight) {
+                    let patterns: Vec<Vec<usize>> = (0..n_bits).combinations(weight).collect();
+                    let n_failures = self.count_failures(&patterns);
+                    let n_patterns = patterns.len() as u64;
+                    (n_failures as f64 / n_patterns as f64, n_patterns, true)
+                } else {
+                    let seeds: Vec<u64> = rng
+                        .sample_iter(Standard)
+                        .take(n_samples_per_weight)
+                        .collect();
+                    let patterns: Vec<Vec<usize>> = seeds
+                        .iter()
+                        .map(|seed| {
+                            let mut rng = ChaCha8Rng::seed_from_u64(*seed);
+                            let mut pattern = sample(&mut rng, n_bits, weight).into_vec();
+                            pattern.sort();
+                            pattern
+                        })
+                        .collect();
+                    let n_failures = self.count_failures(&patterns);
+                    let n_patterns = patterns.len() as u64;
+                    (n_failures as f64 / n_patterns as f64, n_patterns, false)
+                }
+            })
+            .collect();
+        ErasureFailurePolynomial::from_weights(weights)
+    }
+
+    /// Computes the number of uncorrectable erasure patterns of each weight with the thread
+    /// random number generator. This gives the failure probability for any erasure probability.
+    ///
+    /// See [`get_failure_polynomial_with_rng`](#method.get_failure_polynomial_with_rng).
+    pub fn get_failure_polynomial(&self, n_samples_per_weight: usize) -> ErasureFailurePolynomial {
+        self.get_failure_polynomial_with_rng(n_samples_per_weight, &mut thread_rng())
+    }
+
+    fn count_failures(&self, patterns: &[Vec<usize>]) -> usize {
+        patterns
+            .par_iter()
+            .map_init(
+                || self.clone(),
+                |decoder, pattern| decoder.decode(pattern).is_failure(),
+            )
+            .filter(|is_failure| *is_failure)
+            .count()
+    }
+}
+
+// Checks if the binomial coefficient of `n_bits` and `weight` is at most `max`.
+fn has_at_most_n_patterns(n_bits: usize, weight: usize, max: usize) -> bool {
+    let mut n_patterns: u128 = 1;
+    for index in 0..weight.min(n_bits - weight) {
+        n_patterns = n_patterns * (n_bits - index) as u128 / (index + 1) as u128;
+        if n_patterns > max as u128 {
+            return false;
+        }
+    }
+    true
 }
 
 impl Decoder for ErasureDecoder {
@@ -71,7 +177,7 @@ impl Decoder for ErasureDecoder {
     }
 
     fn take_code(&mut self) -> Self::Code {
-        std::mem::replace(&mut self.code, ParityCheckMatrix::new())
+        std::mem::take(&mut self.code)
     }
 
     // An erasure error can be corrected if there is no information in the erased submatrix. That
@@ -96,6 +202,40 @@ impl Decoder for ErasureDecoder {
         }
     }
 
+    // An erased bit can't be recovered if it is in the support of a codeword supported on the
+    // erased bits. The codewords of the code restricted to the erased bits are spanned by the
+    // erased part of its codeword basis.
+    fn get_residual_errors(
+        &self,
+        error: &Self::Error,
+        result: &Self::Result,
+    ) -> Option<ResidualErrors> {
+        let n_bits = self.code.get_n_bits();
+        if result.is_success() {
+            return Some(ResidualErrors::new(n_bits, Vec::new()));
+        }
+        let mut is_erased = vec![false; n_bits];
+        error.iter().for_each(|bit| is_erased[*bit] = true);
+        let mut is_lost = vec![false; n_bits];
+        self.code
+            .keep(error)
+            .get_codeword_basis()
+            .iter()
+            .flatten()
+            .filter(|bit| is_erased[**bit])
+            .for_each(|bit| is_lost[*bit] = true);
+        let bits = (0..n_bits).filter(|bit| is_lost[*bit]).collect();
+        Some(ResidualErrors::new(n_bits, bits))
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(bernoulli_log_probability(
+            error.len(),
+            self.code.get_n_bits(),
+            self.erasure_prob,
+        ))
+    }
+
     // Erase random bits with given probability.
     fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
         (0..self.code.get_n_bits())
@@ -110,7 +250,7 @@ mod test {
 
     #[test]
     fn repetition_code() {
-        let code = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+        let code = ParityCheckMatrix::repetition_code(3);
         let mut decoder = ErasureDecoder::with_prob(0.2).for_code(code);
 
         assert_eq!(decoder.decode(&vec![]), ErasureResult::Success);
@@ -154,4 +294,82 @@ mod test {
             ErasureResult::Failure
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn only_unrecoverable_erased_bits_are_residual() {
+        // Bits 0 and 1 form a codeword, but bit 3 is determined by the last check.
+        let code = ParityCheckMatrix::with_n_bits(4).with_checks(vec![vec![0, 1, 2], vec![2, 3]]);
+        let mut decoder = ErasureDecoder::with_prob(0.2).for_code(code);
+
+        let error = vec![0, 1];
+        let result = decoder.decode(&error);
+        assert_eq!(
+            decoder.get_residual_errors(&error, &result),
+            Some(ResidualErrors::new(4, vec![0, 1]))
+        );
+
+        let error = vec![0, 3];
+        let result = decoder.decode(&error);
+        assert_eq!(
+            decoder.get_residual_errors(&error, &result),
+            Some(ResidualErrors::new(4, vec![]))
+        );
+    }
+
+    #[test]
+    fn weak_bits_of_a_code_fail_more_often() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        // Bits 3 and 4 are lost when both are erased while bits 0, 1 and 2 are lost only
+        // when all three are erased.
+        let code =
+            ParityCheckMatrix::with_n_bits(5).with_checks(vec![vec![0, 1], vec![1, 2], vec![3, 4]]);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(10);
+        let statistics = decoder.simulate_bit_errors_n_iterations_with_rng(2000, &mut rng);
+        let counts = statistics.get_bit_failure_counts();
+        assert!(counts[3] > counts[0] && counts[4] > counts[0]);
+        assert!(statistics.get_weakest_bits()[0] >= 3);
+        assert!(statistics.get_bit_error_rate() < statistics.get_frame_error_rate());
+    }
+
+    #[test]
+    fn failure_polynomial_matches_the_simulations() {
+        let code = ParityCheckMatrix::hamming_code(4);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let exact = decoder.get_failure_polynomial_with_rng(10_000, &mut rng);
+        let sampled = decoder.get_failure_polynomial_with_rng(200, &mut rng);
+        assert!(exact.is_exact());
+        // Only the weights 3 and 4 have more than 200 patterns and at most 4 erased bits.
+        assert!(!sampled.is_exact());
+        assert!(sampled.is_exact_for_weight(2) && sampled.is_exact_for_weight(5));
+        assert_eq!(sampled.get_n_samples(3), 200);
+        assert_eq!(sampled.get_n_samples(5), 0);
+
+        let simulated = decoder.simulate_n_iterations_with_rng(20_000, &mut rng);
+        let error = simulated.get_standard_error();
+        assert!((exact.evaluate(0.3) - simulated.get_failure_rate()).abs() < 4.0 * error);
+        for prob in [0.1, 0.3, 0.5].iter() {
+            let difference = (exact.evaluate(*prob) - sampled.evaluate(*prob)).abs();
+            assert!(difference < 4.0 * sampled.get_standard_error(*prob) + 1e-12);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn failure_polynomial_needs_samples() {
+        ErasureDecoder::with_prob(0.1)
+            .for_code(ParityCheckMatrix::hamming_code(3))
+            .get_failure_polynomial(0);
+    }
+
+    #[test]
+    fn pattern_counts() {
+        assert!(has_at_most_n_patterns(10, 3, 120));
+        assert!(!has_at_most_n_patterns(10, 3, 119));
+        assert!(has_at_most_n_patterns(10, 10, 1));
+        assert!(!has_at_most_n_patterns(100, 50, usize::MAX));
+    }
+}
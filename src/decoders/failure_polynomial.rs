@@ -0,0 +1,170 @@
+//! The failure probability of an erasure decoder as a function of the erasure probability.
+
+use super::statistics::bernoulli_log_probability;
+
+/// The number of uncorrectable erasure patterns of each weight of a code.
+///
+/// The failure probability of a code of `n` bits at erasure probability `p` is
+/// `sum_w N(w) p^w (1 - p)^(n - w)` where `N(w)` is the number of uncorrectable patterns of `w`
+/// erased bits. Each `N(w)` is either exact or estimated from the fraction of failures among
+/// random patterns of weight `w`.
+///
+/// See [`ErasureDecoder::get_failure_polynomial_with_rng`](struct.ErasureDecoder.html#method.get_failure_polynomial_with_rng).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErasureFailurePolynomial {
+    failure_fractions: Vec<f64>,
+    n_samples: Vec<u64>,
+    is_exact: Vec<bool>,
+    log_binomials: Vec<f64>,
+}
+
+impl ErasureFailurePolynomial {
+    // ***** Construction *****
+
+    // The failure fraction, the number of decoded patterns and the exactness of each weight.
+    pub(super) fn from_weights(weights: Vec<(f64, u64, bool)>) -> Self {
+        let n_bits = weights.len() - 1;
+        let mut log_binomials = vec![0.0; n_bits + 1];
+        for weight in 1..=n_bits {
+            log_binomials[weight] = log_binomials[weight - 1]
+                + ((n_bits - weight + 1) as f64 / weight as f64).ln();
+        }
+        Self {
+            failure_fractions: weights.iter().map(|(fraction, _, _)| *fraction).collect(),
+            n_samples: weights.iter().map(|(_, n_samples, _)| *n_samples).collect(),
+            is_exact: weights.iter().map(|(_, _, is_exact)| *is_exact).collect(),
+            log_binomials,
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of bits of the code.
+    pub fn get_n_bits(&self) -> usize {
+        self.failure_fractions.len() - 1
+    }
+
+    /// Returns the fraction of uncorrectable patterns among the patterns of `weight` erased
+    /// bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `weight` is larger than the number of bits.
+    pub fn get_failure_fraction(&self, weight: usize) -> f64 {
+        self.failure_fractions[weight]
+    }
+
+    /// Returns the number of uncorrectable patterns of `weight` erased bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `weight` is larger than the number of bits.
+    pub fn get_n_uncorrectable_patterns(&self, weight: usize) -> f64 {
+        self.failure_fractions[weight] * self.log_binomials[weight].exp()
+    }
+
+    /// Returns the number of decoded patterns of `weight` erased bits. This is 0 if the
+    /// fraction is known without decoding.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `weight` is larger than the number of bits.
+    pub fn get_n_samples(&self, weight: usize) -> u64 {
+        self.n_samples[weight]
+    }
+
+    /// Checks if the number of uncorrectable patterns of `weight` erased bits is exact.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `weight` is larger than the number of bits.
+    pub fn is_exact_for_weight(&self, weight: usize) -> bool {
+        self.is_exact[weight]
+    }
+
+    /// Checks if the numbers of uncorrectable patterns of all weights are exact.
+    pub fn is_exact(&self) -> bool {
+        self.is_exact.iter().all(|is_exact| *is_exact)
+    }
+
+    // ***** Evaluation *****
+
+    /// Returns the failure probability at erasure probability `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 1.0.
+    pub fn evaluate(&self, prob: f64) -> f64 {
+        self.get_weight_probabilities(prob)
+            .zip(self.failure_fractions.iter())
+            .map(|(weight_prob, fraction)| weight_prob * fraction)
+            .sum()
+    }
+
+    /// Returns the standard error of the failure probability at erasure probability `prob`
+    /// coming from the sampled weights.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 1.0.
+    pub fn get_standard_error(&self, prob: f64) -> f64 {
+        self.get_weight_probabilities(prob)
+            .enumerate()
+            .filter(|(weight, _)| !self.is_exact[*weight])
+            .map(|(weight, weight_prob)| {
+                let fraction = self.failure_fractions[weight];
+                weight_prob * weight_prob * fraction * (1.0 - fraction)
+                    / self.n_samples[weight] as f64
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    // The probability to erase exactly each number of bits.
+    fn get_weight_probabilities(&self, prob: f64) -> impl Iterator<Item = f64> + '_ {
+        if !(0.0..=1.0).contains(&prob) {
+            panic!("invalid probability");
+        }
+        let n_bits = self.get_n_bits();
+        self.log_binomials
+            .iter()
+            .enumerate()
+            .map(move |(weight, log_binomial)| {
+                (log_binomial + bernoulli_log_probability(weight, n_bits, prob)).exp()
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exact_polynomial_of_a_repetition_code() {
+        let polynomial = ErasureFailurePolynomial::from_weights(vec![
+            (0.0, 0, true),
+            (0.0, 3, true),
+            (0.0, 3, true),
+            (1.0, 0, true),
+        ]);
+        assert_eq!(polynomial.get_n_bits(), 3);
+        assert!(polynomial.is_exact());
+        assert!((polynomial.get_n_uncorrectable_patterns(3) - 1.0).abs() < 1e-12);
+        for prob in [0.0, 0.1, 0.5, 1.0].iter() {
+            assert!((polynomial.evaluate(*prob) - prob.powi(3)).abs() < 1e-12);
+            assert_eq!(polynomial.get_standard_error(*prob), 0.0);
+        }
+    }
+
+    #[test]
+    fn standard_error_comes_from_sampled_weights() {
+        let polynomial = ErasureFailurePolynomial::from_weights(vec![
+            (0.0, 0, true),
+            (0.5, 100, false),
+            (1.0, 0, true),
+        ]);
+        assert!(!polynomial.is_exact());
+        assert!((polynomial.evaluate(0.5) - 0.5).abs() < 1e-12);
+        assert!((polynomial.get_standard_error(0.5) - 0.025).abs() < 1e-12);
+    }
+}
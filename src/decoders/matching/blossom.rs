@@ -0,0 +1,647 @@
+//! Maximum weight matching in general graphs with Edmonds' blossom algorithm.
+//!
+//! This is a port of the O(n^3) implementation of "Efficient algorithms for finding maximum
+//! matching in graphs, Zvi Galil, 1986, ACM Computing Surveys" by Joris van Rantwijk. All
+//! weights are doubled such that the dual variables stay integers.
+
+// An edge between two vertices with an integer weight.
+pub(super) type Edge = (usize, usize, i64);
+
+const NONE: usize = usize::MAX;
+
+/// Returns the mate of each vertex in a minimum weight perfect matching of the graph with
+/// `n_vertices` and the given `edges` or `None` if there is no perfect matching.
+pub(super) fn min_weight_perfect_matching(n_vertices: usize, edges: &[Edge]) -> Option<Vec<usize>> {
+    if n_vertices == 0 {
+        return Some(Vec::new());
+    }
+    let max_weight = edges.iter().map(|edge| edge.2).max().unwrap_or(0);
+    let reversed_edges: Vec<Edge> = edges
+        .iter()
+        .map(|(first, second, weight)| (*first, *second, max_weight + 1 - weight))
+        .collect();
+    let mates = max_weight_matching(n_vertices, &reversed_edges, true);
+    if mates.iter().all(|mate| *mate != NONE) {
+        Some(mates)
+    } else {
+        None
+    }
+}
+
+/// Returns the mate of each vertex in a maximum weight matching or `usize::MAX` for unmatched
+/// vertices. If `max_cardinality` is true, the matching is of maximum weight among the matchings
+/// of maximum cardinality.
+pub(super) fn max_weight_matching(
+    n_vertices: usize,
+    edges: &[Edge],
+    max_cardinality: bool,
+) -> Vec<usize> {
+    let edges: Vec<Edge> = edges
+        .iter()
+        .map(|(first, second, weight)| (*first, *second, 2 * weight))
+        .collect();
+    Matcher::new(n_vertices, edges, max_cardinality).solve()
+}
+
+struct Matcher {
+    n_vertices: usize,
+    edges: Vec<Edge>,
+    max_cardinality: bool,
+    endpoint: Vec<usize>,
+    neighbend: Vec<Vec<usize>>,
+    mate: Vec<usize>,
+    label: Vec<u8>,
+    labelend: Vec<usize>,
+    inblossom: Vec<usize>,
+    blossomparent: Vec<usize>,
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<usize>,
+    blossomendps: Vec<Vec<usize>>,
+    bestedge: Vec<usize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    dualvar: Vec<i64>,
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl Matcher {
+    fn new(n_vertices: usize, edges: Vec<Edge>, max_cardinality: bool) -> Self {
+        let max_weight = edges.iter().map(|edge| edge.2).max().unwrap_or(0).max(0);
+        let endpoint = (0..2 * edges.len())
+            .map(|p| {
+                let edge = edges[p / 2];
+                if p % 2 == 0 {
+                    edge.0
+                } else {
+                    edge.1
+                }
+            })
+            .collect();
+        let mut neighbend = vec![Vec::new(); n_vertices];
+        for (k, (i, j, _)) in edges.iter().enumerate() {
+            neighbend[*i].push(2 * k + 1);
+            neighbend[*j].push(2 * k);
+        }
+        let n_edges = edges.len();
+        Self {
+            n_vertices,
+            edges,
+            max_cardinality,
+            endpoint,
+            neighbend,
+            mate: vec![NONE; n_vertices],
+            label: vec![0; 2 * n_vertices],
+            labelend: vec![NONE; 2 * n_vertices],
+            inblossom: (0..n_vertices).collect(),
+            blossomparent: vec![NONE; 2 * n_vertices],
+            blossomchilds: vec![Vec::new(); 2 * n_vertices],
+            blossombase: (0..2 * n_vertices)
+                .map(|b| if b < n_vertices { b } else { NONE })
+                .collect(),
+            blossomendps: vec![Vec::new(); 2 * n_vertices],
+            bestedge: vec![NONE; 2 * n_vertices],
+            blossombestedges: vec![None; 2 * n_vertices],
+            unusedblossoms: (n_vertices..2 * n_vertices).collect(),
+            dualvar: (0..2 * n_vertices)
+                .map(|b| if b < n_vertices { max_weight } else { 0 })
+                .collect(),
+            allowedge: vec![false; n_edges],
+            queue: Vec::new(),
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, weight) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * weight
+    }
+
+    fn blossom_leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![b];
+        while let Some(node) = stack.pop() {
+            if node < self.n_vertices {
+                leaves.push(node);
+            } else {
+                stack.extend(self.blossomchilds[node].iter().rev());
+            }
+        }
+        leaves
+    }
+
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = NONE;
+        self.bestedge[b] = NONE;
+        if t == 1 {
+            let leaves = self.blossom_leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let base = self.blossombase[b];
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate], 1, mate ^ 1);
+        }
+    }
+
+    // Traces back from `v` and `w` to find a new blossom or an augmenting path. Returns the base
+    // of the blossom or `NONE` for an augmenting path.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.inblossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.labelend[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.labelend[b]];
+                b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self.unusedblossoms.pop().unwrap();
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            v = self.endpoint[self.labelend[bv]];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            w = self.endpoint[self.labelend[bw]];
+            bw = self.inblossom[w];
+        }
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        self.blossomchilds[b] = path.clone();
+        self.blossomendps[b] = endps;
+        for leaf in self.blossom_leaves(b) {
+            if self.label[self.inblossom[leaf]] == 2 {
+                self.queue.push(leaf);
+            }
+            self.inblossom[leaf] = b;
+        }
+
+        let mut bestedgeto = vec![NONE; 2 * self.n_vertices];
+        for bv in path {
+            let neighbor_lists: Vec<Vec<usize>> = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .map(|leaf| self.neighbend[leaf].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+            for list in neighbor_lists {
+                for k in list {
+                    let (mut i, mut j, _) = self.edges[k];
+                    if self.inblossom[j] == b {
+                        std::mem::swap(&mut i, &mut j);
+                    }
+                    let bj = self.inblossom[j];
+                    if bj != b
+                        && self.label[bj] == 1
+                        && (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                    {
+                        bestedgeto[bj] = k;
+                    }
+                }
+            }
+            self.bestedge[bv] = NONE;
+        }
+        let best_edges: Vec<usize> = bestedgeto.into_iter().filter(|k| *k != NONE).collect();
+        self.bestedge[b] = NONE;
+        for k in best_edges.iter() {
+            if self.bestedge[b] == NONE || self.slack(*k) < self.slack(self.bestedge[b]) {
+                self.bestedge[b] = *k;
+            }
+        }
+        self.blossombestedges[b] = Some(best_edges);
+    }
+
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        let childs = self.blossomchilds[b].clone();
+        for s in childs.iter() {
+            self.blossomparent[*s] = NONE;
+            if *s < self.n_vertices {
+                self.inblossom[*s] = *s;
+            } else if endstage && self.dualvar[*s] == 0 {
+                self.expand_blossom(*s, endstage);
+            } else {
+                for leaf in self.blossom_leaves(*s) {
+                    self.inblossom[leaf] = *s;
+                }
+            }
+        }
+
+        if !endstage && self.label[b] == 2 {
+            let len = childs.len() as i64;
+            let at = |j: i64| -> usize { j.rem_euclid(len) as usize };
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs.iter().position(|s| *s == entrychild).unwrap() as i64;
+            let (jstep, endptrick) = if j & 1 == 1 {
+                j -= len;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.labelend[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = self.blossomendps[b][at(j - endptrick)] ^ endptrick as usize ^ 1;
+                self.label[self.endpoint[q]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowedge[self.blossomendps[b][at(j - endptrick)] / 2] = true;
+                j += jstep;
+                p = self.blossomendps[b][at(j - endptrick)] ^ endptrick as usize;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+            let bv = childs[at(j)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+            j += jstep;
+            while childs[at(j)] != entrychild {
+                let bv = childs[at(j)];
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+                if let Some(v) = self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .find(|leaf| self.label[*leaf] != 0)
+                {
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.blossombase[bv]]]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+
+        self.label[b] = NONE as u8;
+        self.labelend[b] = NONE;
+        self.blossomchilds[b] = Vec::new();
+        self.blossomendps[b] = Vec::new();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b {
+            t = self.blossomparent[t];
+        }
+        if t >= self.n_vertices {
+            self.augment_blossom(t, v);
+        }
+        let len = self.blossomchilds[b].len() as i64;
+        let at = |j: i64| -> usize { j.rem_euclid(len) as usize };
+        let i = self.blossomchilds[b].iter().position(|s| *s == t).unwrap();
+        let mut j = i as i64;
+        let (jstep, endptrick) = if j & 1 == 1 {
+            j -= len;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            let p = self.blossomendps[b][at(j - endptrick)] ^ endptrick as usize;
+            if t >= self.n_vertices {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += jstep;
+            let t = self.blossomchilds[b][at(j)];
+            if t >= self.n_vertices {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)].iter().cloned() {
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.n_vertices {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.labelend[bs] == NONE {
+                    break;
+                }
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt]];
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+                if bt >= self.n_vertices {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    fn solve(mut self) -> Vec<usize> {
+        let n = self.n_vertices;
+        for _ in 0..n {
+            self.label.iter_mut().for_each(|label| *label = 0);
+            self.bestedge.iter_mut().for_each(|edge| *edge = NONE);
+            self.blossombestedges[n..]
+                .iter_mut()
+                .for_each(|edges| *edges = None);
+            self.allowedge
+                .iter_mut()
+                .for_each(|allowed| *allowed = false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let v = match self.queue.pop() {
+                        Some(v) => v,
+                        None => break,
+                    };
+                    for p in self.neighbend[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 {
+                                self.allowedge[k] = true;
+                            }
+                        }
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = p ^ 1;
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) {
+                                self.bestedge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                        {
+                            self.bestedge[w] = k;
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                // Updates the dual variables.
+                let mut deltatype = 0;
+                let mut delta = 0;
+                let mut deltaedge = NONE;
+                let mut deltablossom = NONE;
+                if !self.max_cardinality {
+                    deltatype = 1;
+                    delta = *self.dualvar[..n].iter().min().unwrap();
+                }
+                for v in 0..n {
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != NONE {
+                        let d = self.slack(self.bestedge[v]);
+                        if deltatype == 0 || d < delta {
+                            delta = d;
+                            deltatype = 2;
+                            deltaedge = self.bestedge[v];
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    if self.blossomparent[b] == NONE
+                        && self.label[b] == 1
+                        && self.bestedge[b] != NONE
+                    {
+                        let d = self.slack(self.bestedge[b]) / 2;
+                        if deltatype == 0 || d < delta {
+                            delta = d;
+                            deltatype = 3;
+                            deltaedge = self.bestedge[b];
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE
+                        && self.blossomparent[b] == NONE
+                        && self.label[b] == 2
+                        && (deltatype == 0 || self.dualvar[b] < delta)
+                    {
+                        delta = self.dualvar[b];
+                        deltatype = 4;
+                        deltablossom = b;
+                    }
+                }
+                if deltatype == 0 {
+                    deltatype = 1;
+                    delta = (*self.dualvar[..n].iter().min().unwrap()).max(0);
+                }
+
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => (),
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b] != NONE && self.blossomparent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => (),
+                        }
+                    }
+                }
+
+                match deltatype {
+                    1 => break,
+                    2 => {
+                        self.allowedge[deltaedge] = true;
+                        let (mut i, j, _) = self.edges[deltaedge];
+                        if self.label[self.inblossom[i]] == 0 {
+                            i = j;
+                        }
+                        self.queue.push(i);
+                    }
+                    3 => {
+                        self.allowedge[deltaedge] = true;
+                        let (i, _, _) = self.edges[deltaedge];
+                        self.queue.push(i);
+                    }
+                    _ => self.expand_blossom(deltablossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            for b in n..2 * n {
+                if self.blossomparent[b] == NONE
+                    && self.blossombase[b] != NONE
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+
+        (0..n)
+            .map(|v| {
+                if self.mate[v] == NONE {
+                    NONE
+                } else {
+                    self.endpoint[self.mate[v]]
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    // Finds the minimum weight of a perfect matching by enumerating all of them.
+    fn brute_force(n_vertices: usize, edges: &[Edge]) -> Option<i64> {
+        if n_vertices == 0 {
+            return Some(0);
+        }
+        let first = 0;
+        edges
+            .iter()
+            .filter(|(i, j, _)| *i == first || *j == first)
+            .filter_map(|(i, j, weight)| {
+                let other = if *i == first { *j } else { *i };
+                let remaining: Vec<usize> = (0..n_vertices)
+                    .filter(|v| *v != first && *v != other)
+                    .collect();
+                let relabeled: Vec<Edge> = edges
+                    .iter()
+                    .filter_map(|(a, b, w)| {
+                        let a = remaining.iter().position(|v| v == a)?;
+                        let b = remaining.iter().position(|v| v == b)?;
+                        Some((a, b, *w))
+                    })
+                    .collect();
+                brute_force(remaining.len(), &relabeled).map(|rest| rest + weight)
+            })
+            .min()
+    }
+
+    #[test]
+    fn small_matchings() {
+        let edges = vec![(0, 1, 5), (1, 2, 1), (2, 3, 5), (3, 0, 1)];
+        assert_eq!(
+            min_weight_perfect_matching(4, &edges),
+            Some(vec![3, 2, 1, 0])
+        );
+        assert_eq!(min_weight_perfect_matching(3, &[(0, 1, 1)]), None);
+    }
+
+    #[test]
+    fn random_complete_graphs_match_brute_force() {
+        let mut rng = ChaCha8Rng::seed_from_u64(17);
+        for n_vertices in [2, 4, 6, 8].iter() {
+            for _ in 0..30 {
+                let edges: Vec<Edge> = (0..*n_vertices)
+                    .tuple_combinations()
+                    .filter_map(|(i, j)| {
+                        if rng.gen::<f64>() < 0.8 {
+                            Some((i, j, rng.gen_range(0, 20)))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                let expected = brute_force(*n_vertices, &edges);
+                let mates = min_weight_perfect_matching(*n_vertices, &edges);
+                assert_eq!(mates.is_some(), expected.is_some());
+                if let Some(mates) = mates {
+                    let weight: i64 = edges
+                        .iter()
+                        .filter(|(i, j, _)| mates[*i] == *j)
+                        .map(|edge| edge.2)
+                        .sum();
+                    assert_eq!(Some(weight), expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,485 @@
+//! A minimum-weight perfect matching decoder for codes whose bits are in at most 2 checks.
+//!
+//! The weighted matching is computed with the blossom algorithm of "Paths, trees, and flowers,
+//! Jack Edmonds, 1965, Canadian Journal of Mathematics".
+
+use super::statistics::bernoulli_log_probability;
+use super::{Decoder, DecodingResult};
+use crate::channel::{BinaryChannel, BinarySymmetricChannel};
+use crate::{DetectorErrorModel, ParityCheckMatrix, GF2};
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+mod blossom;
+use blossom::{min_weight_perfect_matching, Edge};
+
+// The log-likelihood weights are rounded to integers after being scaled by this factor.
+const WEIGHT_RESOLUTION: f64 = 1000.0;
+
+/// Decoder for bit flips on codes where each bit is in at most 2 checks.
+///
+/// This includes repetition codes, one type of checks of surface and toric codes, their
+/// spacetime codes and detector error models with graph-like faults. The syndrome graph has a
+/// node for each check and a boundary node. Each bit is an edge between its 2 checks or between
+/// its check and the boundary. The weight of a bit is the log-likelihood ratio
+/// `log2((1 - p) / p)` of its prior `p` such that a minimum weight correction is a most likely
+/// one. Bits with a prior of 0 are never used in a correction.
+///
+/// The decoder pairs the flipped checks along shortest paths by computing a minimum-weight
+/// perfect matching of the flipped checks where each flipped check can also be matched to the
+/// boundary.
+///
+/// By default, the decoding succeeds if the correction is the error. If some logical
+/// operators are given, the decoding succeeds if the correction is equal to the error up to an
+/// operator that commutes with all logicals.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::repetition_code(5);
+/// let mut decoder = MatchingDecoder::with_prob(0.1).for_code(code);
+///
+/// let syndrome = vec![GF2::B0, GF2::B1, GF2::B0, GF2::B1];
+/// let correction = decoder.find_correction(&syndrome);
+/// assert_eq!(correction, vec![GF2::B0, GF2::B0, GF2::B1, GF2::B1, GF2::B0]);
+///
+/// assert_eq!(decoder.decode(&vec![0, 4]), MatchingResult::Success);
+/// assert_eq!(decoder.decode(&vec![0, 1, 4]), MatchingResult::Failure);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MatchingDecoder {
+    code: ParityCheckMatrix,
+    logicals: Option<ParityCheckMatrix>,
+    prob: f64,
+    priors: Vec<f64>,
+    graph: SyndromeGraph,
+}
+
+/// The result of a matching decoder.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MatchingResult {
+    Failure,
+    Success,
+}
+
+impl DecodingResult for MatchingResult {
+    fn is_success(&self) -> bool {
+        self == &Self::Success
+    }
+}
+
+impl MatchingDecoder {
+    // ***** Construction *****
+
+    /// Creates a matching decoder for an empty code where each bit is flipped with probability
+    /// `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 0.5. Above 0.5, the log-likelihood weights are
+    /// negative and a minimum-weight matching is not a most likely correction.
+    pub fn with_prob(prob: f64) -> Self {
+        if !(0.0..=0.5).contains(&prob) {
+            panic!("invalid probability");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            logicals: None,
+            prob,
+            priors: Vec::new(),
+            graph: SyndromeGraph::from_checks_and_priors(&ParityCheckMatrix::new(), &[]),
+        }
+    }
+
+    /// Creates a matching decoder for the mechanisms of a detector error model.
+    ///
+    /// The detectors are the checks, the observables are the logicals and the priors of the
+    /// mechanisms are the priors of the bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some mechanisms flip more than 2 detectors or have a prior above 0.5.
+    pub fn from_detector_error_model(model: &DetectorErrorModel) -> Self {
+        Self::with_prob(0.0)
+            .for_code(model.get_detectors().clone())
+            .with_priors(model.get_priors().to_vec())
+            .with_logicals(model.get_observables().clone())
+    }
+
+    /// Sets the prior of each bit.
+    ///
+    /// The priors are reset to the decoder probability each time the code is changed.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the number of priors is not the number of bits or if some priors are not
+    /// between 0.0 and 0.5.
+    pub fn with_priors(mut self, priors: Vec<f64>) -> Self {
+        if priors.len() != self.code.get_n_bits() {
+            panic!("wrong number of priors");
+        }
+        if priors.iter().any(|prior| !(0.0..=0.5).contains(prior)) {
+            panic!("invalid probability");
+        }
+        self.graph = SyndromeGraph::from_checks_and_priors(&self.code, &priors);
+        self.priors = priors;
+        self
+    }
+
+    /// Sets the logical operators used to decide if a decoding succeeds.
+    ///
+    /// The logicals are removed each time the code is changed.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the logicals and the code have different numbers of bits.
+    pub fn with_logicals(mut self, logicals: ParityCheckMatrix) -> Self {
+        if logicals.get_n_bits() != self.code.get_n_bits() {
+            panic!("logicals and code have different numbers of bits");
+        }
+        self.logicals = Some(logicals);
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the prior of each bit.
+    pub fn get_priors(&self) -> &[f64] {
+        &self.priors
+    }
+
+    /// Returns the logical operators if any.
+    pub fn get_logicals(&self) -> Option<&ParityCheckMatrix> {
+        self.logicals.as_ref()
+    }
+
+    // ***** Decoding *****
+
+    /// Returns a most likely set of flipped bits with the given `syndrome`.
+    ///
+    /// If no set of bits has this syndrome, the correction is empty.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `syndrome` is not the number of checks.
+    pub fn find_correction(&self, syndrome: &[GF2]) -> Vec<GF2> {
+        if syndrome.len() != self.code.get_n_checks() {
+            panic!("wrong syndrome length");
+        }
+        let defects: Vec<usize> = syndrome
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value == GF2::B1)
+            .map(|(check, _)| check)
+            .collect();
+        self.graph
+            .find_correction(&defects)
+            .unwrap_or_else(|| vec![GF2::B0; self.code.get_n_bits()])
+    }
+}
+
+impl Decoder for MatchingDecoder {
+    type Error = Vec<usize>; // Positions of flipped bits.
+    type Result = MatchingResult;
+    type Code = ParityCheckMatrix;
+
+    /// # Panic
+    ///
+    /// Panics if some bits are in more than 2 checks.
+    fn for_code(mut self, code: Self::Code) -> Self {
+        let priors = vec![self.prob; code.get_n_bits()];
+        self.graph = SyndromeGraph::from_checks_and_priors(&code, &priors);
+        self.priors = priors;
+        self.logicals = None;
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.logicals = None;
+        self.priors = Vec::new();
+        self.graph = SyndromeGraph::from_checks_and_priors(&ParityCheckMatrix::new(), &[]);
+        std::mem::take(&mut self.code)
+    }
+
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        let mut residual = vec![GF2::B0; self.code.get_n_bits()];
+        error.iter().for_each(|bit| residual[*bit] = GF2::B1);
+        let correction = self.find_correction(&self.code.get_syndrome_of(&residual));
+        residual
+            .iter_mut()
+            .zip(correction)
+            .for_each(|(value, correction)| *value = *value + correction);
+
+        let is_success = match &self.logicals {
+            Some(logicals) => {
+                self.code
+                    .get_syndrome_of(&residual)
+                    .iter()
+                    .all(|v| *v == GF2::B0)
+                    && logicals
+                        .get_syndrome_of(&residual)
+                        .iter()
+                        .all(|v| *v == GF2::B0)
+            }
+            None => residual.iter().all(|value| *value == GF2::B0),
+        };
+        if is_success {
+            MatchingResult::Success
+        } else {
+            MatchingResult::Failure
+        }
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        let mut is_flipped = vec![false; self.priors.len()];
+        error.iter().for_each(|bit| is_flipped[*bit] = true);
+        Some(
+            self.priors
+                .iter()
+                .zip(is_flipped.iter())
+                .map(|(prior, flipped)| bernoulli_log_probability(*flipped as usize, 1, *prior))
+                .sum(),
+        )
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        self.priors
+            .iter()
+            .enumerate()
+            .filter(|(_, prior)| rng.gen::<f64>() < **prior)
+            .map(|(bit, _)| bit)
+            .collect()
+    }
+}
+
+// The syndrome graph of a check matrix. The checks are the nodes `0` to `n_checks - 1` and the
+// boundary is the node `n_checks`. Each edge is a bit with a non-negative integer weight.
+#[derive(Debug, Clone)]
+struct SyndromeGraph {
+    n_bits: usize,
+    has_boundary: bool,
+    adjacency: Vec<Vec<(usize, usize)>>, // (neighbor, bit) for each node.
+    weights: Vec<i64>,
+}
+
+impl SyndromeGraph {
+    fn from_checks_and_priors(checks: &ParityCheckMatrix, priors: &[f64]) -> Self {
+        let boundary = checks.get_n_checks();
+        let mut adjacency = vec![Vec::new(); boundary + 1];
+        let mut has_boundary = false;
+        for (bit, bit_checks) in checks.get_transposed_matrix().checks_iter().enumerate() {
+            let edge = match bit_checks.to_vec().as_slice() {
+                [] => None,
+                [check] => Some((*check, boundary)),
+                [first, second] => Some((*first, *second)),
+                _ => panic!("bit {} is in more than 2 checks", bit),
+            };
+            if let Some((first, second)) = edge {
+                if priors[bit] > 0.0 {
+                    adjacency[first].push((second, bit));
+                    adjacency[second].push((first, bit));
+                    has_boundary |= second == boundary;
+                }
+            }
+        }
+        let weights = priors
+            .iter()
+            .map(|prior| {
+                let weight = BinarySymmetricChannel::new(*prior).intrinsic_likelyhood(GF2::B1);
+                if weight.is_finite() {
+                    (weight * WEIGHT_RESOLUTION).round() as i64
+                } else {
+                    0
+                }
+            })
+            .collect();
+        Self {
+            n_bits: checks.get_n_bits(),
+            has_boundary,
+            adjacency,
+            weights,
+        }
+    }
+
+    fn boundary(&self) -> usize {
+        self.adjacency.len() - 1
+    }
+
+    // Returns a minimum weight set of bits whose syndrome is the given `defects` or `None` if
+    // there is no such set.
+    fn find_correction(&self, defects: &[usize]) -> Option<Vec<GF2>> {
+        let paths: Vec<ShortestPaths> = defects
+            .iter()
+            .map(|defect| self.shortest_paths_from(*defect))
+            .collect();
+
+        // The defects are the vertices 0 to n_defects - 1 and each of them has a boundary copy.
+        // The boundary copies are matched together for free.
+        let n_defects = defects.len();
+        let mut edges: Vec<Edge> = Vec::new();
+        for (first, first_paths) in paths.iter().enumerate() {
+            for (second, second_defect) in defects.iter().enumerate().skip(first + 1) {
+                if let Some(distance) = first_paths.distances[*second_defect] {
+                    edges.push((first, second, distance));
+                }
+            }
+        }
+        let n_vertices = if self.has_boundary {
+            for (defect, defect_paths) in paths.iter().enumerate() {
+                if let Some(distance) = defect_paths.distances[self.boundary()] {
+                    edges.push((defect, n_defects + defect, distance));
+                }
+                for other in defect + 1..n_defects {
+                    edges.push((n_defects + defect, n_defects + other, 0));
+                }
+            }
+            2 * n_defects
+        } else {
+            n_defects
+        };
+
+        let mates = min_weight_perfect_matching(n_vertices, &edges)?;
+        let mut correction = vec![GF2::B0; self.n_bits];
+        for (defect, mate) in mates.into_iter().enumerate().take(n_defects) {
+            let target = if mate < n_defects {
+                if mate < defect {
+                    continue;
+                }
+                defects[mate]
+            } else {
+                self.boundary()
+            };
+            for bit in paths[defect].get_path_to(target) {
+                correction[bit] = correction[bit] + GF2::B1;
+            }
+        }
+        Some(correction)
+    }
+
+    // Computes the shortest paths from `source` with Dijkstra's algorithm. The paths never go
+    // through the boundary.
+    fn shortest_paths_from(&self, source: usize) -> ShortestPaths {
+        let mut distances = vec![None; self.adjacency.len()];
+        let mut previous = vec![None; self.adjacency.len()];
+        let mut queue = BinaryHeap::new();
+        distances[source] = Some(0);
+        queue.push(Reverse((0, source)));
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            if distances[node] != Some(distance) || node == self.boundary() {
+                continue;
+            }
+            for (neighbor, bit) in self.adjacency[node].iter() {
+                let new_distance = distance + self.weights[*bit];
+                if !matches!(distances[*neighbor], Some(old) if old <= new_distance) {
+                    distances[*neighbor] = Some(new_distance);
+                    previous[*neighbor] = Some((node, *bit));
+                    queue.push(Reverse((new_distance, *neighbor)));
+                }
+            }
+        }
+        ShortestPaths {
+            distances,
+            previous,
+        }
+    }
+}
+
+struct ShortestPaths {
+    distances: Vec<Option<i64>>,
+    previous: Vec<Option<(usize, usize)>>, // (node, bit) before each node.
+}
+
+impl ShortestPaths {
+    fn get_path_to(&self, target: usize) -> Vec<usize> {
+        let mut bits = Vec::new();
+        let mut node = target;
+        while let Some((previous, bit)) = self.previous[node] {
+            bits.push(bit);
+            node = previous;
+        }
+        bits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{GF4Stabilizers, NoiseModel, SpacetimeCode, SyndromeExtraction};
+
+    #[test]
+    #[should_panic]
+    fn priors_above_one_half_are_rejected() {
+        MatchingDecoder::with_prob(0.1)
+            .for_code(ParityCheckMatrix::repetition_code(3))
+            .with_priors(vec![0.1, 0.6, 0.1]);
+    }
+
+    #[test]
+    fn repetition_code_corrects_up_to_half_distance() {
+        let code = ParityCheckMatrix::repetition_code(7);
+        let mut decoder = MatchingDecoder::with_prob(0.1).for_code(code);
+        for error in [vec![], vec![3], vec![0, 6], vec![1, 2, 5]].iter() {
+            assert_eq!(decoder.decode(error), MatchingResult::Success);
+        }
+        assert_eq!(decoder.decode(&vec![0, 1, 2, 3]), MatchingResult::Failure);
+    }
+
+    #[test]
+    fn priors_change_the_correction() {
+        let code = ParityCheckMatrix::repetition_code(3);
+        let syndrome = vec![GF2::B1, GF2::B0];
+        let decoder = MatchingDecoder::with_prob(0.1).for_code(code.clone());
+        assert_eq!(
+            decoder.find_correction(&syndrome),
+            vec![GF2::B1, GF2::B0, GF2::B0]
+        );
+        let decoder = MatchingDecoder::with_prob(0.1)
+            .for_code(code)
+            .with_priors(vec![0.001, 0.1, 0.1]);
+        assert_eq!(
+            decoder.find_correction(&syndrome),
+            vec![GF2::B0, GF2::B1, GF2::B1]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn bits_in_many_checks_are_rejected() {
+        MatchingDecoder::with_prob(0.1).for_code(GF4Stabilizers::steane_code().z_checks().clone());
+    }
+
+    #[test]
+    fn surface_code_corrects_single_errors_of_spacetime_code() {
+        let surface_code = GF4Stabilizers::surface_code(3);
+        let code = SpacetimeCode::for_x_errors(&surface_code, 3);
+        let mut decoder = MatchingDecoder::with_prob(0.01)
+            .for_code(code.get_checks().clone())
+            .with_logicals(code.get_logicals().clone());
+        for bit in 0..code.get_n_bits() {
+            assert_eq!(decoder.decode(&vec![bit]), MatchingResult::Success);
+        }
+        let logical = surface_code.get_x_logicals()[0].clone();
+        assert_eq!(decoder.decode(&logical), MatchingResult::Failure);
+    }
+
+    #[test]
+    fn graphlike_detector_error_model_corrects_single_faults() {
+        let code = GF4Stabilizers::from_parity_check_matrices(
+            ParityCheckMatrix::with_n_bits(5),
+            ParityCheckMatrix::repetition_code(5),
+        )
+        .unwrap();
+        let circuit = SyndromeExtraction::from_code(&code)
+            .with_n_rounds(3)
+            .with_noise_model(NoiseModel::uniform(0.001))
+            .build_circuit();
+        let model = DetectorErrorModel::from_circuit(&circuit);
+        let mut decoder = MatchingDecoder::from_detector_error_model(&model);
+        for mechanism in 0..model.get_n_mechanisms() {
+            assert_eq!(decoder.decode(&vec![mechanism]), MatchingResult::Success);
+        }
+    }
+}
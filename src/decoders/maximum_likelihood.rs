@@ -0,0 +1,409 @@
+//! Exact maximum-likelihood decoders for small codes.
+//!
+//! These decoders are exponential either in the number of checks or in the dimension of the
+//! code. They are meant to give the ground-truth performance of small codes to validate other
+//! decoders.
+
+use super::bit_flipping::{get_random_word_with_rng, get_word_log_probability};
+use super::{Decoder, DecodingResult};
+use crate::{ParityCheckMatrix, GF2};
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum-likelihood decoder for the binary symmetric channel using a table of coset leaders.
+///
+/// For each syndrome, the table contains a minimum weight error with that syndrome. Since the
+/// channel error probability is below 0.5, a minimum weight error is a most likely one. The
+/// table has `2^rank` entries and is built by a breadth-first search on the syndromes when the
+/// code is set. Thus, the rank of the code is limited to 24.
+///
+/// The random errors are the words received when the all-zero codeword is sent through the
+/// channel. The decoding succeeds if the word is decoded to the all-zero codeword.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::hamming_code(3);
+/// let mut decoder = SyndromeTableDecoder::with_prob(0.1).for_code(code);
+/// assert_eq!(decoder.get_n_syndromes(), 8);
+///
+/// let mut word = vec![GF2::B0; 7];
+/// word[5] = GF2::B1;
+/// assert_eq!(decoder.decode(&word), MLResult::Success);
+///
+/// word[2] = GF2::B1;
+/// assert_eq!(decoder.decode(&word), MLResult::Failure);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SyndromeTableDecoder {
+    code: ParityCheckMatrix,
+    prob: f64,
+    // The last bit of the coset leader of each syndrome and the syndrome of the rest of the
+    // leader. There is no bit for the zero syndrome.
+    table: HashMap<u64, (usize, u64)>,
+}
+
+/// Maximum-likelihood decoder for the binary symmetric channel enumerating all codewords.
+///
+/// The received word is decoded to a closest codeword. The `2^k` codewords are generated from
+/// a basis of the code, that is the rows of a generator matrix. If many codewords are at the
+/// same minimal distance, the decoding is a failure. Thus, the failure rate is an upper bound on
+/// the failure rate of a maximum-likelihood decoder breaking ties at random.
+///
+/// The random errors are the words received when the all-zero codeword is sent through the
+/// channel. The decoding succeeds if the word is decoded to the all-zero codeword.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = ParityCheckMatrix::repetition_code(4);
+/// let mut decoder = CodewordEnumerationDecoder::with_prob(0.1).for_code(code);
+/// assert_eq!(decoder.get_n_codewords(), 2);
+///
+/// let word = vec![GF2::B1, GF2::B0, GF2::B0, GF2::B0];
+/// assert_eq!(decoder.decode(&word), MLResult::Success);
+///
+/// // Equally close to both codewords.
+/// let word = vec![GF2::B1, GF2::B1, GF2::B0, GF2::B0];
+/// assert_eq!(decoder.decode(&word), MLResult::Failure);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodewordEnumerationDecoder {
+    code: ParityCheckMatrix,
+    prob: f64,
+    codewords: Vec<Vec<GF2>>,
+}
+
+/// The result of a maximum-likelihood decoder.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MLResult {
+    Failure,
+    Success,
+}
+
+impl DecodingResult for MLResult {
+    fn is_success(&self) -> bool {
+        self == &Self::Success
+    }
+}
+
+// The largest number of checks and rank of a syndrome table and the largest dimension of an
+// enumerated code.
+const MAX_N_CHECKS: usize = 64;
+const MAX_RANK: usize = 24;
+const MAX_DIMENSION: usize = 24;
+
+impl SyndromeTableDecoder {
+    // ***** Construction *****
+
+    /// Creates a syndrome table decoder for an empty code and a binary symmetric channel of
+    /// error probability `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 0.5.
+    pub fn with_prob(prob: f64) -> Self {
+        if !(0.0..=0.5).contains(&prob) {
+            panic!("invalid probability");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            prob,
+            table: get_syndrome_table(&ParityCheckMatrix::new()),
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of syndromes in the table.
+    pub fn get_n_syndromes(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns a minimum weight error with the given `syndrome` or `None` if there is no such
+    /// error.
+    ///
+    /// The error is given by the positions of its non-zero bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `syndrome` is not the number of checks.
+    pub fn get_coset_leader(&self, syndrome: &[GF2]) -> Option<Vec<usize>> {
+        if syndrome.len() != self.code.get_n_checks() {
+            panic!("wrong syndrome length");
+        }
+        let mut syndrome = pack(syndrome);
+        let mut leader = Vec::new();
+        while syndrome != 0 {
+            let (bit, previous) = self.table.get(&syndrome)?;
+            leader.push(*bit);
+            syndrome = *previous;
+        }
+        leader.sort_unstable();
+        Some(leader)
+    }
+}
+
+impl Decoder for SyndromeTableDecoder {
+    type Error = Vec<GF2>; // The received word.
+    type Result = MLResult;
+    type Code = ParityCheckMatrix;
+
+    /// # Panic
+    ///
+    /// Panics if the code has more than 64 checks or a rank larger than 24.
+    fn for_code(mut self, code: Self::Code) -> Self {
+        self.table = get_syndrome_table(&code);
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.table = get_syndrome_table(&ParityCheckMatrix::new());
+        std::mem::take(&mut self.code)
+    }
+
+    /// # Panic
+    ///
+    /// Panics if the length of the received word is not the number of bits.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        if error.len() != self.code.get_n_bits() {
+            panic!("wrong word length");
+        }
+        let leader = self.get_coset_leader(&self.code.get_syndrome_of(error));
+        let errors: Vec<usize> = error
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value == GF2::B1)
+            .map(|(bit, _)| bit)
+            .collect();
+        if leader == Some(errors) {
+            MLResult::Success
+        } else {
+            MLResult::Failure
+        }
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(get_word_log_probability(error, self.prob))
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        get_random_word_with_rng(self.code.get_n_bits(), self.prob, rng)
+    }
+}
+
+impl CodewordEnumerationDecoder {
+    // ***** Construction *****
+
+    /// Creates a codeword enumeration decoder for an empty code and a binary symmetric channel
+    /// of error probability `prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not between 0.0 and 0.5.
+    pub fn with_prob(prob: f64) -> Self {
+        if !(0.0..=0.5).contains(&prob) {
+            panic!("invalid probability");
+        }
+        Self {
+            code: ParityCheckMatrix::new(),
+            prob,
+            codewords: vec![Vec::new()],
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of codewords.
+    pub fn get_n_codewords(&self) -> usize {
+        self.codewords.len()
+    }
+
+    /// Returns the closest codewords to `word`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `word` is not the number of bits.
+    pub fn get_closest_codewords(&self, word: &[GF2]) -> Vec<&[GF2]> {
+        if word.len() != self.code.get_n_bits() {
+            panic!("wrong word length");
+        }
+        let distances: Vec<usize> = self
+            .codewords
+            .iter()
+            .map(|codeword| {
+                codeword
+                    .iter()
+                    .zip(word.iter())
+                    .filter(|(first, second)| first != second)
+                    .count()
+            })
+            .collect();
+        let min_distance = distances.iter().min().cloned().unwrap_or(0);
+        self.codewords
+            .iter()
+            .zip(distances)
+            .filter(|(_, distance)| *distance == min_distance)
+            .map(|(codeword, _)| codeword.as_slice())
+            .collect()
+    }
+}
+
+impl Decoder for CodewordEnumerationDecoder {
+    type Error = Vec<GF2>; // The received word.
+    type Result = MLResult;
+    type Code = ParityCheckMatrix;
+
+    /// # Panic
+    ///
+    /// Panics if the code has a dimension larger than 24.
+    fn for_code(mut self, code: Self::Code) -> Self {
+        self.codewords = get_codewords(&code);
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        self.codewords = vec![Vec::new()];
+        std::mem::take(&mut self.code)
+    }
+
+    /// # Panic
+    ///
+    /// Panics if the length of the received word is not the number of bits.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        match self.get_closest_codewords(error).as_slice() {
+            [codeword] if codeword.iter().all(|bit| *bit == GF2::B0) => MLResult::Success,
+            _ => MLResult::Failure,
+        }
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(get_word_log_probability(error, self.prob))
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        get_random_word_with_rng(self.code.get_n_bits(), self.prob, rng)
+    }
+}
+
+fn pack(syndrome: &[GF2]) -> u64 {
+    syndrome
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value == GF2::B1)
+        .fold(0, |packed, (check, _)| packed | (1 << check))
+}
+
+// Builds the coset leaders by a breadth-first search from the zero syndrome where each step
+// adds a bit to the leader.
+fn get_syndrome_table(code: &ParityCheckMatrix) -> HashMap<u64, (usize, u64)> {
+    if code.get_n_checks() > MAX_N_CHECKS {
+        panic!("too many checks for a syndrome table");
+    }
+    if code.get_rank() > MAX_RANK {
+        panic!("rank too large for a syndrome table");
+    }
+    let bit_syndromes: Vec<u64> = code
+        .get_transposed_matrix()
+        .checks_iter()
+        .map(|checks| checks.iter().fold(0, |packed, check| packed | (1 << check)))
+        .collect();
+    let mut table = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+    while let Some(syndrome) = queue.pop_front() {
+        for (bit, bit_syndrome) in bit_syndromes.iter().enumerate() {
+            let next = syndrome ^ bit_syndrome;
+            if next != 0 && !table.contains_key(&next) {
+                table.insert(next, (bit, syndrome));
+                queue.push_back(next);
+            }
+        }
+    }
+    table.insert(0, (0, 0));
+    table
+}
+
+fn get_codewords(code: &ParityCheckMatrix) -> Vec<Vec<GF2>> {
+    let basis = code.get_codeword_basis();
+    if basis.len() > MAX_DIMENSION {
+        panic!("dimension too large to enumerate codewords");
+    }
+    let mut codewords = vec![vec![GF2::B0; code.get_n_bits()]];
+    for generator in basis {
+        let shifted: Vec<Vec<GF2>> = codewords
+            .iter()
+            .map(|codeword| {
+                let mut codeword = codeword.clone();
+                generator
+                    .iter()
+                    .for_each(|bit| codeword[*bit] = codeword[*bit] + GF2::B1);
+                codeword
+            })
+            .collect();
+        codewords.extend(shifted);
+    }
+    codewords
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BitFlippingDecoder;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn coset_leaders_have_minimum_weight() {
+        let code = ParityCheckMatrix::bch_code(4, 5);
+        let decoder = SyndromeTableDecoder::with_prob(0.1).for_code(code.clone());
+        assert_eq!(decoder.get_n_syndromes(), 1 << code.get_rank());
+        for bits in [vec![], vec![3], vec![0, 14], vec![2, 7]].iter() {
+            let mut word = vec![GF2::B0; 15];
+            bits.iter().for_each(|bit| word[*bit] = GF2::B1);
+            let leader = decoder.get_coset_leader(&code.get_syndrome_of(&word));
+            assert_eq!(leader.as_ref(), Some(bits));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn syndrome_table_rank_is_bounded() {
+        SyndromeTableDecoder::with_prob(0.1).for_code(ParityCheckMatrix::identity_with_n_bits(25));
+    }
+
+    #[test]
+    fn both_decoders_agree_when_the_closest_codeword_is_unique() {
+        let mut rng = ChaCha8Rng::seed_from_u64(23);
+        let code = ParityCheckMatrix::hamming_code(4);
+        let mut table = SyndromeTableDecoder::with_prob(0.1).for_code(code.clone());
+        let mut enumeration = CodewordEnumerationDecoder::with_prob(0.1).for_code(code);
+        for _ in 0..200 {
+            let word = table.get_random_error_with_rng(&mut rng);
+            let enumeration_result = enumeration.decode(&word);
+            if enumeration.get_closest_codewords(&word).len() == 1 {
+                assert_eq!(table.decode(&word), enumeration_result);
+            } else {
+                assert_eq!(enumeration_result, MLResult::Failure);
+            }
+        }
+    }
+
+    #[test]
+    fn bit_flipping_never_beats_the_oracle() {
+        let mut rng = ChaCha8Rng::seed_from_u64(29);
+        let code = ParityCheckMatrix::repetition_code(7);
+        let mut oracle = SyndromeTableDecoder::with_prob(0.2).for_code(code.clone());
+        let mut bit_flipping = BitFlippingDecoder::with_prob(0.2).for_code(code);
+        for _ in 0..200 {
+            let word = oracle.get_random_error_with_rng(&mut rng);
+            if bit_flipping.decode(&word).is_success() {
+                assert!(oracle.decode(&word).is_success());
+            }
+        }
+    }
+}
@@ -11,6 +11,24 @@ use n_iterations_simulator::NIterationsSimulator;
 mod n_events_simulator;
 use n_events_simulator::NEventsSimulator;
 
+mod adaptive_simulator;
+use adaptive_simulator::AdaptiveSimulator;
+pub use adaptive_simulator::StoppingCriteria;
+
+pub mod checkpoint;
+pub use checkpoint::SimulationCheckpoint;
+
+pub mod monitor;
+pub use monitor::{CancellationToken, SimulationMonitor, SimulationObserver, SimulationProgress};
+
+mod statistics;
+
+pub mod bit_error_statistics;
+pub use bit_error_statistics::{BitErrorStatistics, ResidualErrors};
+
+mod bit_error_simulator;
+use bit_error_simulator::BitErrorSimulator;
+
 // pub mod belief_propagation;
 // pub use belief_propagation::*;
 
@@ -20,10 +38,38 @@ pub use erasure::*;
 pub mod quantum_erasure;
 pub use quantum_erasure::*;
 
+pub mod spacetime_erasure;
+pub use spacetime_erasure::*;
+
+pub mod union_find;
+pub use union_find::*;
+
+pub mod matching;
+pub use matching::*;
+
+pub mod bit_flipping;
+pub use bit_flipping::*;
+
+pub mod maximum_likelihood;
+pub use maximum_likelihood::*;
+
+pub mod threshold;
+pub use threshold::*;
+
+pub mod rare_events;
+pub use rare_events::*;
+
+pub mod failure_polynomial;
+pub use failure_polynomial::ErasureFailurePolynomial;
+
 /// An interface to deal with decoders
 ///
 /// This is the global decoder trait. For more details, see each decoder implementation.
-pub trait Decoder: Send + Sync + Sized {
+///
+/// The simulations run in parallel where each worker decodes with its own clone of the decoder.
+/// Each decoded error is generated from its own seed such that the results are the same for any
+/// number of threads.
+pub trait Decoder: Clone + Send + Sync + Sized {
     /// The type of code the decoder is using.
     type Code;
 
@@ -46,6 +92,26 @@ pub trait Decoder: Send + Sync + Sized {
     /// Generates a random error with random number generator `rng`.
     fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error;
 
+    /// Returns the bits that are still wrong or erased after decoding `error` into `result`.
+    ///
+    /// This is `None` by default for decoders that can't identify these bits.
+    fn get_residual_errors(
+        &self,
+        _error: &Self::Error,
+        _result: &Self::Result,
+    ) -> Option<ResidualErrors> {
+        None
+    }
+
+    /// Returns the logarithm of the probability, or of the probability density for continuous
+    /// errors, that `get_random_error_with_rng` generates `error`.
+    ///
+    /// This is `None` by default for decoders that don't know this probability. It is needed by
+    /// the rare-event estimators.
+    fn get_error_log_probability(&self, _error: &Self::Error) -> Option<f64> {
+        None
+    }
+
     /// Generates a random error with the default random number generator.
     fn get_random_error(&self) -> Self::Error {
         self.get_random_error_with_rng(&mut thread_rng())
@@ -64,7 +130,7 @@ pub trait Decoder: Send + Sync + Sized {
     /// Simulates decoding random error using `self` for `n_iterations` with random number
     /// generator `rng`.
     fn simulate_n_iterations_with_rng<R: Rng>(
-        &mut self,
+        &self,
         n_iterations: usize,
         rng: &mut R,
     ) -> SimulationResult {
@@ -75,16 +141,48 @@ pub trait Decoder: Send + Sync + Sized {
 
     /// Simulates decoding random error using `self` for `n_iterations` using the thread random
     /// number generator `rng`.
-    fn simulate_n_iterations(&mut self, n_iterations: usize) -> SimulationResult {
+    fn simulate_n_iterations(&self, n_iterations: usize) -> SimulationResult {
         self.simulate_n_iterations_with_rng(n_iterations, &mut thread_rng())
     }
 
+    /// Simulates decoding random error using `self` for `n_iterations` with random number
+    /// generator `rng`, reporting the progress to `monitor`.
+    ///
+    /// If the token of `monitor` is cancelled, returns the result of the completed iterations.
+    /// Otherwise, the result is the same as `simulate_n_iterations_with_rng`.
+    fn simulate_n_iterations_with_monitor_and_rng<R: Rng>(
+        &self,
+        n_iterations: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> SimulationResult {
+        NIterationsSimulator::from(self)
+            .simulate_n_iterations_with_monitor_and_rng(n_iterations, monitor, rng)
+            .get_result()
+    }
+
+    /// Simulates decoding random error using `self` for `n_iterations` with the thread random
+    /// number generator, reporting the progress to `monitor`.
+    ///
+    /// If the token of `monitor` is cancelled, returns the result of the completed iterations.
+    fn simulate_n_iterations_with_monitor(
+        &self,
+        n_iterations: usize,
+        monitor: &mut SimulationMonitor,
+    ) -> SimulationResult {
+        self.simulate_n_iterations_with_monitor_and_rng(n_iterations, monitor, &mut thread_rng())
+    }
+
     /// Simulates the decoder until `n_events` are found with random number
     /// generator `rng`.
     ///
     /// That is, simulate until `n_events` successes and `n_events` are found.
+    ///
+    /// Each of the `n_events` parallel runs stops after its first success and first failure.
+    /// This gives no guarantee on the precision of the failure rate. Use `simulate_until_with_rng`
+    /// for a target precision or number of failures.
     fn simulate_until_n_events_are_found_with_rng<R: Rng>(
-        &mut self,
+        &self,
         n_events: usize,
         rng: &mut R,
     ) -> SimulationResult {
@@ -97,10 +195,97 @@ pub trait Decoder: Send + Sync + Sized {
     /// generator `rng`.
     ///
     /// That is, simulate until `n_events` successes and `n_events` are found.
-    fn simulate_until_n_events_are_found(&mut self, n_events: usize) -> SimulationResult {
+    fn simulate_until_n_events_are_found(&self, n_events: usize) -> SimulationResult {
         self.simulate_until_n_events_are_found_with_rng(n_events, &mut thread_rng())
     }
-    
+
+    /// Simulates the decoder until `n_events` are found with random number generator `rng`,
+    /// reporting the number of events found to `monitor`.
+    ///
+    /// If the token of `monitor` is cancelled, the parallel runs stop and the result contains
+    /// all the decoded errors so far. Otherwise, the result is the same as
+    /// `simulate_until_n_events_are_found_with_rng`.
+    fn simulate_until_n_events_are_found_with_monitor_and_rng<R: Rng>(
+        &self,
+        n_events: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> SimulationResult {
+        NEventsSimulator::from(self)
+            .simulate_until_n_events_are_found_with_monitor_and_rng(n_events, monitor, rng)
+            .get_result()
+    }
+
+    /// Simulates the decoder until `n_events` are found with the thread random number
+    /// generator, reporting the number of events found to `monitor`.
+    ///
+    /// If the token of `monitor` is cancelled, the parallel runs stop and the result contains
+    /// all the decoded errors so far.
+    fn simulate_until_n_events_are_found_with_monitor(
+        &self,
+        n_events: usize,
+        monitor: &mut SimulationMonitor,
+    ) -> SimulationResult {
+        self.simulate_until_n_events_are_found_with_monitor_and_rng(
+            n_events,
+            monitor,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Simulates the decoder by batches until the stopping `criteria` are met with random
+    /// number generator `rng`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if neither a maximal number of iterations nor a time budget is set.
+    fn simulate_until_with_rng<R: Rng>(
+        &self,
+        criteria: &StoppingCriteria,
+        rng: &mut R,
+    ) -> SimulationResult {
+        AdaptiveSimulator::from(self)
+            .simulate_until_with_rng(criteria, rng)
+            .get_result()
+    }
+
+    /// Simulates the decoder by batches until the stopping `criteria` are met with the thread
+    /// random number generator.
+    ///
+    /// # Panic
+    ///
+    /// Panics if neither a maximal number of iterations nor a time budget is set.
+    fn simulate_until(&self, criteria: &StoppingCriteria) -> SimulationResult {
+        self.simulate_until_with_rng(criteria, &mut thread_rng())
+    }
+
+    /// Simulates decoding random error using `self` for `n_iterations` with random number
+    /// generator `rng` and collects the bit error rate, the distribution of the failure weights
+    /// and the number of failures of each bit.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoder doesn't report its residual errors.
+    fn simulate_bit_errors_n_iterations_with_rng<R: Rng>(
+        &self,
+        n_iterations: usize,
+        rng: &mut R,
+    ) -> BitErrorStatistics {
+        BitErrorSimulator::from(self)
+            .simulate_n_iterations_with_rng(n_iterations, rng)
+            .get_statistics()
+    }
+
+    /// Simulates decoding random error using `self` for `n_iterations` with the thread random
+    /// number generator and collects the bit error rate, the distribution of the failure
+    /// weights and the number of failures of each bit.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoder doesn't report its residual errors.
+    fn simulate_bit_errors_n_iterations(&self, n_iterations: usize) -> BitErrorStatistics {
+        self.simulate_bit_errors_n_iterations_with_rng(n_iterations, &mut thread_rng())
+    }
 }
 
 /// An interface for decoder outcome.
@@ -117,7 +302,7 @@ pub trait DecodingResult: Send + Sync {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ressources {
     rank_mtx: Option<Vec<Vec<usize>>>,
     sum_vec: Option<Vec<usize>>,
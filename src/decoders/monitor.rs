@@ -0,0 +1,257 @@
+//! Progress reports and cancellation of long simulations.
+
+use super::SimulationResult;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The default number of progress reports during a simulation.
+const DEFAULT_N_REPORTS: usize = 100;
+
+/// A flag shared between a simulation and its controller to stop the simulation.
+///
+/// The clones of a token share the same flag. Once cancelled, a simulation stops as soon as
+/// possible and returns the result of the work completed so far.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels `self` and all its clones.
+    pub fn cancel(&self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if `self` is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A snapshot of a running simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationProgress {
+    n_completed: u64,
+    n_total: u64,
+    result: SimulationResult,
+    elapsed: Duration,
+}
+
+impl SimulationProgress {
+    /// Returns the number of completed work units. Those are iterations, events or codes
+    /// depending on the simulation.
+    pub fn get_n_completed(&self) -> u64 {
+        self.n_completed
+    }
+
+    /// Returns the total number of work units of the simulation.
+    pub fn get_n_total(&self) -> u64 {
+        self.n_total
+    }
+
+    /// Returns the fraction of completed work units.
+    pub fn get_fraction(&self) -> f64 {
+        if self.n_total == 0 {
+            1.0
+        } else {
+            self.n_completed as f64 / self.n_total as f64
+        }
+    }
+
+    /// Returns the result of the completed work units.
+    pub fn get_result(&self) -> SimulationResult {
+        self.result
+    }
+
+    /// Returns the time since the start of the simulation.
+    pub fn get_elapsed_time(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the estimated remaining time assuming the remaining work units take as long as
+    /// the completed ones.
+    ///
+    /// Returns `None` if no work unit is completed.
+    pub fn get_eta(&self) -> Option<Duration> {
+        if self.n_completed == 0 {
+            None
+        } else {
+            let n_remaining = self.n_total.saturating_sub(self.n_completed);
+            Some(
+                self.elapsed
+                    .mul_f64(n_remaining as f64 / self.n_completed as f64),
+            )
+        }
+    }
+}
+
+/// An interface to receive the progress of a simulation.
+///
+/// It is implemented for any closure taking a `&SimulationProgress`.
+pub trait SimulationObserver {
+    /// Called by the simulation after each completed chunk of work.
+    fn on_progress(&mut self, progress: &SimulationProgress);
+}
+
+impl<F: FnMut(&SimulationProgress)> SimulationObserver for F {
+    fn on_progress(&mut self, progress: &SimulationProgress) {
+        self(progress)
+    }
+}
+
+/// The observer and cancellation token of a simulation.
+///
+/// The work is split in chunks simulated in parallel one after the other. The observer is
+/// called from the calling thread after each chunk and the token is checked before each work
+/// unit. Since each work unit keeps its own random seed, the chunks don't change the results of
+/// a simulation that is not cancelled.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let token = CancellationToken::new();
+/// let stopper = token.clone();
+/// let mut monitor = SimulationMonitor::new()
+///     .with_observer(move |progress: &SimulationProgress| {
+///         if progress.get_result().get_n_failures() >= 100 {
+///             stopper.cancel();
+///         }
+///     })
+///     .with_cancellation_token(token.clone())
+///     .with_n_reports(1000);
+///
+/// let code = ParityCheckMatrix::repetition_code(3);
+/// let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+/// let result = decoder.simulate_n_iterations_with_monitor(1_000_000, &mut monitor);
+///
+/// assert!(token.is_cancelled());
+/// assert!(result.get_n_iterations() < 1_000_000);
+/// assert!(result.get_n_failures() >= 100);
+/// ```
+pub struct SimulationMonitor<'a> {
+    observer: Option<Box<dyn SimulationObserver + 'a>>,
+    token: CancellationToken,
+    n_reports: usize,
+    start: Instant,
+}
+
+impl<'a> SimulationMonitor<'a> {
+    // ***** Construction *****
+
+    /// Creates a monitor without observer, with a token that is never cancelled and with 100
+    /// reports.
+    pub fn new() -> Self {
+        Self {
+            observer: None,
+            token: CancellationToken::new(),
+            n_reports: DEFAULT_N_REPORTS,
+            start: Instant::now(),
+        }
+    }
+
+    /// Sets the `observer` receiving the progress of the simulation.
+    pub fn with_observer<O: SimulationObserver + 'a>(mut self, observer: O) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Sets the `token` stopping the simulation.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Sets the approximate number of progress reports during a simulation. The default is 100.
+    ///
+    /// There are less reports if there are less work units than reports or if the chunks would
+    /// be too small to keep all the threads busy.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_reports` is 0.
+    pub fn with_n_reports(mut self, n_reports: usize) -> Self {
+        if n_reports == 0 {
+            panic!("the number of reports must be positive");
+        }
+        self.n_reports = n_reports;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the cancellation token of `self`.
+    pub fn get_cancellation_token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Checks if the simulation is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    // ***** Simulation hooks *****
+
+    // Starts the clock and splits `n_total` work units in chunks.
+    pub(crate) fn start(&mut self, n_total: usize) -> impl Iterator<Item = Range<usize>> {
+        self.start = Instant::now();
+        let chunk_size = n_total
+            .div_ceil(self.n_reports)
+            .max(rayon::current_num_threads())
+            .max(1);
+        (0..n_total)
+            .step_by(chunk_size)
+            .map(move |first| first..(first + chunk_size).min(n_total))
+    }
+
+    pub(crate) fn report(&mut self, n_completed: usize, n_total: usize, result: SimulationResult) {
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_progress(&SimulationProgress {
+                n_completed: n_completed as u64,
+                n_total: n_total as u64,
+                result,
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+}
+
+impl<'a> Default for SimulationMonitor<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunks_cover_all_work_units() {
+        let mut monitor = SimulationMonitor::new().with_n_reports(3);
+        let chunks: Vec<Range<usize>> = monitor.start(1000).collect();
+        assert_eq!(chunks.first().unwrap().start, 0);
+        assert_eq!(chunks.last().unwrap().end, 1000);
+        assert!(chunks.windows(2).all(|pair| pair[0].end == pair[1].start));
+        assert!(chunks.len() <= 3);
+    }
+
+    #[test]
+    fn eta_is_proportional_to_remaining_work() {
+        let progress = SimulationProgress {
+            n_completed: 25,
+            n_total: 100,
+            result: SimulationResult::new(),
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(progress.get_fraction(), 0.25);
+        assert_eq!(progress.get_eta(), Some(Duration::from_secs(30)));
+    }
+}
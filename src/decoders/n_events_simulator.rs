@@ -1,18 +1,19 @@
-use super::{Decoder, SimulationResult};
+use super::{CancellationToken, Decoder, SimulationMonitor, SimulationResult};
 use rand::distributions::Standard;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
+use std::ops::Range;
 
 pub(super) struct NEventsSimulator<'a, D> {
-    decoder: &'a mut D,
+    decoder: &'a D,
     n_events: usize,
     result: SimulationResult,
     random_seeds: Vec<u64>,
 }
 
 impl<'a, D: Decoder> NEventsSimulator<'a, D> {
-    pub(super) fn from(decoder: &'a mut D) -> Self {
+    pub(super) fn from(decoder: &'a D) -> Self {
         Self {
             decoder,
             n_events: 0,
@@ -31,6 +32,27 @@ impl<'a, D: Decoder> NEventsSimulator<'a, D> {
         self
     }
 
+    pub(super) fn simulate_until_n_events_are_found_with_monitor_and_rng<R: Rng>(
+        mut self,
+        n_events: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> Self {
+        self.initialize_simulation_with_n_events_and_rng(n_events, rng);
+        let mut n_completed = 0;
+        for chunk in monitor.start(n_events) {
+            let (n_completed_in_chunk, result) =
+                self.simulate_range(chunk, monitor.get_cancellation_token());
+            n_completed += n_completed_in_chunk;
+            self.result = self.result.combine_with(result);
+            monitor.report(n_completed, self.n_events, self.result);
+            if monitor.is_cancelled() {
+                break;
+            }
+        }
+        self
+    }
+
     fn initialize_simulation_with_n_events_and_rng<R: Rng>(
         &mut self,
         n_events: usize,
@@ -41,26 +63,47 @@ impl<'a, D: Decoder> NEventsSimulator<'a, D> {
     }
 
     fn run_the_simulation(&mut self) {
-        let results = (0..self.n_events)
-            // .into_par_iter()
-            .map(|thread_index| self.simulate_thread_until_one_event_is_found(thread_index));
-
-        let mut n_successes: u64 = 0;
-        let mut n_failures: u64 = 0;
-
-        for simres in results {
-            n_successes += simres.get_n_successes();
-            n_failures += simres.get_n_failures();
-        }
+        let (_, result) = self.simulate_range(0..self.n_events, &CancellationToken::new());
+        self.result = result;
+    }
 
-        self.result = SimulationResult::with_n_successes_and_failures(n_successes, n_failures);
+    // Returns the number of events found and the combined result of the threads in `range`.
+    // After the cancellation of `token`, the threads stop and keep their partial results.
+    fn simulate_range(
+        &self,
+        range: Range<usize>,
+        token: &CancellationToken,
+    ) -> (usize, SimulationResult) {
+        range
+            .into_par_iter()
+            .map_init(
+                || self.decoder.clone(),
+                |decoder, thread_index| {
+                    self.simulate_thread_until_one_event_is_found(decoder, thread_index, token)
+                },
+            )
+            .map(|result| {
+                let is_completed = !result.has_not_at_least_one_success_and_one_failure();
+                (is_completed as usize, result)
+            })
+            .reduce(
+                || (0, SimulationResult::new()),
+                |(completed, result), (other_completed, other_result)| {
+                    (completed + other_completed, result.combine_with(other_result))
+                },
+            )
     }
 
-    fn simulate_thread_until_one_event_is_found(&mut self, thread_index: usize) -> SimulationResult {
+    fn simulate_thread_until_one_event_is_found(
+        &self,
+        decoder: &mut D,
+        thread_index: usize,
+        token: &CancellationToken,
+    ) -> SimulationResult {
         let mut rng = self.get_thread_rng(thread_index);
         let mut result = SimulationResult::new();
-        while result.has_not_at_least_one_success_and_one_failure() {
-            let decoding_result = self.decoder.decode_random_error_with_rng(&mut rng);
+        while result.has_not_at_least_one_success_and_one_failure() && !token.is_cancelled() {
+            let decoding_result = decoder.decode_random_error_with_rng(&mut rng);
             result.add_decoding_result(decoding_result);
         }
         result
@@ -95,10 +138,10 @@ mod test {
         let code = ParityCheckMatrix::with_n_bits(3)
         .with_checks(vec![vec![0, 1], vec![1, 2]]);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
 
-        let result = NEventsSimulator::from(&mut decoder)
+        let result = NEventsSimulator::from(&decoder)
             .simulate_until_n_events_are_found_with_rng(10, &mut rng.clone())
             .get_result();
 
@@ -111,15 +154,15 @@ mod test {
         let code = ParityCheckMatrix::with_n_bits(3)
             .with_checks(vec![vec![0, 1], vec![1, 2]]);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
 
-        let result_0 = NEventsSimulator::from(&mut decoder)
+        let result_0 = NEventsSimulator::from(&decoder)
             .simulate_until_n_events_are_found_with_rng(10, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
-        let result_1 = NEventsSimulator::from(&mut decoder)
+        let result_1 = NEventsSimulator::from(&decoder)
             .simulate_until_n_events_are_found_with_rng(10, &mut rng.clone())
             .get_result()
             .get_success_rate();
@@ -132,19 +175,86 @@ mod test {
         let code = ParityCheckMatrix::with_n_bits(7)
             .with_checks(vec![vec![0, 1, 2, 4], vec![0, 1, 3, 5], vec![0, 2, 3, 6]]);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
 
-        let result_0 = NEventsSimulator::from(&mut decoder)
+        let result_0 = NEventsSimulator::from(&decoder)
             .simulate_until_n_events_are_found_with_rng(10, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
-        let result_1 = NEventsSimulator::from(&mut decoder)
+        let result_1 = NEventsSimulator::from(&decoder)
             .simulate_until_n_events_are_found_with_rng(10, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
         assert!((result_0 - result_1).abs() < 1e-6); 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_threads() {
+        let code = ParityCheckMatrix::hamming_code(4);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let results: Vec<SimulationResult> = [1, 4]
+            .iter()
+            .map(|n_threads| {
+                let mut rng = ChaCha8Rng::seed_from_u64(7);
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(*n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| {
+                        NEventsSimulator::from(&decoder)
+                            .simulate_until_n_events_are_found_with_rng(20, &mut rng)
+                            .get_result()
+                    })
+            })
+            .collect();
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn monitored_simulation_has_the_same_result() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let rng = ChaCha8Rng::seed_from_u64(12);
+        let result = NEventsSimulator::from(&decoder)
+            .simulate_until_n_events_are_found_with_rng(100, &mut rng.clone())
+            .get_result();
+
+        let mut n_events = 0;
+        let mut monitor = SimulationMonitor::new()
+            .with_observer(|progress: &crate::SimulationProgress| {
+                n_events = progress.get_n_completed()
+            })
+            .with_n_reports(10);
+        let monitored_result = NEventsSimulator::from(&decoder)
+            .simulate_until_n_events_are_found_with_monitor_and_rng(
+                100,
+                &mut monitor,
+                &mut rng.clone(),
+            )
+            .get_result();
+        drop(monitor);
+
+        assert_eq!(monitored_result, result);
+        assert_eq!(n_events, 100);
+    }
+
+    #[test]
+    fn cancelled_simulation_stops() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut monitor = SimulationMonitor::new().with_cancellation_token(token);
+        let result = NEventsSimulator::from(&decoder)
+            .simulate_until_n_events_are_found_with_monitor_and_rng(
+                100,
+                &mut monitor,
+                &mut ChaCha8Rng::seed_from_u64(12),
+            )
+            .get_result();
+        assert_eq!(result.get_n_iterations(), 0);
+    }
+}
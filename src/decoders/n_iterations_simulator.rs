@@ -1,21 +1,24 @@
-use super::{Decoder, DecodingResult, SimulationResult};
+use super::{CancellationToken, Decoder, DecodingResult, SimulationMonitor, SimulationResult};
 use rand::distributions::Standard;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
+use std::ops::Range;
 
 pub(super) struct NIterationsSimulator<'a, D: Decoder> {
-    decoder: &'a mut D,
+    decoder: &'a D,
     n_iterations: usize,
+    n_completed: usize,
     n_successes: usize,
     random_seeds: Vec<u64>,
 }
 
 impl<'a, D: Decoder> NIterationsSimulator<'a, D> {
-    pub(super) fn from(decoder: &'a mut D) -> Self {
+    pub(super) fn from(decoder: &'a D) -> Self {
         Self {
             decoder,
             n_iterations: 0,
+            n_completed: 0,
             n_successes: 0,
             random_seeds: Vec::new(),
         }
@@ -31,6 +34,26 @@ impl<'a, D: Decoder> NIterationsSimulator<'a, D> {
         self
     }
 
+    pub(super) fn simulate_n_iterations_with_monitor_and_rng<R: Rng>(
+        mut self,
+        n_iterations: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> Self {
+        self.initialize_simulation_with_n_iterations_and_rng(n_iterations, rng);
+        for chunk in monitor.start(n_iterations) {
+            let (n_completed, n_successes) =
+                self.simulate_range(chunk, monitor.get_cancellation_token());
+            self.n_completed += n_completed;
+            self.n_successes += n_successes;
+            monitor.report(self.n_completed, self.n_iterations, self.get_result());
+            if monitor.is_cancelled() {
+                break;
+            }
+        }
+        self
+    }
+
     fn initialize_simulation_with_n_iterations_and_rng<R: Rng>(
         &mut self,
         n_iterations: usize,
@@ -45,15 +68,34 @@ impl<'a, D: Decoder> NIterationsSimulator<'a, D> {
     }
 
     fn run_the_simulation(&mut self) {
-        self.n_successes = (0..self.n_iterations)
-            // .into_par_iter()
-            .filter(|thread_index| {
-                let mut rng = self.get_thread_rng(*thread_index);
-                self.decoder
-                    .decode_random_error_with_rng(&mut rng)
-                    .is_success()
-            })
-            .count();
+        let (n_completed, n_successes) =
+            self.simulate_range(0..self.n_iterations, &CancellationToken::new());
+        self.n_completed = n_completed;
+        self.n_successes = n_successes;
+    }
+
+    // Returns the number of completed iterations and of successes among the iterations in
+    // `range`. The iterations starting after the cancellation of `token` are skipped.
+    fn simulate_range(&self, range: Range<usize>, token: &CancellationToken) -> (usize, usize) {
+        range
+            .into_par_iter()
+            .map_init(
+                || self.decoder.clone(),
+                |decoder, thread_index| {
+                    if token.is_cancelled() {
+                        return (0, 0);
+                    }
+                    let mut rng = self.get_thread_rng(thread_index);
+                    let is_success = decoder.decode_random_error_with_rng(&mut rng).is_success();
+                    (1, is_success as usize)
+                },
+            )
+            .reduce(
+                || (0, 0),
+                |(completed, successes), (other_completed, other_successes)| {
+                    (completed + other_completed, successes + other_successes)
+                },
+            )
     }
 
     // Yep, I'm imposing ChaCha8Rng for each thread.
@@ -63,7 +105,7 @@ impl<'a, D: Decoder> NIterationsSimulator<'a, D> {
     }
 
     pub(super) fn get_result(&self) -> SimulationResult {
-        let n_failures = self.n_iterations - self.n_successes;
+        let n_failures = self.n_completed - self.n_successes;
         SimulationResult::with_n_successes_and_failures(self.n_successes as u64, n_failures as u64)
     }
 }
@@ -79,11 +121,11 @@ mod test {
 
     #[test]
     fn there_is_n_iterations() {
-        let code = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+        let code = ParityCheckMatrix::repetition_code(3);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
-        let n_iterations = NIterationsSimulator::from(&mut decoder)
+        let n_iterations = NIterationsSimulator::from(&decoder)
             .simulate_n_iterations_with_rng(1000, &mut rng.clone())
             .get_result()
             .get_n_iterations();
@@ -92,16 +134,16 @@ mod test {
 
     #[test]
     fn reproductibility_for_repetition_code() {
-        let code = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+        let code = ParityCheckMatrix::repetition_code(3);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
-        let result_0 = NIterationsSimulator::from(&mut decoder)
+        let result_0 = NIterationsSimulator::from(&decoder)
             .simulate_n_iterations_with_rng(1000, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
-        let result_1 = NIterationsSimulator::from(&mut decoder)
+        let result_1 = NIterationsSimulator::from(&decoder)
             .simulate_n_iterations_with_rng(1000, &mut rng.clone())
             .get_result()
             .get_success_rate();
@@ -117,18 +159,88 @@ mod test {
             vec![0, 2, 3, 6],
         ]);
 
-        let mut decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
         let rng = ChaCha8Rng::seed_from_u64(123);
-        let result_0 = NIterationsSimulator::from(&mut decoder)
+        let result_0 = NIterationsSimulator::from(&decoder)
             .simulate_n_iterations_with_rng(1000, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
-        let result_1 = NIterationsSimulator::from(&mut decoder)
+        let result_1 = NIterationsSimulator::from(&decoder)
             .simulate_n_iterations_with_rng(1000, &mut rng.clone())
             .get_result()
             .get_success_rate();
 
         assert!((result_0 - result_1).abs() < 1e-6);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_threads() {
+        let code = ParityCheckMatrix::hamming_code(4);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let results: Vec<SimulationResult> = [1, 4]
+            .iter()
+            .map(|n_threads| {
+                let mut rng = ChaCha8Rng::seed_from_u64(7);
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(*n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| {
+                        NIterationsSimulator::from(&decoder)
+                            .simulate_n_iterations_with_rng(1000, &mut rng)
+                            .get_result()
+                    })
+            })
+            .collect();
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn monitored_simulation_has_the_same_result() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let rng = ChaCha8Rng::seed_from_u64(11);
+        let result = NIterationsSimulator::from(&decoder)
+            .simulate_n_iterations_with_rng(1000, &mut rng.clone())
+            .get_result();
+
+        let mut reports = Vec::new();
+        let mut monitor = SimulationMonitor::new()
+            .with_observer(|progress: &crate::SimulationProgress| reports.push(*progress))
+            .with_n_reports(10);
+        let monitored_result = NIterationsSimulator::from(&decoder)
+            .simulate_n_iterations_with_monitor_and_rng(1000, &mut monitor, &mut rng.clone())
+            .get_result();
+        drop(monitor);
+
+        assert_eq!(monitored_result, result);
+        assert!(reports.len() > 1);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].get_n_completed() < pair[1].get_n_completed()));
+        assert_eq!(reports.last().unwrap().get_n_completed(), 1000);
+        assert_eq!(reports.last().unwrap().get_result(), result);
+    }
+
+    #[test]
+    fn cancelled_simulation_returns_partial_result() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let decoder = ErasureDecoder::with_prob(0.3).for_code(code);
+        let token = CancellationToken::new();
+        let stopper = token.clone();
+        let mut monitor = SimulationMonitor::new()
+            .with_observer(move |_: &crate::SimulationProgress| stopper.cancel())
+            .with_cancellation_token(token)
+            .with_n_reports(10);
+        let result = NIterationsSimulator::from(&decoder)
+            .simulate_n_iterations_with_monitor_and_rng(
+                1000,
+                &mut monitor,
+                &mut ChaCha8Rng::seed_from_u64(11),
+            )
+            .get_result();
+        assert!(result.get_n_iterations() > 0);
+        assert!(result.get_n_iterations() < 1000);
+    }
+}
@@ -1,5 +1,6 @@
 //! NOTE: Need to check the math to be sure everything work.
 
+use super::statistics::bernoulli_log_probability;
 use super::{Decoder};
 use crate::ErasureResult;
 use crate::Ressources;
@@ -13,16 +14,14 @@ use rand::{Rng};
 ///
 /// ```
 /// # use believer::*;
-/// let x_checks = ParityCheckMatrix::new(vec![vec![0, 1], vec![1, 2]], 3);
-/// let z_checks = ParityCheckMatrix::new(vec![vec![0, 1], vec![1, 2]], 3);
+/// let x_checks = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+/// let z_checks = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
 /// let erasure_prob = 0.25;
-/// let stabilizers = GF4Stabilizers::from_parity_check_matrices(x_checks, z_checks);
-/// let decoder = QuantumErasureDecoder::new(stabilizers, erasure_prob);
-/// decoder.decode(&decoder.random_error());
+/// let stabilizers = GF4Stabilizers::from_parity_check_matrices(x_checks, z_checks).unwrap();
+/// let mut decoder = QuantumErasureDecoder::new(stabilizers, erasure_prob);
+/// decoder.decode(&decoder.get_random_error());
 /// ```
-/// 
-
-
+#[derive(Clone)]
 pub struct QuantumErasureDecoder {
     stabilizers: GF4Stabilizers,
     merged: ParityCheckMatrix,
@@ -43,7 +42,7 @@ impl QuantumErasureDecoder {
     ///
     /// Panics if `erasure_prob` is not between 0.0 and 1.0.
     pub fn new(stabilizers: GF4Stabilizers, erasure_prob: f64) -> Self {
-        if erasure_prob < 0.0 || erasure_prob > 1.0 {
+        if !(0.0..=1.0).contains(&erasure_prob) {
             panic!("invalid probability");
         }
         let merged = stabilizers.merge();
@@ -67,7 +66,7 @@ impl QuantumErasureDecoder {
     }
 
     pub fn new_merged(merged: ParityCheckMatrix, erasure_prob: f64) -> Self {
-        if erasure_prob < 0.0 || erasure_prob > 1.0 {
+        if !(0.0..=1.0).contains(&erasure_prob) {
             panic!("invalid probability");
         }
         let stabilizers = GF4Stabilizers::empty_with_n_bits(merged.get_n_bits()/2);
@@ -137,6 +136,14 @@ impl Decoder for QuantumErasureDecoder {
         }
     }
 
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        Some(bernoulli_log_probability(
+            error.len(),
+            self.stabilizers.n_qubits(),
+            self.erasure_prob,
+        ))
+    }
+
     fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
         (0..self.stabilizers.n_qubits())
             .filter(|_| self.next_bit_is_erased(rng))
@@ -146,10 +153,7 @@ impl Decoder for QuantumErasureDecoder {
     fn take_code(&mut self) -> Self::Code {
         std::mem::replace(
             &mut self.stabilizers,
-            GF4Stabilizers::from_parity_check_matrices(
-                ParityCheckMatrix::new(),
-                ParityCheckMatrix::new(),
-            ),
+            GF4Stabilizers::empty_with_n_bits(0),
         )
     }
 
@@ -159,21 +163,10 @@ impl Decoder for QuantumErasureDecoder {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::Pauli::{I, X, Z};
 
     #[test]
     fn steane_code() {
-        let stabilizers = GF4Stabilizers::from_sparse_paulis(
-            vec![
-                vec![(X, 0), (X, 1), (X, 2), (X, 4)],
-                vec![(X, 0), (X, 1), (X, 3), (X, 5)],
-                vec![(X, 0), (X, 2), (X, 3), (X, 6)],
-                vec![(Z, 0), (Z, 1), (Z, 2), (Z, 4)],
-                vec![(Z, 0), (Z, 1), (Z, 3), (Z, 5)],
-                vec![(Z, 0), (Z, 2), (Z, 3), (Z, 6)],
-            ],
-            7,
-        );
+        let stabilizers = GF4Stabilizers::steane_code();
 
         let mut decoder = QuantumErasureDecoder::new(stabilizers, 0.25);
 
@@ -202,20 +195,8 @@ mod test {
     }
 
     #[test]
-    fn shor_code_from_builder() {
-        let stabilizers = GF4Stabilizers::from_sparse_paulis(
-            vec![
-                vec![(Z, 0), (Z, 1)],
-                vec![(Z, 1), (Z, 2)],
-                vec![(Z, 3), (Z, 4)],
-                vec![(Z, 4), (Z, 5)],
-                vec![(Z, 6), (Z, 7)],
-                vec![(Z, 7), (Z, 8)],
-                vec![(X, 0), (X, 1), (X, 2), (X, 3), (X, 4), (X, 5)],
-                vec![(X, 3), (X, 4), (X, 5), (X, 6), (X, 7), (X, 8)],
-            ],
-            9,
-        );
+    fn shor_code() {
+        let stabilizers = GF4Stabilizers::shor_code();
 
         let mut decoder = QuantumErasureDecoder::new(stabilizers, 0.25);
 
@@ -233,15 +214,7 @@ mod test {
 
     #[test]
     fn five_qubits_code() {
-        let stabilizers = GF4Stabilizers::from_dense_paulis(
-            vec![
-                vec![X, Z, Z, X, I],
-                vec![I, X, Z, Z, X],
-                vec![X, I, X, Z, Z],
-                vec![Z, X, I, X, Z],
-            ],
-            5,
-        );
+        let stabilizers = GF4Stabilizers::five_qubit_code();
         let mut decoder = QuantumErasureDecoder::new(stabilizers, 0.25);
         assert_eq!(decoder.decode(&vec![]), ErasureResult::Success);
         for i in 0..5 {
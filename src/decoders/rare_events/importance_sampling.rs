@@ -0,0 +1,192 @@
+use super::RareEventEstimate;
+use crate::decoders::{Decoder, DecodingResult};
+use rand::distributions::Standard;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use std::fmt;
+
+/// Estimates the failure rate of a target decoder from errors sampled with a biased decoder.
+///
+/// The errors are generated by the biased decoder, usually the same decoder with a larger error
+/// probability, and decoded by the target decoder. Each failure is weighted by the likelihood
+/// ratio of its error between the target and the biased decoders.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// # use rand::SeedableRng;
+/// # use rand_chacha::ChaCha8Rng;
+/// let code = ParityCheckMatrix::repetition_code(5);
+/// let target = SyndromeTableDecoder::with_prob(0.001).for_code(code.clone());
+/// let biased = SyndromeTableDecoder::with_prob(0.3).for_code(code);
+///
+/// let mut rng = ChaCha8Rng::seed_from_u64(10);
+/// let estimate = ImportanceSampler::new(&target, &biased)
+///     .unwrap()
+///     .estimate_n_iterations_with_rng(10_000, &mut rng);
+///
+/// // The exact failure rate is about 1e-8.
+/// assert!((estimate.get_failure_rate() - 9.985e-9).abs() < 1e-9);
+/// ```
+pub struct ImportanceSampler<'a, D: Decoder> {
+    target: &'a D,
+    biased: &'a D,
+}
+
+/// The reasons why an importance sampler can't be built from two decoders.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImportanceSamplerError {
+    /// The target and biased decoders don't use the same code.
+    DifferentCodes,
+}
+
+impl fmt::Display for ImportanceSamplerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DifferentCodes => {
+                write!(f, "the target and biased decoders have different codes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportanceSamplerError {}
+
+impl<'a, D: Decoder> ImportanceSampler<'a, D> {
+    /// Creates an importance sampler for the `target` decoder sampling its errors from the
+    /// `biased` decoder.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the decoders don't use the same code. Otherwise, the biased errors
+    /// may not even have the length expected by the target decoder.
+    pub fn new(target: &'a D, biased: &'a D) -> Result<Self, ImportanceSamplerError>
+    where
+        D::Code: PartialEq,
+    {
+        if target.clone().take_code() != biased.clone().take_code() {
+            return Err(ImportanceSamplerError::DifferentCodes);
+        }
+        Ok(Self { target, biased })
+    }
+
+    /// Estimates the failure rate of the target decoder from `n_iterations` biased errors with
+    /// random number generator `rng`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoders don't know the probability of their errors.
+    pub fn estimate_n_iterations_with_rng<R: Rng>(
+        &self,
+        n_iterations: usize,
+        rng: &mut R,
+    ) -> RareEventEstimate {
+        let seeds: Vec<u64> = rng.sample_iter(Standard).take(n_iterations).collect();
+        let weights: Vec<f64> = seeds
+            .par_iter()
+            .map_init(
+                || self.target.clone(),
+                |decoder, seed| {
+                    let mut rng = ChaCha8Rng::seed_from_u64(*seed);
+                    let error = self.biased.get_random_error_with_rng(&mut rng);
+                    if decoder.decode(&error).is_success() {
+                        0.0
+                    } else {
+                        self.get_likelihood_ratio(&error)
+                    }
+                },
+            )
+            .collect();
+        let n_samples = weights.len() as f64;
+        let mean = weights.iter().sum::<f64>() / n_samples;
+        let mean_of_squares = weights.iter().map(|weight| weight * weight).sum::<f64>() / n_samples;
+        let variance = (mean_of_squares - mean * mean).max(0.0);
+        RareEventEstimate::new(mean, (variance / n_samples).sqrt(), weights.len() as u64)
+    }
+
+    /// Estimates the failure rate of the target decoder from `n_iterations` biased errors with
+    /// the thread random number generator.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoders don't know the probability of their errors.
+    pub fn estimate_n_iterations(&self, n_iterations: usize) -> RareEventEstimate {
+        self.estimate_n_iterations_with_rng(n_iterations, &mut thread_rng())
+    }
+
+    fn get_likelihood_ratio(&self, error: &D::Error) -> f64 {
+        let target = self.target.get_error_log_probability(error);
+        let biased = self.biased.get_error_log_probability(error);
+        match (target, biased) {
+            (Some(target), Some(biased)) => (target - biased).exp(),
+            _ => panic!("the decoder doesn't know the probability of its errors"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ErasureDecoder, ParityCheckMatrix};
+
+    #[test]
+    fn estimate_is_unbiased_without_bias() {
+        let code = ParityCheckMatrix::repetition_code(3);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let estimate = ImportanceSampler::new(&decoder, &decoder)
+            .unwrap()
+            .estimate_n_iterations_with_rng(20_000, &mut rng);
+        assert!((estimate.get_failure_rate() - 0.125).abs() < 4.0 * estimate.get_standard_error());
+        assert_eq!(estimate.get_n_samples(), 20_000);
+    }
+
+    #[test]
+    fn erasures_of_a_repetition_code() {
+        let code = ParityCheckMatrix::repetition_code(10);
+        let target = ErasureDecoder::with_prob(0.01).for_code(code.clone());
+        let biased = ErasureDecoder::with_prob(0.8).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let estimate = ImportanceSampler::new(&target, &biased)
+            .unwrap()
+            .estimate_n_iterations_with_rng(5000, &mut rng);
+        let exact = 1e-20;
+        assert!((estimate.get_failure_rate() - exact).abs() < 4.0 * estimate.get_standard_error());
+        assert!(estimate.get_relative_error() < 0.1);
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_threads() {
+        let code = ParityCheckMatrix::hamming_code(3);
+        let target = ErasureDecoder::with_prob(0.05).for_code(code.clone());
+        let biased = ErasureDecoder::with_prob(0.4).for_code(code);
+        let estimates: Vec<RareEventEstimate> = [1, 4]
+            .iter()
+            .map(|n_threads| {
+                let mut rng = ChaCha8Rng::seed_from_u64(3);
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(*n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| {
+                        ImportanceSampler::new(&target, &biased)
+                            .unwrap()
+                            .estimate_n_iterations_with_rng(1000, &mut rng)
+                    })
+            })
+            .collect();
+        assert_eq!(estimates[0], estimates[1]);
+    }
+
+    #[test]
+    fn decoders_must_have_the_same_code() {
+        let target = ErasureDecoder::with_prob(0.01).for_code(ParityCheckMatrix::hamming_code(3));
+        let biased = ErasureDecoder::with_prob(0.3).for_code(ParityCheckMatrix::hamming_code(4));
+        assert_eq!(
+            ImportanceSampler::new(&target, &biased).err(),
+            Some(ImportanceSamplerError::DifferentCodes)
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use rand::{Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// A random number generator replaying a given list of random words.
+//
+// Once the words are exhausted, new words are drawn from an extension generator and appended to
+// the list. Since any random error is a function of the words consumed by its generator, the
+// words are latent variables with independent uniform distributions whatever the decoder.
+pub(super) struct LatentRng {
+    words: Vec<u64>,
+    position: usize,
+    extension: ChaCha8Rng,
+}
+
+impl LatentRng {
+    pub(super) fn from_words_and_seed(words: Vec<u64>, seed: u64) -> Self {
+        Self {
+            words,
+            position: 0,
+            extension: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    // Returns the words consumed so far.
+    pub(super) fn into_used_words(mut self) -> Vec<u64> {
+        self.words.truncate(self.position);
+        self.words
+    }
+}
+
+impl RngCore for LatentRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.position == self.words.len() {
+            self.words.push(self.extension.next_u64());
+        }
+        self.position += 1;
+        self.words[self.position - 1]
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn used_words_are_replayed() {
+        let mut rng = LatentRng::from_words_and_seed(vec![1, 2], 5);
+        let values: Vec<f64> = (0..4).map(|_| rng.gen()).collect();
+        let words = rng.into_used_words();
+        assert_eq!(words.len(), 4);
+        assert_eq!(&words[..2], &[1, 2]);
+
+        let mut replay = LatentRng::from_words_and_seed(words, 9);
+        let replayed: Vec<f64> = (0..4).map(|_| replay.gen()).collect();
+        assert_eq!(values, replayed);
+    }
+}
@@ -0,0 +1,58 @@
+//! Estimators of small failure rates.
+//!
+//! The plain simulators need about `100 / p` iterations to estimate a failure rate `p`. The
+//! estimators of this module reach much smaller failure rates by sampling the rare errors more
+//! often. They need decoders implementing
+//! [`get_error_log_probability`](trait.Decoder.html#method.get_error_log_probability).
+
+mod latent_rng;
+
+mod importance_sampling;
+pub use importance_sampling::{ImportanceSampler, ImportanceSamplerError};
+
+mod subset_simulation;
+pub use subset_simulation::SubsetSimulation;
+
+/// An estimate of a failure rate with its standard error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RareEventEstimate {
+    failure_rate: f64,
+    standard_error: f64,
+    n_samples: u64,
+}
+
+impl RareEventEstimate {
+    fn new(failure_rate: f64, standard_error: f64, n_samples: u64) -> Self {
+        Self {
+            failure_rate,
+            standard_error,
+            n_samples,
+        }
+    }
+
+    /// Returns the estimated failure rate.
+    pub fn get_failure_rate(&self) -> f64 {
+        self.failure_rate
+    }
+
+    /// Returns the standard error of the estimated failure rate.
+    pub fn get_standard_error(&self) -> f64 {
+        self.standard_error
+    }
+
+    /// Returns the standard error relative to the failure rate.
+    ///
+    /// This is infinite if the estimated failure rate is 0.
+    pub fn get_relative_error(&self) -> f64 {
+        if self.failure_rate == 0.0 {
+            f64::INFINITY
+        } else {
+            self.standard_error / self.failure_rate
+        }
+    }
+
+    /// Returns the number of decoded errors.
+    pub fn get_n_samples(&self) -> u64 {
+        self.n_samples
+    }
+}
@@ -0,0 +1,420 @@
+use super::latent_rng::LatentRng;
+use super::RareEventEstimate;
+use crate::decoders::{Decoder, DecodingResult};
+use rand::distributions::Standard;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+const DEFAULT_N_SAMPLES_PER_LEVEL: usize = 1000;
+const DEFAULT_LEVEL_PROB: f64 = 0.1;
+const DEFAULT_MAX_LEVELS: usize = 20;
+const DEFAULT_N_MUTATIONS: f64 = 2.0;
+
+/// Estimates the failure rate of a decoder with subset simulation, a multilevel splitting
+/// method.
+///
+/// The severity of an error is minus the logarithm of its probability. The levels are
+/// increasing severities such that the level probability fraction of the samples of a level are
+/// above the next level. The samples above a level are the seeds of Markov chains generating the
+/// samples of the next level. The failure rate is the sum over the levels of the probability to
+/// reach a level times the fraction of its samples failing below the next level, plus the
+/// probability to reach the last level times the fraction of its failing samples. Thus, the
+/// failures of any severity are counted, even if the failure rate doesn't increase with the
+/// severity.
+///
+/// An error is a function of the random words consumed by `get_random_error_with_rng`. The
+/// Markov chains resample each of these words with a fixed probability and accept the new
+/// error if it is severe enough. Thus, this works with any decoder implementing
+/// [`get_error_log_probability`](trait.Decoder.html#method.get_error_log_probability).
+///
+/// The simulation stops when the level probability fraction of the samples fail or after the
+/// maximal number of levels. The standard error ignores the correlations between the samples of
+/// a Markov chain and between the levels. It is then a lower bound. For errors on a few bits with
+/// small probabilities, the chains rarely move and the actual error can be a few times larger.
+///
+/// The implementation is based on "Estimation of small failure probabilities in high dimensions
+/// by subset simulation, S.-K. Au and J. L. Beck, 2001, Probabilistic Engineering Mechanics".
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// # use rand::SeedableRng;
+/// # use rand_chacha::ChaCha8Rng;
+/// let code = ParityCheckMatrix::repetition_code(6);
+/// let decoder = ErasureDecoder::with_prob(0.05).for_code(code);
+///
+/// let mut rng = ChaCha8Rng::seed_from_u64(10);
+/// let estimate = SubsetSimulation::from(&decoder)
+///     .with_n_samples_per_level(2000)
+///     .estimate_with_rng(&mut rng);
+///
+/// // The exact failure rate is 0.05^6, about 1.6e-8.
+/// let ratio = estimate.get_failure_rate() / 0.05_f64.powi(6);
+/// assert!(ratio > 1.0 / 3.0 && ratio < 3.0);
+/// ```
+pub struct SubsetSimulation<'a, D: Decoder> {
+    decoder: &'a D,
+    n_samples_per_level: usize,
+    level_prob: f64,
+    max_levels: usize,
+    n_mutations: f64,
+}
+
+// A sample with its random words, the severity of its error and its decoding outcome.
+#[derive(Clone)]
+struct Sample {
+    words: Vec<u64>,
+    severity: f64,
+    is_failure: bool,
+}
+
+impl<'a, D: Decoder> SubsetSimulation<'a, D> {
+    // ***** Construction *****
+
+    /// Creates a subset simulation for `decoder` with 1000 samples per level, a level
+    /// probability of 0.1, at most 20 levels and 2 mutations per Markov chain step.
+    pub fn from(decoder: &'a D) -> Self {
+        Self {
+            decoder,
+            n_samples_per_level: DEFAULT_N_SAMPLES_PER_LEVEL,
+            level_prob: DEFAULT_LEVEL_PROB,
+            max_levels: DEFAULT_MAX_LEVELS,
+            n_mutations: DEFAULT_N_MUTATIONS,
+        }
+    }
+
+    /// Sets the number of decoded samples at each level.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_samples` is 0.
+    pub fn with_n_samples_per_level(mut self, n_samples: usize) -> Self {
+        if n_samples == 0 {
+            panic!("no sample");
+        }
+        self.n_samples_per_level = n_samples;
+        self
+    }
+
+    /// Sets the fraction of samples kept as seeds for the next level.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `prob` is not strictly between 0.0 and 1.0.
+    pub fn with_level_prob(mut self, prob: f64) -> Self {
+        if prob <= 0.0 || prob >= 1.0 {
+            panic!("invalid probability");
+        }
+        self.level_prob = prob;
+        self
+    }
+
+    /// Sets the maximal number of levels.
+    pub fn with_max_levels(mut self, max_levels: usize) -> Self {
+        self.max_levels = max_levels;
+        self
+    }
+
+    /// Sets the average number of random words resampled at each step of the Markov chains.
+    ///
+    /// Each word is resampled with the same probability, the number of mutations over the
+    /// average number of words used by the errors of the first level.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_mutations` is not positive.
+    pub fn with_n_mutations(mut self, n_mutations: f64) -> Self {
+        if n_mutations <= 0.0 {
+            panic!("non positive number of mutations");
+        }
+        self.n_mutations = n_mutations;
+        self
+    }
+
+    // ***** Estimation *****
+
+    /// Estimates the failure rate of the decoder with random number generator `rng`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoder doesn't know the probability of its errors.
+    pub fn estimate_with_rng<R: Rng>(&self, rng: &mut R) -> RareEventEstimate {
+        let mut samples = self.get_initial_samples_with_rng(rng);
+        let mutation_prob = self.get_mutation_prob(&samples);
+        let mut n_samples = samples.len() as u64;
+        let mut prob = 1.0;
+        let mut squared_relative_error = 0.0;
+        let mut failure_rate = 0.0;
+        let mut squared_error = 0.0;
+        for _ in 0..self.max_levels {
+            if self.get_failure_fraction(&samples, f64::INFINITY) >= self.level_prob {
+                break;
+            }
+            let level = self.get_next_level(&samples);
+            let (rate, error) = self.get_failure_rate_and_squared_error(
+                prob,
+                squared_relative_error,
+                self.get_failure_fraction(&samples, level),
+            );
+            failure_rate += rate;
+            squared_error += error;
+            let seeds: Vec<Sample> = samples
+                .into_iter()
+                .filter(|sample| sample.severity >= level)
+                .collect();
+            let level_prob = seeds.len() as f64 / self.n_samples_per_level as f64;
+            if level_prob == 1.0 {
+                samples = seeds;
+                break;
+            }
+            prob *= level_prob;
+            squared_relative_error += self.get_squared_relative_error(level_prob);
+            samples = self.get_samples_above_level_with_rng(seeds, level, mutation_prob, rng);
+            n_samples += samples.len() as u64;
+        }
+        let (rate, error) = self.get_failure_rate_and_squared_error(
+            prob,
+            squared_relative_error,
+            self.get_failure_fraction(&samples, f64::INFINITY),
+        );
+        failure_rate += rate;
+        squared_error += error;
+        RareEventEstimate::new(failure_rate, squared_error.sqrt(), n_samples)
+    }
+
+    /// Estimates the failure rate of the decoder with the thread random number generator.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the decoder doesn't know the probability of its errors.
+    pub fn estimate(&self) -> RareEventEstimate {
+        self.estimate_with_rng(&mut thread_rng())
+    }
+
+    fn get_initial_samples_with_rng<R: Rng>(&self, rng: &mut R) -> Vec<Sample> {
+        let seeds: Vec<u64> = rng
+            .sample_iter(Standard)
+            .take(self.n_samples_per_level)
+            .collect();
+        seeds
+            .par_iter()
+            .map_init(
+                || self.decoder.clone(),
+                |decoder, seed| get_sample(decoder, Vec::new(), *seed),
+            )
+            .collect()
+    }
+
+    // The same probability is used for every word such that the mutations are symmetric.
+    fn get_mutation_prob(&self, samples: &[Sample]) -> f64 {
+        let n_words: usize = samples.iter().map(|sample| sample.words.len()).sum();
+        let average_n_words = n_words as f64 / samples.len() as f64;
+        (self.n_mutations / average_n_words.max(1.0)).min(1.0)
+    }
+
+    // The severity of the level probability quantile of the samples. If too many samples share
+    // the smallest severity, the level is the next severity to ensure progress.
+    fn get_next_level(&self, samples: &[Sample]) -> f64 {
+        let mut severities: Vec<f64> = samples.iter().map(|sample| sample.severity).collect();
+        severities.sort_by(|first, second| second.partial_cmp(first).unwrap());
+        let n_seeds = (self.level_prob * samples.len() as f64).ceil() as usize;
+        let level = severities[n_seeds.max(1) - 1];
+        let smallest = severities[severities.len() - 1];
+        if level > smallest {
+            level
+        } else {
+            severities
+                .iter()
+                .rev()
+                .find(|severity| **severity > smallest)
+                .cloned()
+                .unwrap_or(level)
+        }
+    }
+
+    // Runs a Markov chain from each seed such that the chains have the requested number of
+    // samples in total. The seeds are the first samples of their chain.
+    fn get_samples_above_level_with_rng<R: Rng>(
+        &self,
+        seeds: Vec<Sample>,
+        level: f64,
+        mutation_prob: f64,
+        rng: &mut R,
+    ) -> Vec<Sample> {
+        let n_seeds = seeds.len();
+        let chains: Vec<(Sample, usize, u64)> = seeds
+            .into_iter()
+            .enumerate()
+            .map(|(index, seed)| {
+                let length = self.n_samples_per_level / n_seeds
+                    + (index < self.n_samples_per_level % n_seeds) as usize;
+                (seed, length, rng.gen())
+            })
+            .collect();
+        chains
+            .into_par_iter()
+            .map_init(
+                || self.decoder.clone(),
+                |decoder, (seed, length, chain_seed)| {
+                    self.run_chain(decoder, seed, length, level, mutation_prob, chain_seed)
+                },
+            )
+            .flatten()
+            .collect()
+    }
+
+    fn run_chain(
+        &self,
+        decoder: &mut D,
+        seed: Sample,
+        length: usize,
+        level: f64,
+        mutation_prob: f64,
+        chain_seed: u64,
+    ) -> Vec<Sample> {
+        let mut rng = ChaCha8Rng::seed_from_u64(chain_seed);
+        let mut chain = Vec::with_capacity(length);
+        let mut current = seed;
+        for step in 0..length {
+            if step > 0 {
+                let words = current
+                    .words
+                    .iter()
+                    .map(|word| {
+                        if rng.gen::<f64>() < mutation_prob {
+                            rng.gen()
+                        } else {
+                            *word
+                        }
+                    })
+                    .collect();
+                let candidate = get_sample(decoder, words, rng.gen());
+                if candidate.severity >= level {
+                    current = candidate;
+                }
+            }
+            chain.push(current.clone());
+        }
+        chain
+    }
+
+    // The fraction of the samples failing with a severity smaller than `level`.
+    fn get_failure_fraction(&self, samples: &[Sample], level: f64) -> f64 {
+        let n_failures = samples
+            .iter()
+            .filter(|sample| sample.is_failure && sample.severity < level)
+            .count();
+        n_failures as f64 / samples.len() as f64
+    }
+
+    // The contribution of a fraction of the samples of a level reached with probability `prob`
+    // to the failure rate and to its squared standard error.
+    fn get_failure_rate_and_squared_error(
+        &self,
+        prob: f64,
+        squared_relative_error: f64,
+        fraction: f64,
+    ) -> (f64, f64) {
+        if fraction == 0.0 {
+            return (0.0, 0.0);
+        }
+        let rate = prob * fraction;
+        let squared_relative_error =
+            squared_relative_error + self.get_squared_relative_error(fraction);
+        (rate, rate * rate * squared_relative_error)
+    }
+
+    fn get_squared_relative_error(&self, prob: f64) -> f64 {
+        (1.0 - prob) / (prob * self.n_samples_per_level as f64)
+    }
+}
+
+fn get_sample<D: Decoder>(decoder: &mut D, words: Vec<u64>, extension_seed: u64) -> Sample {
+    let mut rng = LatentRng::from_words_and_seed(words, extension_seed);
+    let error = decoder.get_random_error_with_rng(&mut rng);
+    let severity = match decoder.get_error_log_probability(&error) {
+        Some(log_prob) => -log_prob,
+        None => panic!("the decoder doesn't know the probability of its errors"),
+    };
+    let is_failure = decoder.decode(&error).is_failure();
+    Sample {
+        words: rng.into_used_words(),
+        severity,
+        is_failure,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ErasureDecoder, ParityCheckMatrix, SyndromeTableDecoder};
+
+    #[test]
+    fn estimate_without_rare_events_is_plain_monte_carlo() {
+        let code = ParityCheckMatrix::repetition_code(2);
+        let decoder = ErasureDecoder::with_prob(0.5).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let estimate = SubsetSimulation::from(&decoder).estimate_with_rng(&mut rng);
+        assert_eq!(estimate.get_n_samples(), 1000);
+        assert!((estimate.get_failure_rate() - 0.25).abs() < 4.0 * estimate.get_standard_error());
+    }
+
+    #[test]
+    fn bit_flips_on_a_repetition_code() {
+        // The decoding fails if at least 4 bits are flipped.
+        let code = ParityCheckMatrix::repetition_code(7);
+        let decoder = SyndromeTableDecoder::with_prob(0.01).for_code(code);
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        let estimate = SubsetSimulation::from(&decoder)
+            .with_n_samples_per_level(2000)
+            .estimate_with_rng(&mut rng);
+        let exact = 35.0 * 0.01_f64.powi(4) * 0.99_f64.powi(3);
+        let ratio = estimate.get_failure_rate() / exact;
+        assert!(ratio > 1.0 / 3.0 && ratio < 3.0);
+        assert!(estimate.get_n_samples() > 2000);
+    }
+
+    #[test]
+    fn failures_below_the_last_level_are_counted() {
+        // Some erasures of 3 bits are uncorrectable while the rarest errors reach more bits.
+        let code = ParityCheckMatrix::hamming_code(4);
+        let mut decoder = ErasureDecoder::with_prob(0.01).for_code(code);
+        let exact: f64 = (0..1_usize << 15)
+            .map(|pattern| {
+                let erasure: Vec<usize> = (0..15).filter(|bit| pattern >> bit & 1 == 1).collect();
+                let weight = erasure.len() as i32;
+                if decoder.decode(&erasure).is_success() {
+                    0.0
+                } else {
+                    0.01_f64.powi(weight) * 0.99_f64.powi(15 - weight)
+                }
+            })
+            .sum();
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let estimate = SubsetSimulation::from(&decoder)
+            .with_n_samples_per_level(4000)
+            .estimate_with_rng(&mut rng);
+        let ratio = estimate.get_failure_rate() / exact;
+        assert!(ratio > 1.0 / 3.0 && ratio < 3.0);
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_threads() {
+        let code = ParityCheckMatrix::repetition_code(5);
+        let decoder = ErasureDecoder::with_prob(0.1).for_code(code);
+        let estimates: Vec<RareEventEstimate> = [1, 4]
+            .iter()
+            .map(|n_threads| {
+                let mut rng = ChaCha8Rng::seed_from_u64(3);
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(*n_threads)
+                    .build()
+                    .unwrap()
+                    .install(|| SubsetSimulation::from(&decoder).estimate_with_rng(&mut rng))
+            })
+            .collect();
+        assert_eq!(estimates[0], estimates[1]);
+    }
+}
@@ -1,7 +1,26 @@
+use super::statistics::{inverse_regularized_incomplete_beta, normal_quantile};
 use super::DecodingResult;
+use serde::{Deserialize, Serialize};
 
 /// An interface for simulation result. 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+///
+/// A result is serialized as its number of successes and failures.
+///
+/// # Example
+///
+/// ```
+/// use believer::SimulationResult;
+/// let result = SimulationResult::with_n_successes_and_failures(9, 16);
+///
+/// let json = serde_json::to_string(&result).unwrap();
+/// assert_eq!(json, r#"{"n_successes":9,"n_failures":16}"#);
+///
+/// let mut writer = csv::Writer::from_writer(Vec::new());
+/// writer.serialize(result).unwrap();
+/// let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(csv, "n_successes,n_failures\n9,16\n");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct SimulationResult {
     n_successes: u64,
     n_failures: u64,
@@ -86,7 +105,24 @@ impl SimulationResult {
     /// assert_eq!(result.get_effective_success_rate_for_code_dimension(2), 0.6);
     /// ```
     pub fn get_effective_success_rate_for_code_dimension(&self, dimension: u32) -> f64 {
-        (self.get_success_rate() as f64).powf(1.0 / dimension as f64)
+        self.get_success_rate().powf(1.0 / dimension as f64)
+    }
+
+    /// Get the failure rate per round of `self` for a simulation of `n_rounds` rounds of
+    /// syndrome measurements.
+    ///
+    /// This is the failure rate of a single round such that `n_rounds` independent rounds have
+    /// the failure rate of `self`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::SimulationResult;
+    /// let result = SimulationResult::with_n_successes_and_failures(36, 64);
+    /// assert!((result.get_failure_rate_per_round(2) - 0.4).abs() < 1e-12);
+    /// ```
+    pub fn get_failure_rate_per_round(&self, n_rounds: u32) -> f64 {
+        self.get_effective_failure_rate_for_code_dimension(n_rounds)
     }
 
     /// Get the failure rate of `self`.
@@ -115,6 +151,97 @@ impl SimulationResult {
         self.n_successes as f64 / self.get_n_iterations() as f64
     }
 
+    /// Get the standard error of the failure rate of `self`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::SimulationResult;
+    /// let result = SimulationResult::with_n_successes_and_failures(84, 16);
+    /// assert!((result.get_standard_error() - 0.0366606).abs() < 1e-6);
+    /// ```
+    pub fn get_standard_error(&self) -> f64 {
+        let failure_rate = self.get_failure_rate();
+        (failure_rate * (1.0 - failure_rate) / self.get_n_iterations() as f64).sqrt()
+    }
+
+    /// Get the standard error of `self` relative to its failure rate.
+    ///
+    /// This is infinite if there is no failure.
+    pub fn get_relative_precision(&self) -> f64 {
+        if self.n_failures == 0 {
+            f64::INFINITY
+        } else {
+            self.get_standard_error() / self.get_failure_rate()
+        }
+    }
+
+    /// Get the Wilson score interval of the failure rate of `self` with the given `confidence`
+    /// level.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `confidence` is not strictly between 0.0 and 1.0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::SimulationResult;
+    /// let result = SimulationResult::with_n_successes_and_failures(90, 10);
+    /// let (low, high) = result.get_wilson_interval(0.95);
+    /// assert!((low - 0.0552).abs() < 1e-4);
+    /// assert!((high - 0.1744).abs() < 1e-4);
+    /// ```
+    pub fn get_wilson_interval(&self, confidence: f64) -> (f64, f64) {
+        let z = get_normal_quantile_for(confidence);
+        let n = self.get_n_iterations() as f64;
+        if n == 0.0 {
+            return (0.0, 1.0);
+        }
+        let failure_rate = self.get_failure_rate();
+        let denominator = 1.0 + z * z / n;
+        let center = (failure_rate + z * z / (2.0 * n)) / denominator;
+        let half_width = z / denominator
+            * (failure_rate * (1.0 - failure_rate) / n + z * z / (4.0 * n * n)).sqrt();
+        ((center - half_width).max(0.0), (center + half_width).min(1.0))
+    }
+
+    /// Get the exact Clopper-Pearson interval of the failure rate of `self` with the given
+    /// `confidence` level.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `confidence` is not strictly between 0.0 and 1.0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::SimulationResult;
+    /// let result = SimulationResult::with_n_successes_and_failures(90, 10);
+    /// let (low, high) = result.get_clopper_pearson_interval(0.95);
+    /// assert!((low - 0.0490).abs() < 1e-4);
+    /// assert!((high - 0.1762).abs() < 1e-4);
+    /// ```
+    pub fn get_clopper_pearson_interval(&self, confidence: f64) -> (f64, f64) {
+        if confidence <= 0.0 || confidence >= 1.0 {
+            panic!("invalid confidence level");
+        }
+        let alpha = 1.0 - confidence;
+        let failures = self.n_failures as f64;
+        let successes = self.n_successes as f64;
+        let low = if self.n_failures == 0 {
+            0.0
+        } else {
+            inverse_regularized_incomplete_beta(alpha / 2.0, failures, successes + 1.0)
+        };
+        let high = if self.n_successes == 0 {
+            1.0
+        } else {
+            inverse_regularized_incomplete_beta(1.0 - alpha / 2.0, failures + 1.0, successes)
+        };
+        (low, high)
+    }
+
     pub fn get_n_iterations(&self) -> u64 {
         self.n_failures + self.n_successes
     }
@@ -126,4 +253,17 @@ impl SimulationResult {
     pub fn get_n_successes(&self) -> u64 {
         self.n_successes
     }
-}
\ No newline at end of file
+}
+
+impl Default for SimulationResult {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn get_normal_quantile_for(confidence: f64) -> f64 {
+    if confidence <= 0.0 || confidence >= 1.0 {
+        panic!("invalid confidence level");
+    }
+    normal_quantile(0.5 + confidence / 2.0)
+}
@@ -0,0 +1,169 @@
+//! An erasure decoder for repeated rounds of noisy syndrome measurements.
+
+use super::statistics::bernoulli_log_probability;
+use super::Decoder;
+use crate::ErasureResult;
+use crate::ParityCheckMatrix;
+use crate::SpacetimeCode;
+use rand::Rng;
+
+/// Decoder for erasures of data bits and measurements during many rounds.
+///
+/// Each data bit is erased with probability `erasure_prob` during each round and each
+/// measurement outcome is erased with probability `measurement_erasure_prob`. The decoding fails
+/// if the erased spacetime bits support an undetectable error that is a logical failure.
+///
+/// The simulation results are per shot. Use
+/// [`get_failure_rate_per_round`](struct.SimulationResult.html#method.get_failure_rate_per_round)
+/// to get the failure rate per round.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 3);
+/// let decoder = SpacetimeErasureDecoder::with_probs(0.05, 0.05).for_code(code);
+///
+/// let result = decoder.simulate_n_iterations(100);
+/// let per_round = result.get_failure_rate_per_round(3);
+/// assert!(per_round <= result.get_failure_rate());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpacetimeErasureDecoder {
+    code: SpacetimeCode,
+    checks_and_logicals: ParityCheckMatrix,
+    erasure_prob: f64,
+    measurement_erasure_prob: f64,
+}
+
+impl SpacetimeErasureDecoder {
+    /// Creates a spacetime erasure decoder for an empty code.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some probabilities are not between 0.0 and 1.0.
+    pub fn with_probs(erasure_prob: f64, measurement_erasure_prob: f64) -> Self {
+        if !(0.0..=1.0).contains(&erasure_prob) || !(0.0..=1.0).contains(&measurement_erasure_prob)
+        {
+            panic!("invalid probability");
+        }
+        let code = empty_code();
+        let checks_and_logicals = get_checks_and_logicals(&code);
+        Self {
+            code,
+            checks_and_logicals,
+            erasure_prob,
+            measurement_erasure_prob,
+        }
+    }
+}
+
+impl Decoder for SpacetimeErasureDecoder {
+    type Error = Vec<usize>; // Positions of erased spacetime bits.
+    type Result = ErasureResult;
+    type Code = SpacetimeCode;
+
+    fn for_code(mut self, code: Self::Code) -> Self {
+        self.checks_and_logicals = get_checks_and_logicals(&code);
+        self.code = code;
+        self
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        std::mem::replace(&mut self.code, empty_code())
+    }
+
+    // The erased bits support an undetectable logical error if and only if adding the logicals
+    // to the erased checks increases the rank.
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        let checks_rank = self.code.get_checks().keep(error).get_rank();
+        let checks_and_logicals_rank = self.checks_and_logicals.keep(error).get_rank();
+        if checks_rank == checks_and_logicals_rank {
+            ErasureResult::Success
+        } else {
+            ErasureResult::Failure
+        }
+    }
+
+    fn get_error_log_probability(&self, error: &Self::Error) -> Option<f64> {
+        let n_data_bits = self.code.get_n_data_bits();
+        let n_erased_data_bits = error.iter().filter(|bit| **bit < n_data_bits).count();
+        Some(
+            bernoulli_log_probability(n_erased_data_bits, n_data_bits, self.erasure_prob)
+                + bernoulli_log_probability(
+                    error.len() - n_erased_data_bits,
+                    self.code.get_n_bits() - n_data_bits,
+                    self.measurement_erasure_prob,
+                ),
+        )
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        let n_data_bits = self.code.get_n_data_bits();
+        (0..self.code.get_n_bits())
+            .filter(|bit| {
+                let prob = if *bit < n_data_bits {
+                    self.erasure_prob
+                } else {
+                    self.measurement_erasure_prob
+                };
+                rng.gen::<f64>() < prob
+            })
+            .collect()
+    }
+}
+
+fn empty_code() -> SpacetimeCode {
+    SpacetimeCode::from_checks_and_logicals(ParityCheckMatrix::new(), ParityCheckMatrix::new(), 1)
+}
+
+fn get_checks_and_logicals(code: &SpacetimeCode) -> ParityCheckMatrix {
+    ParityCheckMatrix::with_n_bits(code.get_n_bits()).with_checks(
+        code.get_checks()
+            .checks_iter()
+            .chain(code.get_logicals().checks_iter())
+            .map(|check| check.to_vec())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GF4Stabilizers;
+
+    #[test]
+    fn single_round_matches_code_capacity() {
+        let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 1);
+        let mut decoder = SpacetimeErasureDecoder::with_probs(0.1, 0.1).for_code(code);
+        assert_eq!(decoder.decode(&vec![0, 1]), ErasureResult::Success);
+        assert_eq!(decoder.decode(&vec![0, 1, 2]), ErasureResult::Success);
+        assert_eq!(decoder.decode(&vec![2, 4, 6]), ErasureResult::Failure);
+    }
+
+    #[test]
+    fn measurement_erasures_are_harmless_alone() {
+        let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 3);
+        let measurements: Vec<usize> = (code.get_n_data_bits()..code.get_n_bits()).collect();
+        let mut decoder = SpacetimeErasureDecoder::with_probs(0.1, 0.1).for_code(code);
+        assert_eq!(decoder.decode(&measurements), ErasureResult::Success);
+    }
+
+    #[test]
+    fn errors_of_each_round_are_detected() {
+        let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 3);
+        let erasure = vec![
+            code.get_data_bit(2, 0),
+            code.get_data_bit(4, 1),
+            code.get_data_bit(6, 2),
+        ];
+        let logical = vec![
+            code.get_data_bit(2, 1),
+            code.get_data_bit(4, 1),
+            code.get_data_bit(6, 1),
+        ];
+        let mut decoder = SpacetimeErasureDecoder::with_probs(0.1, 0.1).for_code(code);
+        assert_eq!(decoder.decode(&erasure), ErasureResult::Success);
+        assert_eq!(decoder.decode(&logical), ErasureResult::Failure);
+    }
+}
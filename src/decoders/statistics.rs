@@ -0,0 +1,184 @@
+//! Special functions used to compute confidence intervals.
+//!
+//! The incomplete beta function follows "Numerical Recipes: The Art of Scientific Computing
+//! (Chapter 6), W. H. Press, S. A. Teukolsky, W. T. Vetterling and B. P. Flannery, 2007,
+//! Cambridge University Press" and the normal quantile follows the rational approximation of
+//! Peter J. Acklam.
+
+const MAX_CONTINUED_FRACTION_STEPS: usize = 10_000;
+const EPSILON: f64 = 1e-15;
+const TINY: f64 = 1e-300;
+
+/// Returns the quantile of the standard normal distribution at `prob`.
+pub(crate) fn normal_quantile(prob: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        let q = (-2.0 * q.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if prob < LOW {
+        tail(prob)
+    } else if prob > 1.0 - LOW {
+        -tail(1.0 - prob)
+    } else {
+        let q = prob - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/// Returns the regularized incomplete beta function `I_x(a, b)`.
+pub(crate) fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - ln_front.exp() * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Returns the `x` such that `I_x(a, b) = prob` found by bisection.
+pub(crate) fn inverse_regularized_incomplete_beta(prob: f64, a: f64, b: f64) -> f64 {
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..200 {
+        let middle = 0.5 * (low + high);
+        if regularized_incomplete_beta(middle, a, b) < prob {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Returns the logarithm of the probability that exactly `n_events` given trials among
+/// `n_trials` independent trials of probability `prob` happen.
+pub(crate) fn bernoulli_log_probability(n_events: usize, n_trials: usize, prob: f64) -> f64 {
+    let term = |count: usize, prob: f64| {
+        if count == 0 {
+            0.0
+        } else {
+            count as f64 * prob.ln()
+        }
+    };
+    term(n_events, prob) + term(n_trials - n_events, 1.0 - prob)
+}
+
+// Lanczos approximation of the logarithm of the gamma function for positive arguments.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |sum, (index, coefficient)| {
+            sum + coefficient / (x + 1.0 + index as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+// Evaluates the continued fraction of the incomplete beta function with the modified Lentz's
+// method.
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    let clamp = |value: f64| {
+        if value.abs() < TINY {
+            TINY
+        } else {
+            value
+        }
+    };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+    for step in 1..=MAX_CONTINUED_FRACTION_STEPS {
+        let m = step as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        result *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        result *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normal_quantiles() {
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.975) - 1.959_963_985).abs() < 1e-6);
+        assert!((normal_quantile(0.001) + 3.090_232_306).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bernoulli_log_probabilities() {
+        let expected = 0.1_f64.powi(2) * 0.9_f64.powi(3);
+        assert!((bernoulli_log_probability(2, 5, 0.1).exp() - expected).abs() < 1e-15);
+        assert_eq!(bernoulli_log_probability(0, 5, 0.0), 0.0);
+        assert_eq!(bernoulli_log_probability(1, 5, 0.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn incomplete_beta_of_binomial_tails() {
+        // P(X >= 2) for X ~ Binomial(5, 0.3) is I_0.3(2, 4).
+        let expected = 1.0 - 0.7_f64.powi(5) - 5.0 * 0.3 * 0.7_f64.powi(4);
+        assert!((regularized_incomplete_beta(0.3, 2.0, 4.0) - expected).abs() < 1e-12);
+        let x = inverse_regularized_incomplete_beta(expected, 2.0, 4.0);
+        assert!((x - 0.3).abs() < 1e-12);
+    }
+}
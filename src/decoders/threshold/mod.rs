@@ -0,0 +1,387 @@
+//! Sweeps of the failure rate over code sizes and error probabilities to estimate thresholds.
+
+use super::{Decoder, SimulationResult, StoppingCriteria};
+use rand::{thread_rng, Rng};
+use serde::Serialize;
+use std::io;
+
+mod scaling_fit;
+pub use scaling_fit::FiniteSizeScalingFit;
+
+// The default number of iterations simulated for each code and probability.
+const DEFAULT_N_ITERATIONS: u64 = 10_000;
+
+/// Simulates a family of codes over a range of error probabilities.
+///
+/// A decoder is built from each probability with the decoder builder and then used with each
+/// code. Each point of the grid is simulated until the stopping criteria are met. The default is
+/// to simulate 10 000 iterations.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// # use rand::SeedableRng;
+/// # use rand_chacha::ChaCha8Rng;
+/// let mut rng = ChaCha8Rng::seed_from_u64(10);
+/// let sweep = ThresholdSweep::from_decoder_builder(|prob| ErasureDecoder::with_prob(prob))
+///     .with_code(3, ParityCheckMatrix::repetition_code(3))
+///     .with_code(5, ParityCheckMatrix::repetition_code(5))
+///     .with_probabilities(vec![0.2, 0.4, 0.6])
+///     .with_stopping_criteria(StoppingCriteria::new().with_max_iterations(1000));
+///
+/// let result = sweep.run_with_rng(&mut rng);
+/// assert_eq!(result.get_failure_rates(0).len(), 3);
+/// assert!(result.get_result(1, 2).get_failure_rate() < result.get_result(0, 2).get_failure_rate());
+/// ```
+pub struct ThresholdSweep<D: Decoder, F: Fn(f64) -> D> {
+    decoder_builder: F,
+    codes: Vec<(usize, D::Code)>,
+    probabilities: Vec<f64>,
+    criteria: StoppingCriteria,
+}
+
+impl<D, F> ThresholdSweep<D, F>
+where
+    D: Decoder,
+    D::Code: Clone,
+    F: Fn(f64) -> D,
+{
+    // ***** Construction *****
+
+    /// Creates a sweep without any code or probability building the decoders with
+    /// `decoder_builder`.
+    pub fn from_decoder_builder(decoder_builder: F) -> Self {
+        Self {
+            decoder_builder,
+            codes: Vec::new(),
+            probabilities: Vec::new(),
+            criteria: StoppingCriteria::new().with_max_iterations(DEFAULT_N_ITERATIONS),
+        }
+    }
+
+    /// Adds a `code` of the given `size` to the family. The size is the `n` of the finite-size
+    /// scaling, usually the number of bits or the distance.
+    pub fn with_code(mut self, size: usize, code: D::Code) -> Self {
+        self.codes.push((size, code));
+        self
+    }
+
+    /// Sets the error probabilities. They are sorted in increasing order.
+    ///
+    /// # Panic
+    ///
+    /// Panics if a probability is not a number.
+    pub fn with_probabilities(mut self, mut probabilities: Vec<f64>) -> Self {
+        if probabilities.iter().any(|prob| prob.is_nan()) {
+            panic!("invalid probability");
+        }
+        probabilities.sort_by(|first, second| first.partial_cmp(second).unwrap());
+        self.probabilities = probabilities;
+        self
+    }
+
+    /// Sets the criteria used to stop the simulation of each point of the grid.
+    pub fn with_stopping_criteria(mut self, criteria: StoppingCriteria) -> Self {
+        self.criteria = criteria;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the sizes of the codes.
+    pub fn get_sizes(&self) -> Vec<usize> {
+        self.codes.iter().map(|(size, _)| *size).collect()
+    }
+
+    /// Returns the error probabilities.
+    pub fn get_probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+
+    /// Returns the criteria used to stop the simulation of each point of the grid.
+    pub fn get_stopping_criteria(&self) -> &StoppingCriteria {
+        &self.criteria
+    }
+
+    // ***** Simulation *****
+
+    /// Simulates each code at each probability with random number generator `rng`.
+    pub fn run_with_rng<R: Rng>(&self, rng: &mut R) -> SweepResult {
+        let results = self
+            .codes
+            .iter()
+            .map(|(_, code)| {
+                self.probabilities
+                    .iter()
+                    .map(|prob| {
+                        (self.decoder_builder)(*prob)
+                            .for_code(code.clone())
+                            .simulate_until_with_rng(&self.criteria, rng)
+                    })
+                    .collect()
+            })
+            .collect();
+        SweepResult::new(self.get_sizes(), self.probabilities.clone(), results)
+    }
+
+    /// Simulates each code at each probability with the thread random number generator.
+    pub fn run(&self) -> SweepResult {
+        self.run_with_rng(&mut thread_rng())
+    }
+}
+
+/// The failure-rate grid of a `ThresholdSweep`.
+///
+/// The rows are the codes and the columns are the error probabilities.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepResult {
+    sizes: Vec<usize>,
+    probabilities: Vec<f64>,
+    results: Vec<Vec<SimulationResult>>,
+}
+
+impl SweepResult {
+    // ***** Construction *****
+
+    /// Creates a sweep result from the `results` of each code size and each probability.
+    ///
+    /// # Panic
+    ///
+    /// Panics if there is not a result for each code and each probability.
+    pub fn new(
+        sizes: Vec<usize>,
+        probabilities: Vec<f64>,
+        results: Vec<Vec<SimulationResult>>,
+    ) -> Self {
+        if results.len() != sizes.len()
+            || results.iter().any(|row| row.len() != probabilities.len())
+        {
+            panic!("results don't match the grid");
+        }
+        Self {
+            sizes,
+            probabilities,
+            results,
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the sizes of the codes.
+    pub fn get_sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// Returns the error probabilities.
+    pub fn get_probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+
+    /// Returns the result of the `code`-th code at the `prob`-th probability.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `code` or `prob` is out of bound.
+    pub fn get_result(&self, code: usize, prob: usize) -> SimulationResult {
+        self.results[code][prob]
+    }
+
+    /// Returns the failure rates of the `code`-th code for each probability.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `code` is out of bound.
+    pub fn get_failure_rates(&self, code: usize) -> Vec<f64> {
+        self.results[code]
+            .iter()
+            .map(|result| result.get_failure_rate())
+            .collect()
+    }
+
+    /// Returns the first probability where the failure-rate curves of the `first` and `second`
+    /// codes cross, linearly interpolated between the probabilities of the grid.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `first` or `second` is out of bound.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use believer::*;
+    /// let result = SweepResult::new(
+    ///     vec![10, 20],
+    ///     vec![0.1, 0.2, 0.3],
+    ///     vec![
+    ///         vec![
+    ///             SimulationResult::with_n_successes_and_failures(90, 10),
+    ///             SimulationResult::with_n_successes_and_failures(70, 30),
+    ///             SimulationResult::with_n_successes_and_failures(50, 50),
+    ///         ],
+    ///         vec![
+    ///             SimulationResult::with_n_successes_and_failures(95, 5),
+    ///             SimulationResult::with_n_successes_and_failures(75, 25),
+    ///             SimulationResult::with_n_successes_and_failures(35, 65),
+    ///         ],
+    ///     ],
+    /// );
+    /// assert!((result.get_crossing_point(0, 1).unwrap() - 0.225).abs() < 1e-12);
+    /// ```
+    pub fn get_crossing_point(&self, first: usize, second: usize) -> Option<f64> {
+        let differences: Vec<f64> = self
+            .get_failure_rates(first)
+            .iter()
+            .zip(self.get_failure_rates(second).iter())
+            .map(|(first, second)| first - second)
+            .collect();
+        (1..differences.len())
+            .find(|index| differences[index - 1] * differences[*index] <= 0.0)
+            .map(|index| {
+                let (low, high) = (differences[index - 1], differences[index]);
+                let (low_prob, high_prob) =
+                    (self.probabilities[index - 1], self.probabilities[index]);
+                if low == high {
+                    low_prob
+                } else {
+                    low_prob + (high_prob - low_prob) * low / (low - high)
+                }
+            })
+    }
+
+    /// Fits the failure rates to the finite-size scaling ansatz
+    /// `P_L = A + B x + C x^2` with `x = (p - p_th) n^(1 / nu)`.
+    ///
+    /// Returns `None` if there are less than 2 codes or less than 6 simulated points, or if the
+    /// fit is degenerate. The points should be close enough to the threshold for the quadratic
+    /// ansatz to hold.
+    pub fn fit_finite_size_scaling(&self) -> Option<FiniteSizeScalingFit> {
+        scaling_fit::fit(self)
+    }
+
+    // ***** Output *****
+
+    /// Writes the results as CSV to `writer` with one row per code size and probability.
+    ///
+    /// The columns are `size`, `probability`, `n_successes`, `n_failures` and `failure_rate`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use believer::*;
+    /// let result = SweepResult::new(
+    ///     vec![10],
+    ///     vec![0.1, 0.2],
+    ///     vec![vec![
+    ///         SimulationResult::with_n_successes_and_failures(90, 10),
+    ///         SimulationResult::with_n_successes_and_failures(75, 25),
+    ///     ]],
+    /// );
+    /// let mut csv = Vec::new();
+    /// result.write_csv(&mut csv).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(csv).unwrap(),
+    ///     "size,probability,n_successes,n_failures,failure_rate\n\
+    ///      10,0.1,90,10,0.1\n\
+    ///      10,0.2,75,25,0.25\n"
+    /// );
+    /// ```
+    pub fn write_csv<W: io::Write>(&self, writer: W) -> csv::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record([
+            "size",
+            "probability",
+            "n_successes",
+            "n_failures",
+            "failure_rate",
+        ])?;
+        for (size, results) in self.sizes.iter().zip(self.results.iter()) {
+            for (prob, result) in self.probabilities.iter().zip(results.iter()) {
+                writer.write_record([
+                    size.to_string(),
+                    prob.to_string(),
+                    result.get_n_successes().to_string(),
+                    result.get_n_failures().to_string(),
+                    result.get_failure_rate().to_string(),
+                ])?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ErasureDecoder, ParityCheckMatrix};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // Exact failure rates following the scaling ansatz with many iterations.
+    fn get_synthetic_result(threshold: f64, critical_exponent: f64) -> SweepResult {
+        let sizes = vec![16, 32, 64, 128];
+        let probabilities: Vec<f64> = (0..11).map(|index| 0.2 + 0.02 * index as f64).collect();
+        let n_iterations = 1_000_000;
+        let results = sizes
+            .iter()
+            .map(|size| {
+                probabilities
+                    .iter()
+                    .map(|prob| {
+                        let x = (prob - threshold) * (*size as f64).powf(1.0 / critical_exponent);
+                        let rate = 0.5 + 0.15 * x + 0.01 * x * x;
+                        let n_failures = (rate * n_iterations as f64).round() as u64;
+                        SimulationResult::with_n_successes_and_failures(
+                            n_iterations - n_failures,
+                            n_failures,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        SweepResult::new(sizes, probabilities, results)
+    }
+
+    #[test]
+    fn fit_recovers_the_parameters_of_the_ansatz() {
+        let fit = get_synthetic_result(0.3, 1.5)
+            .fit_finite_size_scaling()
+            .unwrap();
+        assert!((fit.get_threshold() - 0.3).abs() < 1e-3);
+        assert!((fit.get_critical_exponent() - 1.5).abs() < 1e-2);
+        assert!(fit.get_threshold_uncertainty() > 0.0);
+        assert!(fit.get_threshold_uncertainty() < 1e-3);
+        assert!(fit.get_critical_exponent_uncertainty() < 1e-2);
+        assert!((fit.get_coefficients()[0] - 0.5).abs() < 1e-3);
+        assert!((fit.predict(64, 0.3) - 0.5).abs() < 1e-3);
+        assert!(fit.get_reduced_chi_squared() < 10.0);
+    }
+
+    #[test]
+    fn fit_needs_at_least_two_codes() {
+        let result = get_synthetic_result(0.3, 1.5);
+        let single = SweepResult::new(
+            vec![16],
+            result.get_probabilities().to_vec(),
+            vec![result.results[0].clone()],
+        );
+        assert!(single.fit_finite_size_scaling().is_none());
+    }
+
+    #[test]
+    fn sweep_is_reproducible() {
+        let sweep = ThresholdSweep::from_decoder_builder(ErasureDecoder::with_prob)
+            .with_code(3, ParityCheckMatrix::repetition_code(3))
+            .with_code(7, ParityCheckMatrix::hamming_code(3))
+            .with_probabilities(vec![0.5, 0.1, 0.3])
+            .with_stopping_criteria(StoppingCriteria::new().with_max_iterations(200));
+        assert_eq!(sweep.get_probabilities(), &[0.1, 0.3, 0.5]);
+        let results: Vec<SweepResult> = (0..2)
+            .map(|_| sweep.run_with_rng(&mut ChaCha8Rng::seed_from_u64(3)))
+            .collect();
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0].get_sizes(), &[3, 7]);
+        assert_eq!(results[0].get_result(1, 2).get_n_iterations(), 200);
+    }
+}
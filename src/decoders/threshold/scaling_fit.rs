@@ -0,0 +1,358 @@
+//! Finite-size scaling fit of the failure rates around the threshold.
+//!
+//! The failure rate is modelled as `P_L = A + B x + C x^2` with `x = (p - p_th) n^(1 / nu)`.
+//! For fixed `p_th` and `nu`, the model is linear in `A`, `B` and `C` which are found by
+//! weighted least squares. The remaining chi-squared is then minimized over `p_th` and `nu` with
+//! the Nelder-Mead method.
+
+use super::SweepResult;
+
+const MAX_NELDER_MEAD_STEPS: usize = 2000;
+const NELDER_MEAD_TOLERANCE: f64 = 1e-12;
+
+/// The result of a finite-size scaling fit of a `SweepResult`.
+///
+/// The uncertainties are the standard deviations given by the covariance matrix of the fit
+/// scaled by the reduced chi-squared when it is larger than 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiniteSizeScalingFit {
+    threshold: f64,
+    threshold_uncertainty: f64,
+    critical_exponent: f64,
+    critical_exponent_uncertainty: f64,
+    coefficients: [f64; 3],
+    coefficient_uncertainties: [f64; 3],
+    reduced_chi_squared: f64,
+}
+
+impl FiniteSizeScalingFit {
+    /// Returns the estimated threshold `p_th`.
+    pub fn get_threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Returns the uncertainty of the threshold.
+    pub fn get_threshold_uncertainty(&self) -> f64 {
+        self.threshold_uncertainty
+    }
+
+    /// Returns the estimated critical exponent `nu`.
+    pub fn get_critical_exponent(&self) -> f64 {
+        self.critical_exponent
+    }
+
+    /// Returns the uncertainty of the critical exponent.
+    pub fn get_critical_exponent_uncertainty(&self) -> f64 {
+        self.critical_exponent_uncertainty
+    }
+
+    /// Returns the coefficients `[A, B, C]` of the scaling function.
+    pub fn get_coefficients(&self) -> [f64; 3] {
+        self.coefficients
+    }
+
+    /// Returns the uncertainties of the coefficients of the scaling function.
+    pub fn get_coefficient_uncertainties(&self) -> [f64; 3] {
+        self.coefficient_uncertainties
+    }
+
+    /// Returns the chi-squared of the fit divided by its number of degrees of freedom.
+    pub fn get_reduced_chi_squared(&self) -> f64 {
+        self.reduced_chi_squared
+    }
+
+    /// Returns the failure rate predicted by the fit for a code of the given `size` at error
+    /// probability `prob`.
+    pub fn predict(&self, size: usize, prob: f64) -> f64 {
+        let x = get_scaling_variable(prob, size, self.threshold, self.critical_exponent);
+        evaluate(&self.coefficients, x)
+    }
+}
+
+// A failure rate with its standard error.
+struct DataPoint {
+    size: f64,
+    prob: f64,
+    rate: f64,
+    error: f64,
+}
+
+pub(super) fn fit(sweep: &SweepResult) -> Option<FiniteSizeScalingFit> {
+    let points = get_data_points(sweep);
+    if sweep.get_sizes().len() < 2 || points.len() < 6 {
+        return None;
+    }
+    let chi_squared = |params: [f64; 2]| match solve_coefficients(&points, params[0], params[1]) {
+        Some((_, chi_squared)) => chi_squared,
+        None => f64::INFINITY,
+    };
+
+    let probabilities = sweep.get_probabilities();
+    let (low, high) = (probabilities[0], probabilities[probabilities.len() - 1]);
+    let step = ((high - low) / 4.0).max(1e-6);
+    let starts = [0.25, 0.5, 0.75]
+        .iter()
+        .flat_map(|fraction| {
+            [0.5_f64, 1.0, 2.0]
+                .iter()
+                .map(move |nu| [low + fraction * (high - low), nu.ln()])
+        })
+        .collect::<Vec<_>>();
+    let best = starts
+        .iter()
+        .map(|start| minimize(&chi_squared, *start, [step, 0.5]))
+        .filter(|params| chi_squared(*params).is_finite())
+        .fold(None, |best: Option<[f64; 2]>, params| match best {
+            Some(best) if chi_squared(best) <= chi_squared(params) => Some(best),
+            _ => Some(params),
+        })?;
+
+    let (threshold, critical_exponent) = (best[0], best[1].exp());
+    let (coefficients, chi_squared) = solve_coefficients(&points, threshold, best[1])?;
+    let degrees_of_freedom = points.len() - 5;
+    let reduced_chi_squared = chi_squared / degrees_of_freedom as f64;
+    let variances = get_variances(&points, threshold, critical_exponent, &coefficients)?;
+    let scale = reduced_chi_squared.max(1.0);
+    let uncertainty = |index: usize| (variances[index] * scale).sqrt();
+
+    Some(FiniteSizeScalingFit {
+        threshold,
+        threshold_uncertainty: uncertainty(0),
+        critical_exponent,
+        critical_exponent_uncertainty: uncertainty(1),
+        coefficients,
+        coefficient_uncertainties: [uncertainty(2), uncertainty(3), uncertainty(4)],
+        reduced_chi_squared,
+    })
+}
+
+// The standard errors use the rule of succession to keep a positive weight on the failure
+// rates of 0 or 1.
+fn get_data_points(sweep: &SweepResult) -> Vec<DataPoint> {
+    let mut points = Vec::new();
+    for (code, size) in sweep.get_sizes().iter().enumerate() {
+        for (index, prob) in sweep.get_probabilities().iter().enumerate() {
+            let result = sweep.get_result(code, index);
+            let n_iterations = result.get_n_iterations() as f64;
+            if n_iterations == 0.0 {
+                continue;
+            }
+            let smoothed = (result.get_n_failures() as f64 + 1.0) / (n_iterations + 2.0);
+            points.push(DataPoint {
+                size: *size as f64,
+                prob: *prob,
+                rate: result.get_failure_rate(),
+                error: (smoothed * (1.0 - smoothed) / n_iterations).sqrt(),
+            });
+        }
+    }
+    points
+}
+
+fn get_scaling_variable(prob: f64, size: usize, threshold: f64, critical_exponent: f64) -> f64 {
+    (prob - threshold) * (size as f64).powf(1.0 / critical_exponent)
+}
+
+fn evaluate(coefficients: &[f64; 3], x: f64) -> f64 {
+    coefficients[0] + coefficients[1] * x + coefficients[2] * x * x
+}
+
+// Returns the best coefficients and the chi-squared for fixed threshold and logarithm of the
+// critical exponent.
+fn solve_coefficients(
+    points: &[DataPoint],
+    threshold: f64,
+    log_critical_exponent: f64,
+) -> Option<([f64; 3], f64)> {
+    let inverse_exponent = (-log_critical_exponent).exp();
+    let xs: Vec<f64> = points
+        .iter()
+        .map(|point| (point.prob - threshold) * point.size.powf(inverse_exponent))
+        .collect();
+    let mut normal = vec![vec![0.0; 3]; 3];
+    let mut right = [0.0; 3];
+    for (point, x) in points.iter().zip(xs.iter()) {
+        let weight = 1.0 / (point.error * point.error);
+        let basis = [1.0, *x, x * x];
+        for row in 0..3 {
+            right[row] += weight * basis[row] * point.rate;
+            for column in 0..3 {
+                normal[row][column] += weight * basis[row] * basis[column];
+            }
+        }
+    }
+    let inverse = invert(normal)?;
+    let mut coefficients = [0.0; 3];
+    for (row, coefficient) in coefficients.iter_mut().enumerate() {
+        *coefficient = (0..3)
+            .map(|column| inverse[row][column] * right[column])
+            .sum();
+    }
+    let chi_squared = points
+        .iter()
+        .zip(xs.iter())
+        .map(|(point, x)| ((point.rate - evaluate(&coefficients, *x)) / point.error).powi(2))
+        .sum();
+    Some((coefficients, chi_squared))
+}
+
+// Returns the diagonal of the covariance matrix of the threshold, the critical exponent and
+// the coefficients.
+fn get_variances(
+    points: &[DataPoint],
+    threshold: f64,
+    critical_exponent: f64,
+    coefficients: &[f64; 3],
+) -> Option<Vec<f64>> {
+    let mut information = vec![vec![0.0; 5]; 5];
+    for point in points.iter() {
+        let scale = point.size.powf(1.0 / critical_exponent);
+        let x = (point.prob - threshold) * scale;
+        let slope = coefficients[1] + 2.0 * coefficients[2] * x;
+        let gradient = [
+            -slope * scale,
+            -slope * x * point.size.ln() / (critical_exponent * critical_exponent),
+            1.0,
+            x,
+            x * x,
+        ];
+        let weight = 1.0 / (point.error * point.error);
+        for row in 0..5 {
+            for column in 0..5 {
+                information[row][column] += weight * gradient[row] * gradient[column];
+            }
+        }
+    }
+    let covariance = invert(information)?;
+    Some((0..5).map(|index| covariance[index][index]).collect())
+}
+
+// Inverts a square matrix with Gauss-Jordan elimination and partial pivoting.
+fn invert(mut matrix: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let size = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|column| (row == column) as u8 as f64)
+                .collect()
+        })
+        .collect();
+    for column in 0..size {
+        let pivot = (column..size).max_by(|first, second| {
+            matrix[*first][column]
+                .abs()
+                .partial_cmp(&matrix[*second][column].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if matrix[pivot][column].abs() < 1e-300 || !matrix[pivot][column].is_finite() {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let factor = matrix[column][column];
+        for index in 0..size {
+            matrix[column][index] /= factor;
+            inverse[column][index] /= factor;
+        }
+        for row in 0..size {
+            if row != column {
+                let factor = matrix[row][column];
+                for index in 0..size {
+                    matrix[row][index] -= factor * matrix[column][index];
+                    inverse[row][index] -= factor * inverse[column][index];
+                }
+            }
+        }
+    }
+    Some(inverse)
+}
+
+// Minimizes a function of 2 variables with the Nelder-Mead simplex method.
+fn minimize<F: Fn([f64; 2]) -> f64>(function: &F, start: [f64; 2], steps: [f64; 2]) -> [f64; 2] {
+    let mut simplex = [
+        start,
+        [start[0] + steps[0], start[1]],
+        [start[0], start[1] + steps[1]],
+    ];
+    let mut values: Vec<f64> = simplex.iter().map(|point| function(*point)).collect();
+    let combine = |first: [f64; 2], second: [f64; 2], factor: f64| {
+        [
+            first[0] + factor * (second[0] - first[0]),
+            first[1] + factor * (second[1] - first[1]),
+        ]
+    };
+    for _ in 0..MAX_NELDER_MEAD_STEPS {
+        let mut order = [0, 1, 2];
+        order.sort_by(|first, second| {
+            values[*first]
+                .partial_cmp(&values[*second])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let (best, middle, worst) = (order[0], order[1], order[2]);
+        if (values[worst] - values[best]).abs() <= NELDER_MEAD_TOLERANCE * values[best].abs()
+            && values[best].is_finite()
+        {
+            break;
+        }
+        let centroid = combine(simplex[best], simplex[middle], 0.5);
+        let reflected = combine(centroid, simplex[worst], -1.0);
+        let reflected_value = function(reflected);
+        if reflected_value < values[best] {
+            let expanded = combine(centroid, simplex[worst], -2.0);
+            let expanded_value = function(expanded);
+            if expanded_value < reflected_value {
+                simplex[worst] = expanded;
+                values[worst] = expanded_value;
+            } else {
+                simplex[worst] = reflected;
+                values[worst] = reflected_value;
+            }
+        } else if reflected_value < values[middle] {
+            simplex[worst] = reflected;
+            values[worst] = reflected_value;
+        } else {
+            let contracted = combine(centroid, simplex[worst], 0.5);
+            let contracted_value = function(contracted);
+            if contracted_value < values[worst] {
+                simplex[worst] = contracted;
+                values[worst] = contracted_value;
+            } else {
+                for index in [middle, worst].iter() {
+                    simplex[*index] = combine(simplex[best], simplex[*index], 0.5);
+                    values[*index] = function(simplex[*index]);
+                }
+            }
+        }
+    }
+    let best = (0..3)
+        .min_by(|first, second| {
+            values[*first]
+                .partial_cmp(&values[*second])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0);
+    simplex[best]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inversion_of_a_small_matrix() {
+        let inverse = invert(vec![vec![4.0, 7.0], vec![2.0, 6.0]]).unwrap();
+        assert!((inverse[0][0] - 0.6).abs() < 1e-12);
+        assert!((inverse[0][1] + 0.7).abs() < 1e-12);
+        assert!((inverse[1][0] + 0.2).abs() < 1e-12);
+        assert!((inverse[1][1] - 0.4).abs() < 1e-12);
+        assert!(invert(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).is_none());
+    }
+
+    #[test]
+    fn minimization_of_a_quadratic() {
+        let function = |point: [f64; 2]| (point[0] - 1.0).powi(2) + 3.0 * (point[1] + 2.0).powi(2);
+        let minimum = minimize(&function, [0.0, 0.0], [0.5, 0.5]);
+        assert!((minimum[0] - 1.0).abs() < 1e-4);
+        assert!((minimum[1] + 2.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,616 @@
+//! A union-find decoder for erasures and Pauli errors on CSS codes.
+//!
+//! The implementation follows "Almost-linear time decoding algorithm for topological codes,
+//! Nicolas Delfosse and Naomi H. Nickerson, 2021, Quantum". The clusters grow from their boundary
+//! in the Tanner graph of each type of checks and the correction is found by the peeling decoder
+//! of "Linear-time maximum likelihood decoding of surface codes over the quantum erasure
+//! channel, Nicolas Delfosse and Gilles Zémor, 2020, Physical Review Research".
+
+use super::{Decoder, DecodingResult};
+use crate::{GF4Stabilizers, ParityCheckMatrix, Pauli, PauliString, GF2};
+use rand::Rng;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Decoder for erasures and Pauli errors on CSS codes where each qubit is in at most 2
+/// stabilizers of each type.
+///
+/// This includes surface codes, toric codes and their variants. The X errors are decoded with
+/// the Z stabilizers and the Z errors with the X stabilizers. For each type of error, the
+/// Tanner graph is seen as a graph whose nodes are the checks and a boundary node. Each qubit is
+/// an edge between its 2 checks or between its check and the boundary.
+///
+/// The clusters are initialized with the erased edges and the flipped checks. Then, each
+/// cluster with an odd number of flipped checks that doesn't touch the boundary grows by half an
+/// edge from its boundary nodes until all clusters are even or touch the boundary. Finally, the
+/// correction is found by peeling a spanning forest of the grown edges. For erasures only, no
+/// cluster grows and the decoder succeeds whenever a maximum likelihood decoder succeeds.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let code = GF4Stabilizers::surface_code(3);
+/// let mut decoder = UnionFindDecoder::new(code, 0.1, 0.01);
+///
+/// // An erased qubit with an X error and a Z error on an other qubit.
+/// let paulis = PauliString::from_sparse_paulis(&[(Pauli::X, 4), (Pauli::Z, 8)], 13);
+/// let error = ErasureAndPauliError::new(vec![4], paulis);
+/// assert_eq!(decoder.decode(&error), UnionFindResult::Success);
+///
+/// // The Steane code has qubits in 3 stabilizers of the same type.
+/// let steane = UnionFindDecoder::try_new(GF4Stabilizers::steane_code(), 0.1, 0.01);
+/// assert!(steane.is_err());
+/// ```
+#[derive(Clone)]
+pub struct UnionFindDecoder {
+    stabilizers: GF4Stabilizers,
+    x_error_graph: DecodingGraph,
+    z_error_graph: DecodingGraph,
+    z_logicals: ParityCheckMatrix,
+    x_logicals: ParityCheckMatrix,
+    erasure_prob: f64,
+    pauli_prob: f64,
+}
+
+/// The reasons why a code can't be decoded by a union-find decoder.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnionFindError {
+    /// A stabilizer has both X and Z Paulis.
+    NotCss { stabilizer: usize },
+    /// A qubit is in more than 2 stabilizers of the same type.
+    QubitInTooManyStabilizers { qubit: usize },
+}
+
+impl fmt::Display for UnionFindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotCss { stabilizer } => {
+                write!(f, "stabilizer {} has both X and Z Paulis", stabilizer)
+            }
+            Self::QubitInTooManyStabilizers { qubit } => write!(
+                f,
+                "qubit {} is in more than 2 stabilizers of the same type",
+                qubit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnionFindError {}
+
+/// An error on a quantum code with erased qubits and Pauli errors.
+///
+/// The erased qubits are known to the decoder while the Pauli errors are not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErasureAndPauliError {
+    erased_qubits: Vec<usize>,
+    paulis: PauliString,
+}
+
+impl ErasureAndPauliError {
+    /// Creates an error with the given `erased_qubits` and Pauli errors.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some erased qubits are out of bound.
+    pub fn new(erased_qubits: Vec<usize>, paulis: PauliString) -> Self {
+        if erased_qubits
+            .iter()
+            .any(|qubit| *qubit >= paulis.get_n_qubits())
+        {
+            panic!("erased qubit out of bound");
+        }
+        Self {
+            erased_qubits,
+            paulis,
+        }
+    }
+
+    /// Returns the erased qubits.
+    pub fn get_erased_qubits(&self) -> &[usize] {
+        &self.erased_qubits
+    }
+
+    /// Returns the Pauli errors including the ones on erased qubits.
+    pub fn get_paulis(&self) -> &PauliString {
+        &self.paulis
+    }
+}
+
+/// A union-find decoder either corrects the error up to a stabilizer or introduces a logical
+/// error.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnionFindResult {
+    Failure,
+    Success,
+}
+
+impl DecodingResult for UnionFindResult {
+    fn is_success(&self) -> bool {
+        self == &Self::Success
+    }
+}
+
+impl UnionFindDecoder {
+    /// Creates a union-find decoder for a CSS code.
+    ///
+    /// The random errors erase each qubit with probability `erasure_prob` and apply a uniformly
+    /// random Pauli on it. Each other qubit suffers a depolarizing error of probability
+    /// `pauli_prob`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some probabilities are not between 0.0 and 1.0, if the code is not CSS or if
+    /// some qubits are in more than 2 stabilizers of the same type. The same conditions are
+    /// checked by `for_code`.
+    pub fn new(stabilizers: GF4Stabilizers, erasure_prob: f64, pauli_prob: f64) -> Self {
+        Self::try_new(stabilizers, erasure_prob, pauli_prob)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Creates a union-find decoder for a CSS code or returns an error if the code is not CSS
+    /// or if some qubits are in more than 2 stabilizers of the same type.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some probabilities are not between 0.0 and 1.0.
+    pub fn try_new(
+        stabilizers: GF4Stabilizers,
+        erasure_prob: f64,
+        pauli_prob: f64,
+    ) -> Result<Self, UnionFindError> {
+        if !(0.0..=1.0).contains(&erasure_prob) || !(0.0..=1.0).contains(&pauli_prob) {
+            panic!("invalid probability");
+        }
+        Self::with_probs(erasure_prob, pauli_prob).try_for_code(stabilizers)
+    }
+
+    fn with_probs(erasure_prob: f64, pauli_prob: f64) -> Self {
+        let empty = DecodingGraph::from_checks(ParityCheckMatrix::new()).unwrap();
+        Self {
+            stabilizers: GF4Stabilizers::empty_with_n_bits(0),
+            x_error_graph: empty.clone(),
+            z_error_graph: empty,
+            z_logicals: ParityCheckMatrix::new(),
+            x_logicals: ParityCheckMatrix::new(),
+            erasure_prob,
+            pauli_prob,
+        }
+    }
+
+    fn try_for_code(mut self, code: GF4Stabilizers) -> Result<Self, UnionFindError> {
+        let x_checks = code.x_checks().checks_iter();
+        let z_checks = code.z_checks().checks_iter();
+        if let Some(stabilizer) = x_checks
+            .zip(z_checks)
+            .position(|(x_check, z_check)| x_check.get_n_bits() > 0 && z_check.get_n_bits() > 0)
+        {
+            return Err(UnionFindError::NotCss { stabilizer });
+        }
+        let to_error = |qubit| UnionFindError::QubitInTooManyStabilizers { qubit };
+        let n_qubits = code.n_qubits();
+        self.x_error_graph =
+            DecodingGraph::from_checks(code.z_checks().without_empty_checks()).map_err(to_error)?;
+        self.z_error_graph =
+            DecodingGraph::from_checks(code.x_checks().without_empty_checks()).map_err(to_error)?;
+        self.z_logicals =
+            ParityCheckMatrix::with_n_bits(n_qubits).with_checks(code.get_z_logicals());
+        self.x_logicals =
+            ParityCheckMatrix::with_n_bits(n_qubits).with_checks(code.get_x_logicals());
+        self.stabilizers = code;
+        Ok(self)
+    }
+
+    fn has_logical_error(
+        graph: &DecodingGraph,
+        logicals: &ParityCheckMatrix,
+        error: &[GF2],
+        erased: &[bool],
+    ) -> bool {
+        let syndrome = graph.checks.get_syndrome_of(error);
+        let correction = graph.find_correction(&syndrome, erased);
+        let residual: Vec<GF2> = error
+            .iter()
+            .zip(correction.iter())
+            .map(|(bit, correction)| *bit + *correction)
+            .collect();
+        graph.checks.get_syndrome_of(&residual).contains(&GF2::B1)
+            || logicals.get_syndrome_of(&residual).contains(&GF2::B1)
+    }
+}
+
+impl Decoder for UnionFindDecoder {
+    type Error = ErasureAndPauliError;
+    type Result = UnionFindResult;
+    type Code = GF4Stabilizers;
+
+    fn for_code(self, code: Self::Code) -> Self {
+        self.try_for_code(code)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn take_code(&mut self) -> Self::Code {
+        std::mem::replace(&mut self.stabilizers, GF4Stabilizers::empty_with_n_bits(0))
+    }
+
+    fn decode(&mut self, error: &Self::Error) -> Self::Result {
+        let n_qubits = self.stabilizers.n_qubits();
+        let mut erased = vec![false; n_qubits];
+        error
+            .erased_qubits
+            .iter()
+            .for_each(|qubit| erased[*qubit] = true);
+        let symplectic = error.paulis.to_symplectic();
+        let (x_error, z_error) = symplectic.split_at(n_qubits);
+        if Self::has_logical_error(&self.x_error_graph, &self.z_logicals, x_error, &erased)
+            || Self::has_logical_error(&self.z_error_graph, &self.x_logicals, z_error, &erased)
+        {
+            UnionFindResult::Failure
+        } else {
+            UnionFindResult::Success
+        }
+    }
+
+    fn get_random_error_with_rng<R: Rng>(&self, rng: &mut R) -> Self::Error {
+        let n_qubits = self.stabilizers.n_qubits();
+        let paulis = [Pauli::I, Pauli::X, Pauli::Y, Pauli::Z];
+        let mut erased_qubits = Vec::new();
+        let mut errors = Vec::new();
+        for qubit in 0..n_qubits {
+            if rng.gen::<f64>() < self.erasure_prob {
+                erased_qubits.push(qubit);
+                errors.push((paulis[rng.gen_range(0, 4)], qubit));
+            } else if rng.gen::<f64>() < self.pauli_prob {
+                errors.push((paulis[rng.gen_range(1, 4)], qubit));
+            }
+        }
+        ErasureAndPauliError::new(
+            erased_qubits,
+            PauliString::from_sparse_paulis(&errors, n_qubits),
+        )
+    }
+}
+
+// An edge is fully grown once both of its halves are grown.
+const FULLY_GROWN: u8 = 2;
+
+// The Tanner graph of a check matrix where each bit is in at most 2 checks. The nodes are the
+// checks followed by a boundary node. Each bit is an edge between its checks or between its check
+// and the boundary. The bits that are in no check are not edges.
+#[derive(Clone)]
+struct DecodingGraph {
+    checks: ParityCheckMatrix,
+    edges: Vec<Option<(usize, usize)>>,
+    edges_of_nodes: Vec<Vec<usize>>,
+}
+
+impl DecodingGraph {
+    // Returns the graph of `checks` or the first bit that is in more than 2 checks.
+    fn from_checks(checks: ParityCheckMatrix) -> Result<Self, usize> {
+        let boundary = checks.get_n_checks();
+        let mut checks_of_bits = vec![Vec::new(); checks.get_n_bits()];
+        for (check, bits) in checks.checks_iter().enumerate() {
+            bits.iter().for_each(|bit| checks_of_bits[*bit].push(check));
+        }
+        let mut edges = Vec::with_capacity(checks_of_bits.len());
+        let mut edges_of_nodes = vec![Vec::new(); boundary + 1];
+        for (bit, checks_of_bit) in checks_of_bits.iter().enumerate() {
+            let edge = match checks_of_bit.as_slice() {
+                [] => None,
+                [check] => Some((*check, boundary)),
+                [first, second] => Some((*first, *second)),
+                _ => return Err(bit),
+            };
+            if let Some((first, second)) = edge {
+                edges_of_nodes[first].push(bit);
+                edges_of_nodes[second].push(bit);
+            }
+            edges.push(edge);
+        }
+        Ok(Self {
+            checks,
+            edges,
+            edges_of_nodes,
+        })
+    }
+
+    fn n_nodes(&self) -> usize {
+        self.edges_of_nodes.len()
+    }
+
+    fn get_boundary(&self) -> usize {
+        self.n_nodes() - 1
+    }
+
+    fn get_ends(&self, edge: usize) -> (usize, usize) {
+        self.edges[edge].expect("the bit is not an edge")
+    }
+
+    fn get_other_end(&self, edge: usize, node: usize) -> usize {
+        let (first, second) = self.get_ends(edge);
+        if first == node {
+            second
+        } else {
+            first
+        }
+    }
+
+    // Returns an error supported on the grown clusters whose syndrome is `syndrome`.
+    fn find_correction(&self, syndrome: &[GF2], erased: &[bool]) -> Vec<GF2> {
+        let mut clusters = Clusters::new(self.n_nodes(), self.get_boundary());
+        for (check, value) in syndrome.iter().enumerate() {
+            if *value == GF2::B1 {
+                clusters.add_defect(check);
+            }
+        }
+        let mut growth = vec![0; self.edges.len()];
+        for edge in (0..self.edges.len()).filter(|edge| erased[*edge]) {
+            if let Some((first, second)) = self.edges[edge] {
+                growth[edge] = FULLY_GROWN;
+                clusters.union(first, second);
+            }
+        }
+        self.grow(&mut clusters, &mut growth);
+        self.peel(syndrome, &growth)
+    }
+
+    // Grows the odd clusters by half an edge from their boundary nodes until all clusters are
+    // even or contain the boundary node.
+    fn grow(&self, clusters: &mut Clusters, growth: &mut [u8]) {
+        loop {
+            let odd_roots = clusters.get_odd_roots();
+            let mut fused_edges = Vec::new();
+            for root in odd_roots.iter() {
+                let boundary_nodes = std::mem::take(&mut clusters.boundary_nodes[*root]);
+                let remaining = boundary_nodes
+                    .into_iter()
+                    .filter(|node| {
+                        let mut is_on_boundary = false;
+                        for edge in self.edges_of_nodes[*node].iter() {
+                            if growth[*edge] < FULLY_GROWN {
+                                growth[*edge] += 1;
+                                if growth[*edge] == FULLY_GROWN {
+                                    fused_edges.push(*edge);
+                                } else {
+                                    is_on_boundary = true;
+                                }
+                            }
+                        }
+                        is_on_boundary
+                    })
+                    .collect();
+                clusters.boundary_nodes[*root] = remaining;
+            }
+            // Without fused edges, the remaining odd clusters have no boundary left and the
+            // syndrome has no solution.
+            if fused_edges.is_empty()
+                && odd_roots
+                    .iter()
+                    .all(|root| clusters.boundary_nodes[*root].is_empty())
+            {
+                break;
+            }
+            for edge in fused_edges {
+                let (first, second) = self.get_ends(edge);
+                clusters.union(first, second);
+            }
+        }
+    }
+
+    // Returns the correction found by peeling a spanning forest of the grown edges. The trees
+    // are rooted at the boundary node when possible such that it absorbs the odd parities.
+    fn peel(&self, syndrome: &[GF2], growth: &[u8]) -> Vec<GF2> {
+        let boundary = self.get_boundary();
+        let mut is_defect: Vec<bool> = syndrome.iter().map(|value| *value == GF2::B1).collect();
+        is_defect.push(false);
+        let mut is_visited = vec![false; self.n_nodes()];
+        let mut tree_edges = Vec::new();
+        let roots = std::iter::once(boundary).chain(0..boundary);
+        for root in roots {
+            if is_visited[root] || (root != boundary && !is_defect[root]) {
+                continue;
+            }
+            is_visited[root] = true;
+            let mut queue = VecDeque::new();
+            queue.push_back(root);
+            while let Some(node) = queue.pop_front() {
+                for edge in self.edges_of_nodes[node].iter() {
+                    let neighbor = self.get_other_end(*edge, node);
+                    if growth[*edge] == FULLY_GROWN && !is_visited[neighbor] {
+                        is_visited[neighbor] = true;
+                        tree_edges.push((neighbor, *edge));
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        // The leaves are peeled first. A flipped leaf is explained by the edge to its parent.
+        let mut correction = vec![GF2::B0; self.edges.len()];
+        for (leaf, edge) in tree_edges.into_iter().rev() {
+            if is_defect[leaf] {
+                is_defect[leaf] = false;
+                correction[edge] = GF2::B1;
+                let parent = self.get_other_end(edge, leaf);
+                is_defect[parent] = !is_defect[parent];
+            }
+        }
+        correction
+    }
+}
+
+// A union-find structure over the nodes of a decoding graph. Each root keeps the parity of the
+// flipped checks in its cluster and the nodes of its cluster that have edges not fully grown.
+struct Clusters {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    is_odd: Vec<bool>,
+    boundary_nodes: Vec<Vec<usize>>,
+    boundary: usize,
+    roots: Vec<usize>,
+}
+
+impl Clusters {
+    // Creates a cluster for each node where only the boundary node touches the boundary.
+    fn new(n_nodes: usize, boundary: usize) -> Self {
+        Self {
+            parents: (0..n_nodes).collect(),
+            sizes: vec![1; n_nodes],
+            is_odd: vec![false; n_nodes],
+            boundary_nodes: (0..n_nodes).map(|node| vec![node]).collect(),
+            boundary,
+            roots: Vec::new(),
+        }
+    }
+
+    fn add_defect(&mut self, node: usize) {
+        let root = self.find(node);
+        self.is_odd[root] = !self.is_odd[root];
+        self.roots.push(root);
+    }
+
+    fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = node;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    fn touches_boundary(&mut self, node: usize) -> bool {
+        let boundary = self.boundary;
+        self.find(node) == self.find(boundary)
+    }
+
+    // Merges the smaller cluster into the larger one.
+    fn union(&mut self, first: usize, second: usize) {
+        let mut large = self.find(first);
+        let mut small = self.find(second);
+        if large == small {
+            return;
+        }
+        if self.sizes[large] < self.sizes[small] {
+            std::mem::swap(&mut large, &mut small);
+        }
+        self.parents[small] = large;
+        self.sizes[large] += self.sizes[small];
+        self.is_odd[large] ^= self.is_odd[small];
+        let mut moved = std::mem::take(&mut self.boundary_nodes[small]);
+        self.boundary_nodes[large].append(&mut moved);
+        self.roots.push(large);
+    }
+
+    // Returns the roots of the clusters with an odd number of flipped checks that don't touch
+    // the boundary.
+    fn get_odd_roots(&mut self) -> Vec<usize> {
+        let mut roots: Vec<usize> = std::mem::take(&mut self.roots)
+            .into_iter()
+            .map(|node| self.find(node))
+            .collect();
+        roots.sort_unstable();
+        roots.dedup();
+        self.roots = roots
+            .into_iter()
+            .filter(|root| self.is_odd[*root] && !self.touches_boundary(*root))
+            .collect();
+        self.roots.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::QuantumErasureDecoder;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn single_pauli_errors_are_corrected() {
+        let code = GF4Stabilizers::surface_code(3);
+        let mut decoder = UnionFindDecoder::new(code, 0.0, 0.1);
+        for qubit in 0..13 {
+            for pauli in [Pauli::X, Pauli::Y, Pauli::Z].iter() {
+                let paulis = PauliString::from_sparse_paulis(&[(*pauli, qubit)], 13);
+                let error = ErasureAndPauliError::new(Vec::new(), paulis);
+                assert_eq!(decoder.decode(&error), UnionFindResult::Success);
+            }
+        }
+    }
+
+    #[test]
+    fn weight_2_errors_are_corrected_on_a_distance_5_code() {
+        let code = GF4Stabilizers::surface_code(5);
+        let n_qubits = code.n_qubits();
+        let mut decoder = UnionFindDecoder::new(code, 0.0, 0.1);
+        for first in 0..n_qubits {
+            for second in (first + 1)..n_qubits {
+                let paulis = PauliString::from_sparse_paulis(
+                    &[(Pauli::X, first), (Pauli::X, second)],
+                    n_qubits,
+                );
+                let error = ErasureAndPauliError::new(Vec::new(), paulis);
+                assert_eq!(decoder.decode(&error), UnionFindResult::Success);
+            }
+        }
+    }
+
+    #[test]
+    fn codes_must_be_css_with_qubits_in_at_most_2_stabilizers_of_each_type() {
+        assert_eq!(
+            UnionFindDecoder::try_new(GF4Stabilizers::five_qubit_code(), 0.1, 0.0).err(),
+            Some(UnionFindError::NotCss { stabilizer: 0 })
+        );
+        assert!(matches!(
+            UnionFindDecoder::try_new(GF4Stabilizers::steane_code(), 0.1, 0.0),
+            Err(UnionFindError::QubitInTooManyStabilizers { .. })
+        ));
+    }
+
+    #[test]
+    fn logical_errors_are_failures() {
+        let code = GF4Stabilizers::surface_code(3);
+        let logical = code.get_x_logicals()[0].clone();
+        let mut decoder = UnionFindDecoder::new(code, 0.0, 0.1);
+        let paulis = PauliString::from_sparse_paulis(
+            &logical
+                .iter()
+                .map(|qubit| (Pauli::X, *qubit))
+                .collect::<Vec<_>>(),
+            13,
+        );
+        let error = ErasureAndPauliError::new(Vec::new(), paulis);
+        assert_eq!(decoder.decode(&error), UnionFindResult::Failure);
+    }
+
+    #[test]
+    fn erasures_are_decoded_as_well_as_maximum_likelihood() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let mut union_find = UnionFindDecoder::new(GF4Stabilizers::surface_code(5), 0.3, 0.0);
+        let mut maximum_likelihood =
+            QuantumErasureDecoder::new(GF4Stabilizers::surface_code(5), 0.3);
+        for _ in 0..200 {
+            let error = union_find.get_random_error_with_rng(&mut rng);
+            if maximum_likelihood
+                .decode(&error.get_erased_qubits().to_vec())
+                .is_success()
+            {
+                assert_eq!(union_find.decode(&error), UnionFindResult::Success);
+            }
+        }
+    }
+
+    #[test]
+    fn toric_code_with_mixed_noise() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let decoder = UnionFindDecoder::new(GF4Stabilizers::toric_code(6), 0.05, 0.01);
+        let result = decoder.simulate_n_iterations_with_rng(200, &mut rng);
+        assert!(result.get_failure_rate() < 0.1);
+    }
+}
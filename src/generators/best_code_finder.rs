@@ -1,9 +1,13 @@
 use super::CodeGenerator;
-use crate::{Decoder, ErasureDecoder, ParityCheckMatrix, SimulationResult};
+use crate::{
+    CancellationToken, Decoder, ErasureDecoder, ParityCheckMatrix, SimulationMonitor,
+    SimulationResult,
+};
 use rand::distributions::Standard;
 use rand::{Rng, SeedableRng, thread_rng};
 use rand_chacha::ChaCha8Rng;
 use rayon::prelude::*;
+use std::ops::Range;
 
 type CodeAndResult = (Option<ParityCheckMatrix>, SimulationResult);
 
@@ -86,7 +90,7 @@ impl<'a, G: CodeGenerator> BestCodeFinderUsingErasure<'a, G> {
     ///     .with_erasure_prob(0.2);
     /// ```
     pub fn with_erasure_prob(mut self, prob: f64) -> Self {
-        if prob < 0.0 || prob > 1.0 {
+        if !(0.0..=1.0).contains(&prob) {
             panic!("prob is not between 0 and 1")
         }
         self.erasure_prob = prob;
@@ -151,6 +155,69 @@ impl<'a, G: CodeGenerator> BestCodeFinderUsingErasure<'a, G> {
         self.find_best_code_simulating_n_iterations_with_rng(n_iterations, &mut thread_rng())
     }
 
+    /// Returns the best code and its performance obtained using the given random number generator
+    /// `rng`, reporting to `monitor` the number of simulated codes and the best result so far.
+    /// 
+    /// To evaluate the performance of each code, `n_iterations` random error decoding are done.
+    /// 
+    /// If the token of `monitor` is cancelled, the codes that are not fully simulated are
+    /// ignored and the best code among the others is returned. Otherwise, the code and its
+    /// performance are the same as `find_best_code_simulating_n_iterations_with_rng`.
+    /// 
+    /// # Example 
+    /// 
+    /// ```
+    /// use believer::{BestCodeFinderUsingErasure, SimulationMonitor, SimulationProgress};
+    /// use believer::RegularLDPCCodeGenerator;
+    /// use rand::thread_rng;
+    /// 
+    /// let generator = RegularLDPCCodeGenerator::new(3, 4, 2, 4);
+    /// let code_finder = BestCodeFinderUsingErasure::from_code_generator(&generator)
+    ///     .with_erasure_prob(0.5)
+    ///     .among_n_codes(10);
+    /// let mut n_simulated_codes = 0;
+    /// let mut monitor = SimulationMonitor::new()
+    ///     .with_observer(|progress: &SimulationProgress| {
+    ///         n_simulated_codes = progress.get_n_completed()
+    ///     });
+    /// let (code, result) = code_finder.find_best_code_simulating_n_iterations_with_monitor_and_rng(
+    ///     1000,
+    ///     &mut monitor,
+    ///     &mut thread_rng(),
+    /// );
+    /// drop(monitor);
+    /// assert_eq!(n_simulated_codes, 10);
+    /// ```
+    pub fn find_best_code_simulating_n_iterations_with_monitor_and_rng<R: Rng>(
+        &self,
+        n_iterations: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> CodeAndResult {
+        NIterationsBestCodeFinderUsingErasure::from(self)
+            .with_n_iterations(n_iterations)
+            .find_with_monitor_and_rng(monitor, rng)
+    }
+
+    /// Returns the best code and its performance obtained using the thread rng, reporting to
+    /// `monitor` the number of simulated codes and the best result so far.
+    /// 
+    /// To evaluate the performance of each code, `n_iterations` random error decoding are done.
+    /// 
+    /// If the token of `monitor` is cancelled, the codes that are not fully simulated are
+    /// ignored and the best code among the others is returned.
+    pub fn find_best_code_simulating_n_iterations_with_monitor(
+        &self,
+        n_iterations: usize,
+        monitor: &mut SimulationMonitor,
+    ) -> CodeAndResult {
+        self.find_best_code_simulating_n_iterations_with_monitor_and_rng(
+            n_iterations,
+            monitor,
+            &mut thread_rng(),
+        )
+    }
+
     /// Returns the best code and its performance obtained using the given random number generator 
     /// `rng`. 
     /// 
@@ -210,6 +277,46 @@ impl<'a, G: CodeGenerator> BestCodeFinderUsingErasure<'a, G> {
     pub fn find_best_code_simulating_n_events(&self, n_events: usize) -> CodeAndResult {
         self.find_best_code_simulating_n_events_with_rng(n_events, &mut thread_rng())
     }
+
+    /// Returns the best code and its performance obtained using the given random number generator
+    /// `rng`, reporting to `monitor` the number of simulated codes and the best result so far.
+    /// 
+    /// To evaluate the performance of each code, the code is simulated until `n_events` success
+    /// and `n_events` failures. 
+    /// 
+    /// If the token of `monitor` is cancelled, the codes that are not fully simulated are
+    /// ignored and the best code among the others is returned. Otherwise, the code and its
+    /// performance are the same as `find_best_code_simulating_n_events_with_rng`.
+    pub fn find_best_code_simulating_n_events_with_monitor_and_rng<R: Rng>(
+        &self,
+        n_events: usize,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> CodeAndResult {
+        NEventsBestCodeFinderUsingErasure::from(self)
+            .with_n_events(n_events)
+            .find_with_monitor_and_rng(monitor, rng)
+    }
+
+    /// Returns the best code and its performance obtained using the thread rng, reporting to
+    /// `monitor` the number of simulated codes and the best result so far.
+    /// 
+    /// To evaluate the performance of each code, the code is simulated until `n_events` success
+    /// and `n_events` failures. 
+    /// 
+    /// If the token of `monitor` is cancelled, the codes that are not fully simulated are
+    /// ignored and the best code among the others is returned.
+    pub fn find_best_code_simulating_n_events_with_monitor(
+        &self,
+        n_events: usize,
+        monitor: &mut SimulationMonitor,
+    ) -> CodeAndResult {
+        self.find_best_code_simulating_n_events_with_monitor_and_rng(
+            n_events,
+            monitor,
+            &mut thread_rng(),
+        )
+    }
 }
 
 // The next 2 structs are basically the same things. They should be refactored.
@@ -236,16 +343,25 @@ impl<'a, G: CodeGenerator> NIterationsBestCodeFinderUsingErasure<'a, G> {
 
     fn find_with_rng<R: Rng>(mut self, rng: &mut R) -> CodeAndResult {
         self.initialize_random_seeds_with_rng(rng);
-        (0..self.code_finder.n_codes_to_try)
-            .into_par_iter()
-            .map(|code_index| {
-                let mut rng = self.get_rng_for(code_index);
-                self.simulate_one_code_with_rng(&mut rng)
-            })
-            .reduce(
-                || (None, SimulationResult::worse_result()),
-                |accumulator, code_and_result| Self::get_best_between(accumulator, code_and_result),
-            )
+        let token = CancellationToken::new();
+        let range = 0..self.code_finder.n_codes_to_try;
+        find_best_code_in_range(range, &token, |code_index| {
+            let mut rng = self.get_rng_for(code_index);
+            self.simulate_one_code_with_rng(&mut rng, &token)
+        })
+        .1
+    }
+
+    fn find_with_monitor_and_rng<R: Rng>(
+        mut self,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> CodeAndResult {
+        self.initialize_random_seeds_with_rng(rng);
+        find_best_code_with_monitor(self.code_finder.n_codes_to_try, monitor, |code_index, token| {
+            let mut rng = self.get_rng_for(code_index);
+            self.simulate_one_code_with_rng(&mut rng, token)
+        })
     }
 
     fn initialize_random_seeds_with_rng<R: Rng>(&mut self, rng: &mut R) {
@@ -259,20 +375,18 @@ impl<'a, G: CodeGenerator> NIterationsBestCodeFinderUsingErasure<'a, G> {
         ChaCha8Rng::seed_from_u64(self.random_seeds[index])
     }
 
-    fn simulate_one_code_with_rng<R: Rng>(&self, rng: &mut R) -> CodeAndResult {
+    fn simulate_one_code_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+        token: &CancellationToken,
+    ) -> CodeAndResult {
         let code = self.code_finder.code_generator.generate_with_rng(rng);
         let mut decoder = ErasureDecoder::with_prob(self.code_finder.erasure_prob).for_code(code);
-        let result = decoder.simulate_n_iterations_with_rng(self.n_iterations, rng);
+        let mut monitor = get_monitor_for_one_code(token);
+        let result =
+            decoder.simulate_n_iterations_with_monitor_and_rng(self.n_iterations, &mut monitor, rng);
         (Some(decoder.take_code()), result)
     }
-
-    fn get_best_between(first: CodeAndResult, second: CodeAndResult) -> CodeAndResult {
-        if first.1.is_better_than(&second.1) {
-            first
-        } else {
-            second
-        }
-    }
 }
 
 struct NEventsBestCodeFinderUsingErasure<'a, G: CodeGenerator> {
@@ -297,16 +411,25 @@ impl<'a, G: CodeGenerator> NEventsBestCodeFinderUsingErasure<'a, G> {
 
     fn find_with_rng<R: Rng>(mut self, rng: &mut R) -> CodeAndResult {
         self.initialize_random_seeds_with_rng(rng);
-        (0..self.code_finder.n_codes_to_try)
-            .into_par_iter()
-            .map(|code_index| {
-                let mut rng = self.get_rng_for(code_index);
-                self.simulate_one_code_with_rng(&mut rng)
-            })
-            .reduce(
-                || (None, SimulationResult::worse_result()),
-                |accumulator, code_and_result| Self::get_best_between(accumulator, code_and_result),
-            )
+        let token = CancellationToken::new();
+        let range = 0..self.code_finder.n_codes_to_try;
+        find_best_code_in_range(range, &token, |code_index| {
+            let mut rng = self.get_rng_for(code_index);
+            self.simulate_one_code_with_rng(&mut rng, &token)
+        })
+        .1
+    }
+
+    fn find_with_monitor_and_rng<R: Rng>(
+        mut self,
+        monitor: &mut SimulationMonitor,
+        rng: &mut R,
+    ) -> CodeAndResult {
+        self.initialize_random_seeds_with_rng(rng);
+        find_best_code_with_monitor(self.code_finder.n_codes_to_try, monitor, |code_index, token| {
+            let mut rng = self.get_rng_for(code_index);
+            self.simulate_one_code_with_rng(&mut rng, token)
+        })
     }
 
     fn initialize_random_seeds_with_rng<R: Rng>(&mut self, rng: &mut R) {
@@ -320,20 +443,92 @@ impl<'a, G: CodeGenerator> NEventsBestCodeFinderUsingErasure<'a, G> {
         ChaCha8Rng::seed_from_u64(self.random_seeds[index])
     }
 
-    fn simulate_one_code_with_rng<R: Rng>(&self, rng: &mut R) -> CodeAndResult {
+    fn simulate_one_code_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+        token: &CancellationToken,
+    ) -> CodeAndResult {
         let code = self.code_finder.code_generator.generate_with_rng(rng);
         let mut decoder = ErasureDecoder::with_prob(self.code_finder.erasure_prob).for_code(code);
-        let result = decoder.simulate_until_n_events_are_found_with_rng(self.n_events, rng);
+        let mut monitor = get_monitor_for_one_code(token);
+        let result = decoder.simulate_until_n_events_are_found_with_monitor_and_rng(
+            self.n_events,
+            &mut monitor,
+            rng,
+        );
         (Some(decoder.take_code()), result)
     }
+}
 
-    fn get_best_between(first: CodeAndResult, second: CodeAndResult) -> CodeAndResult {
-        if first.1.is_better_than(&second.1) {
-            first
-        } else {
-            second
+// The simulation of a single code only needs to stop with the whole search.
+fn get_monitor_for_one_code(token: &CancellationToken) -> SimulationMonitor<'static> {
+    SimulationMonitor::new()
+        .with_cancellation_token(token.clone())
+        .with_n_reports(1)
+}
+
+// Simulates the codes chunk by chunk, reporting the best result after each chunk.
+fn find_best_code_with_monitor<F>(
+    n_codes: usize,
+    monitor: &mut SimulationMonitor,
+    simulate_one_code: F,
+) -> CodeAndResult
+where
+    F: Fn(usize, &CancellationToken) -> CodeAndResult + Sync,
+{
+    let mut best = (None, SimulationResult::worse_result());
+    let mut n_completed = 0;
+    for chunk in monitor.start(n_codes) {
+        let token = monitor.get_cancellation_token();
+        let (n_completed_in_chunk, best_in_chunk) =
+            find_best_code_in_range(chunk, token, |code_index| simulate_one_code(code_index, token));
+        n_completed += n_completed_in_chunk;
+        best = get_best_between(best, best_in_chunk);
+        monitor.report(n_completed, n_codes, best.1);
+        if monitor.is_cancelled() {
+            break;
         }
     }
+    best
+}
+
+// Returns the number of simulated codes in `range` and the best of them. The codes that are not
+// fully simulated before the cancellation of `token` are ignored.
+fn find_best_code_in_range<F>(
+    range: Range<usize>,
+    token: &CancellationToken,
+    simulate_one_code: F,
+) -> (usize, CodeAndResult)
+where
+    F: Fn(usize) -> CodeAndResult + Sync,
+{
+    range
+        .into_par_iter()
+        .map(|code_index| {
+            if token.is_cancelled() {
+                return (0, (None, SimulationResult::worse_result()));
+            }
+            let code_and_result = simulate_one_code(code_index);
+            if token.is_cancelled() {
+                (0, (None, SimulationResult::worse_result()))
+            } else {
+                (1, code_and_result)
+            }
+        })
+        .reduce(
+            || (0, (None, SimulationResult::worse_result())),
+            |(completed, best), (other_completed, other_best)| {
+                (completed + other_completed, get_best_between(best, other_best))
+            },
+        )
+}
+
+fn get_best_between(first: CodeAndResult, second: CodeAndResult) -> CodeAndResult {
+    if first.1.is_better_than(&second.1) {
+        first
+    } else {
+        second
+    }
 }
 
 #[cfg(test)]
@@ -376,4 +571,41 @@ mod test {
 
         assert_eq!(code_and_result_0, code_and_result_1);
     }
+
+    #[test]
+    fn monitored_search_finds_the_same_code() {
+        let rng = ChaCha8Rng::seed_from_u64(123);
+        let generator = RegularLDPCCodeGenerator::new(3, 4, 2, 4);
+        let code_finder = BestCodeFinderUsingErasure::from_code_generator(&generator)
+            .with_erasure_prob(0.25)
+            .among_n_codes(10);
+
+        let code_and_result =
+            code_finder.find_best_code_simulating_n_iterations_with_rng(50, &mut rng.clone());
+        let monitored_code_and_result = code_finder
+            .find_best_code_simulating_n_iterations_with_monitor_and_rng(
+                50,
+                &mut SimulationMonitor::new().with_n_reports(3),
+                &mut rng.clone(),
+            );
+        assert_eq!(monitored_code_and_result, code_and_result);
+    }
+
+    #[test]
+    fn cancelled_search_returns_no_code() {
+        let generator = RegularLDPCCodeGenerator::new(3, 4, 2, 4);
+        let code_finder = BestCodeFinderUsingErasure::from_code_generator(&generator)
+            .with_erasure_prob(0.25)
+            .among_n_codes(10);
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut monitor = SimulationMonitor::new().with_cancellation_token(token);
+        let (code, result) = code_finder.find_best_code_simulating_n_events_with_monitor_and_rng(
+            50,
+            &mut monitor,
+            &mut ChaCha8Rng::seed_from_u64(1),
+        );
+        assert_eq!(code, None);
+        assert_eq!(result, SimulationResult::worse_result());
+    }
 }
@@ -16,6 +16,12 @@ pub struct Generator {
     n_blocks_per_layer: usize,
 }
 
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Generator {
     // ***** Construction *****
 
@@ -81,11 +87,11 @@ impl Generator {
     // ***** Getters *****
 
     pub fn get_n_bits(&self) -> usize {
-        self.get_initial_block_length().pow(self.n_layers as u32)
+        self.get_initial_block_length().pow(self.n_layers)
     }
 
     pub fn get_n_checks(&self) -> usize {
-        self.n_checks_per_block.pow(self.n_layers as u32) as usize
+        self.n_checks_per_block.pow(self.n_layers)
     }
 
     fn get_initial_block_length(&self) -> usize {
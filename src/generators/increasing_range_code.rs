@@ -220,7 +220,7 @@ impl IRCodeGenBuilder {
 
     // Returns the initial range if fixed or default to the number of bits if not.
     fn initial_range(&self) -> usize {
-        self.initial_range.unwrap_or_else(|| self.n_bits() as usize)
+        self.initial_range.unwrap_or_else(|| self.n_bits())
     }
 
     // Returns the number of bits.
@@ -233,11 +233,11 @@ impl IRCodeGenBuilder {
     }
 
     fn max_bit_degree(&self) -> usize {
-        self.max_bit_degree.unwrap_or(self.n_checks() as usize)
+        self.max_bit_degree.unwrap_or(self.n_checks())
     }
 
     fn max_check_degree(&self) -> usize {
-        self.max_check_degree.unwrap_or(self.n_bits() as usize)
+        self.max_check_degree.unwrap_or(self.n_bits())
     }
 
     fn minimal_girth(&self) -> usize {
@@ -14,6 +14,18 @@ pub mod hierarchical_codes;
 pub mod increasing_range_code;
 pub use increasing_range_code::*;
 
+pub mod quasi_cyclic;
+pub use quasi_cyclic::*;
+
+pub mod progressive_edge_growth;
+pub use progressive_edge_growth::*;
+
+pub mod protograph;
+pub use protograph::*;
+
+pub mod spatially_coupled;
+pub use spatially_coupled::*;
+
 pub trait CodeGenerator: Sync + Send {
     /// Returns a code generated using the given random number generator `rng`.
     fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix;
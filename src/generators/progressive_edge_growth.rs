@@ -0,0 +1,339 @@
+//! Progressive edge growth codes.
+//!
+//! The implementation is based on "Regular and Irregular Progressive Edge-Growth Tanner Graphs,
+//! X.-Y. Hu, E. Eleftheriou and D. M. Arnold, 2005, IEEE Transactions on Information Theory" and
+//! the ACE improvement from "Improved Progressive-Edge-Growth (PEG) Construction of Irregular LDPC
+//! Codes, H. Xiao and A. H. Banihashemi, 2004, IEEE Communications Letters".
+
+use super::CodeGenerator;
+use crate::ParityCheckMatrix;
+use rand::Rng;
+
+/// A generator for progressive edge growth (PEG) codes.
+///
+/// Edges are placed one at a time. Each new edge of a bit is connected to the check that is the
+/// farthest away from that bit in the current Tanner graph, maximizing the local girth. Ties are
+/// broken by picking a check of minimal degree and then at random. Unlike the
+/// `random_checks::Generator`, every bit always reaches its target degree as long as it is not
+/// larger than the number of checks.
+///
+/// Bits are processed by increasing degree, but the generated code keeps the bit order of the
+/// given degree sequence.
+///
+/// # Example
+///
+/// ```
+/// use believer::{CodeGenerator, PEGCodeGenerator};
+///
+/// let bit_degrees = vec![3; 24];
+/// let generator = PEGCodeGenerator::new(bit_degrees, 12).with_ace_improvement();
+/// let code = generator.generate();
+///
+/// assert_eq!(code.get_n_bits(), 24);
+/// assert_eq!(code.get_n_checks(), 12);
+/// assert_eq!(code.get_bit_degrees(), vec![3; 24]);
+/// ```
+pub struct PEGCodeGenerator {
+    bit_degrees: Vec<usize>,
+    n_checks: usize,
+    use_ace: bool,
+}
+
+impl PEGCodeGenerator {
+    // ***** Construction *****
+
+    /// Creates a generator for codes with the given `bit_degrees` and `n_checks`.
+    pub fn new(bit_degrees: Vec<usize>, n_checks: usize) -> Self {
+        Self {
+            bit_degrees,
+            n_checks,
+            use_ace: false,
+        }
+    }
+
+    /// Set `self` to break ties between equally distant checks of minimal degree by maximizing
+    /// the approximate cycle extrinsic message degree (ACE) of the created cycles.
+    pub fn with_ace_improvement(mut self) -> Self {
+        self.use_ace = true;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of bits in the generated codes.
+    pub fn get_n_bits(&self) -> usize {
+        self.bit_degrees.len()
+    }
+
+    /// Returns the number of checks in the generated codes.
+    pub fn get_n_checks(&self) -> usize {
+        self.n_checks
+    }
+
+    fn get_bits_by_increasing_degree(&self) -> Vec<usize> {
+        let mut bits: Vec<usize> = (0..self.get_n_bits()).collect();
+        bits.sort_by_key(|bit| self.bit_degrees[*bit]);
+        bits
+    }
+}
+
+impl CodeGenerator for PEGCodeGenerator {
+    fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        let mut graph = TannerGraph::new(self);
+        for bit in self.get_bits_by_increasing_degree() {
+            for _ in 0..self.bit_degrees[bit] {
+                if let Some(check) = graph.get_next_check_for(bit, rng) {
+                    graph.connect(bit, check);
+                }
+            }
+        }
+        graph.into_parity_check_matrix()
+    }
+}
+
+// The Tanner graph that is grown one edge at a time.
+struct TannerGraph<'a> {
+    generator: &'a PEGCodeGenerator,
+    checks_of_bits: Vec<Vec<usize>>,
+    bits_of_checks: Vec<Vec<usize>>,
+}
+
+impl<'a> TannerGraph<'a> {
+    fn new(generator: &'a PEGCodeGenerator) -> Self {
+        Self {
+            generator,
+            checks_of_bits: vec![Vec::new(); generator.get_n_bits()],
+            bits_of_checks: vec![Vec::new(); generator.get_n_checks()],
+        }
+    }
+
+    fn connect(&mut self, bit: usize, check: usize) {
+        self.checks_of_bits[bit].push(check);
+        self.bits_of_checks[check].push(bit);
+    }
+
+    fn into_parity_check_matrix(self) -> ParityCheckMatrix {
+        ParityCheckMatrix::with_n_bits(self.generator.get_n_bits()).with_checks(self.bits_of_checks)
+    }
+
+    fn get_next_check_for<R: Rng>(&self, bit: usize, rng: &mut R) -> Option<usize> {
+        let candidates = self.get_farthest_checks_from(bit);
+        let candidates = self.keep_checks_of_minimal_degree(candidates);
+        let candidates = if self.generator.use_ace {
+            self.keep_checks_of_maximal_ace(candidates)
+        } else {
+            candidates.into_iter().map(|(check, _)| check).collect()
+        };
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[rng.gen_range(0, candidates.len())])
+        }
+    }
+
+    // Returns the checks that are the farthest away from `bit` with the ACE of the path to reach
+    // them. Unreachable checks have an infinite ACE since no cycle is created.
+    fn get_farthest_checks_from(&self, bit: usize) -> Vec<(usize, Option<isize>)> {
+        if self.checks_of_bits[bit].is_empty() {
+            return (0..self.generator.get_n_checks())
+                .map(|check| (check, None))
+                .collect();
+        }
+        let mut tree = SpanningTree::from_root(self, bit);
+        loop {
+            let level = tree.grow();
+            if tree.has_reached_all_checks() {
+                return if tree.depth() > 1 {
+                    level
+                        .into_iter()
+                        .map(|check| (check, Some(tree.check_ace[check])))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+            } else if level.is_empty() {
+                return tree
+                    .get_unreached_checks()
+                    .into_iter()
+                    .map(|check| (check, None))
+                    .collect();
+            }
+        }
+    }
+
+    fn keep_checks_of_minimal_degree(
+        &self,
+        candidates: Vec<(usize, Option<isize>)>,
+    ) -> Vec<(usize, Option<isize>)> {
+        let minimal_degree = candidates
+            .iter()
+            .map(|(check, _)| self.bits_of_checks[*check].len())
+            .min();
+        candidates
+            .into_iter()
+            .filter(|(check, _)| Some(self.bits_of_checks[*check].len()) == minimal_degree)
+            .collect()
+    }
+
+    fn keep_checks_of_maximal_ace(&self, candidates: Vec<(usize, Option<isize>)>) -> Vec<usize> {
+        // `None` is an infinite ACE. Since `None < Some(_)`, we compare the reversed options.
+        let ace_key = |ace: &Option<isize>| ace.map(|value| -value);
+        let best_ace = candidates.iter().map(|(_, ace)| ace_key(ace)).min();
+        candidates
+            .into_iter()
+            .filter(|(_, ace)| Some(ace_key(ace)) == best_ace)
+            .map(|(check, _)| check)
+            .collect()
+    }
+
+    fn get_ace_of_bit(&self, bit: usize) -> isize {
+        self.generator.bit_degrees[bit] as isize - 2
+    }
+}
+
+// A breadth first spanning tree of the Tanner graph rooted at a given bit.
+//
+// Each reached node keeps the minimal ACE of the shortest paths reaching it. The ACE of a path is
+// the sum of the degree minus 2 of its bits.
+struct SpanningTree<'a, 'b> {
+    graph: &'b TannerGraph<'a>,
+    check_depths: Vec<Option<usize>>,
+    bit_depths: Vec<Option<usize>>,
+    n_reached_checks: usize,
+    check_ace: Vec<isize>,
+    bit_ace: Vec<isize>,
+    active_bits: Vec<usize>,
+    depth: usize,
+}
+
+impl<'a, 'b> SpanningTree<'a, 'b> {
+    fn from_root(graph: &'b TannerGraph<'a>, root: usize) -> Self {
+        let n_bits = graph.generator.get_n_bits();
+        let n_checks = graph.generator.get_n_checks();
+        let mut tree = Self {
+            graph,
+            check_depths: vec![None; n_checks],
+            bit_depths: vec![None; n_bits],
+            n_reached_checks: 0,
+            check_ace: vec![isize::MAX; n_checks],
+            bit_ace: vec![isize::MAX; n_bits],
+            active_bits: vec![root],
+            depth: 0,
+        };
+        tree.bit_depths[root] = Some(0);
+        tree.bit_ace[root] = graph.get_ace_of_bit(root);
+        tree
+    }
+
+    // Adds the next level of checks and bits to the tree and returns the newly reached checks.
+    fn grow(&mut self) -> Vec<usize> {
+        let new_checks = self.reach_checks_from_active_bits();
+        self.reach_bits_from(&new_checks);
+        self.depth += 1;
+        new_checks
+    }
+
+    fn reach_checks_from_active_bits(&mut self) -> Vec<usize> {
+        let mut new_checks = Vec::new();
+        for bit in self.active_bits.iter() {
+            for check in self.graph.checks_of_bits[*bit].iter() {
+                if self.check_depths[*check].is_none() {
+                    self.check_depths[*check] = Some(self.depth);
+                    self.n_reached_checks += 1;
+                    new_checks.push(*check);
+                }
+                if self.check_depths[*check] == Some(self.depth) {
+                    self.check_ace[*check] = self.check_ace[*check].min(self.bit_ace[*bit]);
+                }
+            }
+        }
+        new_checks
+    }
+
+    fn reach_bits_from(&mut self, checks: &[usize]) {
+        let mut new_bits = Vec::new();
+        for check in checks.iter() {
+            for bit in self.graph.bits_of_checks[*check].iter() {
+                if self.bit_depths[*bit].is_none() {
+                    self.bit_depths[*bit] = Some(self.depth + 1);
+                    new_bits.push(*bit);
+                }
+                if self.bit_depths[*bit] == Some(self.depth + 1) {
+                    let ace = self.check_ace[*check] + self.graph.get_ace_of_bit(*bit);
+                    self.bit_ace[*bit] = self.bit_ace[*bit].min(ace);
+                }
+            }
+        }
+        self.active_bits = new_bits;
+    }
+
+    fn has_reached_all_checks(&self) -> bool {
+        self.n_reached_checks == self.check_depths.len()
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn get_unreached_checks(&self) -> Vec<usize> {
+        self.check_depths
+            .iter()
+            .enumerate()
+            .filter(|(_, depth)| depth.is_none())
+            .map(|(check, _)| check)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn every_bit_reaches_its_degree() {
+        let bit_degrees = vec![2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 6, 6];
+        let generator = PEGCodeGenerator::new(bit_degrees.clone(), 8);
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(10));
+        assert_eq!(code.get_bit_degrees(), bit_degrees);
+    }
+
+    #[test]
+    fn a_bit_degree_larger_than_the_number_of_checks_is_truncated() {
+        let generator = PEGCodeGenerator::new(vec![5, 1], 3);
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(10));
+        assert_eq!(code.get_bit_degrees(), vec![3, 1]);
+    }
+
+    #[test]
+    fn check_degrees_are_nearly_balanced() {
+        let generator = PEGCodeGenerator::new(vec![3; 40], 20);
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(10));
+        assert!(code
+            .get_check_degrees()
+            .iter()
+            .all(|degree| *degree >= 5 && *degree <= 7));
+    }
+
+    #[test]
+    fn large_enough_codes_have_no_4_cycles() {
+        for seed in 0..5 {
+            let generator = PEGCodeGenerator::new(vec![3; 60], 30).with_ace_improvement();
+            let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(seed));
+            assert_eq!(code.get_bit_degrees(), vec![3; 60]);
+            assert_ne!(code.get_girth(), Some(4));
+        }
+    }
+
+    #[test]
+    fn reproductibility() {
+        let generator =
+            PEGCodeGenerator::new(vec![2, 3, 3, 4, 2, 3, 3, 4], 6).with_ace_improvement();
+        let rng = ChaCha8Rng::seed_from_u64(123);
+        assert_eq!(
+            generator.generate_with_rng(&mut rng.clone()),
+            generator.generate_with_rng(&mut rng.clone())
+        );
+    }
+}
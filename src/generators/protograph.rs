@@ -0,0 +1,491 @@
+//! Protograph-based LDPC codes.
+//!
+//! A protograph is a small Tanner graph where a check can be connected many times to the same
+//! bit. A code is obtained by lifting the protograph. That is, by making `Z` copies of the
+//! protograph and permuting the endpoints of each edge type among the copies.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{CodeGenerator, Protograph, ProtographCodeGenerator};
+//!
+//! // The AR4JA rate 1/2 protograph with its first bit punctured.
+//! let protograph = Protograph::from_multiplicities(vec![
+//!     vec![0, 1, 0, 0, 1],
+//!     vec![2, 0, 1, 1, 0],
+//!     vec![1, 1, 2, 1, 0],
+//! ])
+//! .with_punctured_bits(vec![0]);
+//! assert_eq!(protograph.get_design_rate(), Some(0.5));
+//!
+//! let generator = ProtographCodeGenerator::from_protograph(protograph, 16)
+//!     .with_circulant_lifting(6);
+//! let code = generator.generate();
+//!
+//! assert_eq!(code.get_n_bits(), 80);
+//! assert_eq!(code.get_n_checks(), 48);
+//! assert_eq!(generator.get_punctured_bits(), (0..16).collect::<Vec<_>>());
+//! ```
+
+use super::CodeGenerator;
+use crate::ParityCheckMatrix;
+use rand::seq::{index, SliceRandom};
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// A protograph given by the number of edges between each check and each bit.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Protograph {
+    multiplicities: Vec<Vec<usize>>,
+    n_bits: usize,
+    punctured_bits: Vec<usize>,
+}
+
+impl Protograph {
+    // ***** Construction *****
+
+    /// Creates a protograph where `multiplicities[c][b]` is the number of edges between check `c`
+    /// and bit `b`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rows don't all have the same length.
+    pub fn from_multiplicities(multiplicities: Vec<Vec<usize>>) -> Self {
+        let n_bits = multiplicities.first().map(|row| row.len()).unwrap_or(0);
+        if multiplicities.iter().any(|row| row.len() != n_bits) {
+            panic!("rows of the protograph have different lengths");
+        }
+        Self {
+            multiplicities,
+            n_bits,
+            punctured_bits: Vec::new(),
+        }
+    }
+
+    /// Creates a protograph without multi-edges from a base matrix.
+    pub fn from_base_matrix(base_matrix: &ParityCheckMatrix) -> Self {
+        let multiplicities = base_matrix
+            .checks_iter()
+            .map(|check| {
+                let mut row = vec![0; base_matrix.get_n_bits()];
+                check.iter().for_each(|bit| row[*bit] = 1);
+                row
+            })
+            .collect();
+        Self {
+            multiplicities,
+            n_bits: base_matrix.get_n_bits(),
+            punctured_bits: Vec::new(),
+        }
+    }
+
+    /// Set the given `bits` of `self` as punctured. That is, they are not transmitted over the
+    /// channel.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some bits are out of bound.
+    pub fn with_punctured_bits(mut self, mut bits: Vec<usize>) -> Self {
+        if bits.iter().any(|bit| *bit >= self.n_bits) {
+            panic!("some punctured bits are out of bound");
+        }
+        bits.sort_unstable();
+        bits.dedup();
+        self.punctured_bits = bits;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of bits in `self`.
+    pub fn get_n_bits(&self) -> usize {
+        self.n_bits
+    }
+
+    /// Returns the number of checks in `self`.
+    pub fn get_n_checks(&self) -> usize {
+        self.multiplicities.len()
+    }
+
+    /// Returns the number of edges between `check` and `bit`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `check` or `bit` is out of bound.
+    pub fn get_multiplicity(&self, check: usize, bit: usize) -> usize {
+        self.multiplicities[check][bit]
+    }
+
+    /// Returns the punctured bits of `self`.
+    pub fn get_punctured_bits(&self) -> &[usize] {
+        &self.punctured_bits
+    }
+
+    /// Returns the number of edges of each bit including multi-edges.
+    pub fn get_bit_degrees(&self) -> Vec<usize> {
+        (0..self.n_bits)
+            .map(|bit| self.multiplicities.iter().map(|row| row[bit]).sum())
+            .collect()
+    }
+
+    /// Returns the number of edges of each check including multi-edges.
+    pub fn get_check_degrees(&self) -> Vec<usize> {
+        self.multiplicities
+            .iter()
+            .map(|row| row.iter().sum())
+            .collect()
+    }
+
+    /// Returns the design rate of the codes lifted from `self`.
+    ///
+    /// This is the number of information bits over the number of transmitted bits assuming that
+    /// all checks are independent. Returns `None` if every bit is punctured.
+    pub fn get_design_rate(&self) -> Option<f64> {
+        let n_transmitted_bits = self.n_bits - self.punctured_bits.len();
+        if n_transmitted_bits == 0 {
+            return None;
+        }
+        Some((self.n_bits as f64 - self.get_n_checks() as f64) / n_transmitted_bits as f64)
+    }
+
+    /// Returns the largest multiplicity in `self` or 0 if there are no edges.
+    pub fn get_max_multiplicity(&self) -> usize {
+        self.multiplicities
+            .iter()
+            .flat_map(|row| row.iter())
+            .max()
+            .cloned()
+            .unwrap_or(0)
+    }
+}
+
+/// A generator for codes lifted from a protograph.
+///
+/// By default, each edge type is lifted with a random permutation. It is also possible to lift
+/// with circulant permutations where the shifts are picked one at a time to avoid cycles smaller
+/// than a minimal girth. Both methods support multi-edges by using disjoint permutations for each
+/// copy of an edge.
+///
+/// The `lifting_size` copies of a protograph bit `b` are the bits `b * lifting_size` to
+/// `(b + 1) * lifting_size - 1` of the generated codes and similarly for checks.
+pub struct ProtographCodeGenerator {
+    protograph: Protograph,
+    lifting_size: usize,
+    lifting: Lifting,
+}
+
+enum Lifting {
+    RandomPermutations,
+    Circulants { minimal_girth: usize },
+}
+
+impl ProtographCodeGenerator {
+    // ***** Construction *****
+
+    /// Creates a generator that lifts `protograph` with the given `lifting_size`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if some multiplicity is larger than `lifting_size`.
+    pub fn from_protograph(protograph: Protograph, lifting_size: usize) -> Self {
+        if protograph.get_max_multiplicity() > lifting_size {
+            panic!("some multiplicities are larger than the lifting size");
+        }
+        Self {
+            protograph,
+            lifting_size,
+            lifting: Lifting::RandomPermutations,
+        }
+    }
+
+    /// Set `self` to lift each edge type with a random permutation.
+    ///
+    /// This is the default behavior.
+    pub fn with_random_permutation_lifting(mut self) -> Self {
+        self.lifting = Lifting::RandomPermutations;
+        self
+    }
+
+    /// Set `self` to lift each edge type with a circulant permutation avoiding cycles smaller
+    /// than `minimal_girth` when possible.
+    pub fn with_circulant_lifting(mut self, minimal_girth: usize) -> Self {
+        self.lifting = Lifting::Circulants { minimal_girth };
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the protograph of `self`.
+    pub fn get_protograph(&self) -> &Protograph {
+        &self.protograph
+    }
+
+    /// Returns the number of bits in the generated codes including punctured bits.
+    pub fn get_n_bits(&self) -> usize {
+        self.protograph.get_n_bits() * self.lifting_size
+    }
+
+    /// Returns the number of checks in the generated codes.
+    pub fn get_n_checks(&self) -> usize {
+        self.protograph.get_n_checks() * self.lifting_size
+    }
+
+    /// Returns the bits of the generated codes that are copies of punctured protograph bits.
+    pub fn get_punctured_bits(&self) -> Vec<usize> {
+        self.protograph
+            .get_punctured_bits()
+            .iter()
+            .flat_map(|bit| bit * self.lifting_size..(bit + 1) * self.lifting_size)
+            .collect()
+    }
+
+    fn get_edge_types(&self) -> Vec<(usize, usize, usize)> {
+        (0..self.protograph.get_n_checks())
+            .flat_map(|check| {
+                (0..self.protograph.get_n_bits())
+                    .map(move |bit| (check, bit))
+                    .filter(|(check, bit)| self.protograph.get_multiplicity(*check, *bit) > 0)
+                    .map(move |(check, bit)| {
+                        (check, bit, self.protograph.get_multiplicity(check, bit))
+                    })
+            })
+            .collect()
+    }
+
+    fn lift_with_random_permutations<R: Rng>(&self, rng: &mut R) -> LiftedGraph {
+        let mut graph = LiftedGraph::new(self);
+        for (check, bit, multiplicity) in self.get_edge_types() {
+            let mut permutation: Vec<usize> = (0..self.lifting_size).collect();
+            permutation.shuffle(rng);
+            for offset in index::sample(rng, self.lifting_size, multiplicity).into_iter() {
+                for copy in 0..self.lifting_size {
+                    let bit_copy = permutation[(copy + offset) % self.lifting_size];
+                    graph.connect(check, copy, bit, bit_copy);
+                }
+            }
+        }
+        graph
+    }
+
+    fn lift_with_circulants<R: Rng>(&self, minimal_girth: usize, rng: &mut R) -> LiftedGraph {
+        let mut graph = LiftedGraph::new(self);
+        for (check, bit, multiplicity) in self.get_edge_types() {
+            let mut used_shifts = Vec::with_capacity(multiplicity);
+            for _ in 0..multiplicity {
+                let shift =
+                    self.get_random_shift(&graph, check, bit, &used_shifts, minimal_girth, rng);
+                graph.connect_circulant(check, bit, shift);
+                used_shifts.push(shift);
+            }
+        }
+        graph
+    }
+
+    // By symmetry of the circulants, it is enough to look at the cycles going through the first
+    // copy of the check. If no shift avoids the short cycles, any unused shift is picked.
+    fn get_random_shift<R: Rng>(
+        &self,
+        graph: &LiftedGraph,
+        check: usize,
+        bit: usize,
+        used_shifts: &[usize],
+        minimal_girth: usize,
+        rng: &mut R,
+    ) -> usize {
+        let unused_shifts: Vec<usize> = (0..self.lifting_size)
+            .filter(|shift| !used_shifts.contains(shift))
+            .collect();
+        let valid_shifts: Vec<usize> = unused_shifts
+            .iter()
+            .filter(|shift| {
+                !graph.has_path_shorter_than(
+                    check * self.lifting_size,
+                    bit * self.lifting_size + *shift,
+                    minimal_girth.saturating_sub(1),
+                )
+            })
+            .cloned()
+            .collect();
+        if valid_shifts.is_empty() {
+            *unused_shifts.choose(rng).unwrap()
+        } else {
+            *valid_shifts.choose(rng).unwrap()
+        }
+    }
+}
+
+impl CodeGenerator for ProtographCodeGenerator {
+    fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        match self.lifting {
+            Lifting::RandomPermutations => self.lift_with_random_permutations(rng),
+            Lifting::Circulants { minimal_girth } => self.lift_with_circulants(minimal_girth, rng),
+        }
+        .into_parity_check_matrix()
+    }
+}
+
+// The Tanner graph of a lifted protograph.
+struct LiftedGraph {
+    lifting_size: usize,
+    n_bits: usize,
+    bits_of_checks: Vec<Vec<usize>>,
+    checks_of_bits: Vec<Vec<usize>>,
+}
+
+impl LiftedGraph {
+    fn new(generator: &ProtographCodeGenerator) -> Self {
+        Self {
+            lifting_size: generator.lifting_size,
+            n_bits: generator.get_n_bits(),
+            bits_of_checks: vec![Vec::new(); generator.get_n_checks()],
+            checks_of_bits: vec![Vec::new(); generator.get_n_bits()],
+        }
+    }
+
+    fn connect(&mut self, check: usize, check_copy: usize, bit: usize, bit_copy: usize) {
+        let check = check * self.lifting_size + check_copy;
+        let bit = bit * self.lifting_size + bit_copy;
+        self.bits_of_checks[check].push(bit);
+        self.checks_of_bits[bit].push(check);
+    }
+
+    fn connect_circulant(&mut self, check: usize, bit: usize, shift: usize) {
+        for copy in 0..self.lifting_size {
+            self.connect(check, copy, bit, (copy + shift) % self.lifting_size);
+        }
+    }
+
+    // Checks if there is a path of length smaller than `length` between `check` and `bit`.
+    fn has_path_shorter_than(&self, check: usize, bit: usize, length: usize) -> bool {
+        let mut reached_checks = vec![false; self.bits_of_checks.len()];
+        let mut reached_bits = vec![false; self.checks_of_bits.len()];
+        let mut queue = VecDeque::new();
+        reached_checks[check] = true;
+        queue.push_back((Node::Check(check), 0));
+        while let Some((node, distance)) = queue.pop_front() {
+            if distance + 1 >= length {
+                continue;
+            }
+            match node {
+                Node::Check(check) => {
+                    for neighbor in self.bits_of_checks[check].iter() {
+                        if *neighbor == bit {
+                            return true;
+                        }
+                        if !reached_bits[*neighbor] {
+                            reached_bits[*neighbor] = true;
+                            queue.push_back((Node::Bit(*neighbor), distance + 1));
+                        }
+                    }
+                }
+                Node::Bit(bit) => {
+                    for neighbor in self.checks_of_bits[bit].iter() {
+                        if !reached_checks[*neighbor] {
+                            reached_checks[*neighbor] = true;
+                            queue.push_back((Node::Check(*neighbor), distance + 1));
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    fn into_parity_check_matrix(self) -> ParityCheckMatrix {
+        ParityCheckMatrix::with_n_bits(self.n_bits).with_checks(self.bits_of_checks)
+    }
+}
+
+enum Node {
+    Check(usize),
+    Bit(usize),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn get_protograph() -> Protograph {
+        Protograph::from_multiplicities(vec![vec![1, 2, 1, 0], vec![1, 1, 1, 2]])
+    }
+
+    #[test]
+    fn degrees_of_lifted_codes_match_the_protograph() {
+        let protograph = get_protograph();
+        assert_eq!(protograph.get_bit_degrees(), vec![2, 3, 2, 2]);
+        assert_eq!(protograph.get_check_degrees(), vec![4, 5]);
+
+        for generator in &[
+            ProtographCodeGenerator::from_protograph(protograph.clone(), 10),
+            ProtographCodeGenerator::from_protograph(protograph, 10).with_circulant_lifting(6),
+        ] {
+            let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(10));
+            let bit_degrees: Vec<usize> = vec![2, 3, 2, 2]
+                .into_iter()
+                .flat_map(|degree| vec![degree; 10])
+                .collect();
+            let check_degrees: Vec<usize> = vec![4, 5]
+                .into_iter()
+                .flat_map(|degree| vec![degree; 10])
+                .collect();
+            assert_eq!(code.get_bit_degrees(), bit_degrees);
+            assert_eq!(code.get_check_degrees(), check_degrees);
+        }
+    }
+
+    #[test]
+    fn circulant_lifting_avoids_4_cycles() {
+        let generator = ProtographCodeGenerator::from_protograph(get_protograph(), 13)
+            .with_circulant_lifting(6);
+        for seed in 0..5 {
+            let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(seed));
+            assert_ne!(code.get_girth(), Some(4));
+        }
+    }
+
+    #[test]
+    fn lifting_a_base_matrix_without_multi_edges() {
+        let base_matrix =
+            ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+        let protograph = Protograph::from_base_matrix(&base_matrix);
+        assert_eq!(protograph.get_multiplicity(0, 0), 1);
+        assert_eq!(protograph.get_multiplicity(0, 2), 0);
+
+        let code = ProtographCodeGenerator::from_protograph(protograph, 1).generate();
+        assert_eq!(code, base_matrix);
+    }
+
+    #[test]
+    fn punctured_bits_are_lifted() {
+        let protograph = get_protograph().with_punctured_bits(vec![3, 1, 3]);
+        assert_eq!(protograph.get_punctured_bits(), &[1, 3]);
+        assert_eq!(protograph.get_design_rate(), Some(1.0));
+        assert_eq!(
+            get_protograph()
+                .with_punctured_bits(vec![0, 1, 2, 3])
+                .get_design_rate(),
+            None
+        );
+
+        let generator = ProtographCodeGenerator::from_protograph(protograph, 2);
+        assert_eq!(generator.get_punctured_bits(), vec![2, 3, 6, 7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_if_a_multiplicity_is_larger_than_the_lifting_size() {
+        ProtographCodeGenerator::from_protograph(get_protograph(), 1);
+    }
+
+    #[test]
+    fn reproductibility() {
+        let generator = ProtographCodeGenerator::from_protograph(get_protograph(), 8);
+        let rng = ChaCha8Rng::seed_from_u64(123);
+        assert_eq!(
+            generator.generate_with_rng(&mut rng.clone()),
+            generator.generate_with_rng(&mut rng.clone())
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Quasi-cyclic LDPC codes.
+//!
+//! A quasi-cyclic code is described by an exponent matrix and a lifting size `Z`. Each entry of
+//! the exponent matrix is either `-1`, for a `Z x Z` zero block, or a shift `s`, for the `Z x Z`
+//! identity matrix cyclically shifted by `s`. This is how the 5G NR and WiFi LDPC codes are
+//! specified.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::ExponentMatrix;
+//!
+//! let exponents = ExponentMatrix::from_rows(vec![
+//!     vec![0, 1, -1],
+//!     vec![-1, 0, 2],
+//! ]);
+//! let code = exponents.expand_with_lifting_size(3);
+//!
+//! assert_eq!(code.get_n_bits(), 9);
+//! assert_eq!(code.get_n_checks(), 6);
+//! assert_eq!(code.get_check(0).unwrap().as_ref(), &[0, 4]);
+//! assert_eq!(code.get_check(3).unwrap().as_ref(), &[3, 8]);
+//! ```
+
+use super::CodeGenerator;
+use crate::ParityCheckMatrix;
+use rand::{thread_rng, Rng};
+
+/// The exponent matrix of a quasi-cyclic code.
+///
+/// Entries are stored as `Some(shift)` for a circulant block and `None` for a zero block.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExponentMatrix {
+    shifts: Vec<Vec<Option<usize>>>,
+    n_block_columns: usize,
+}
+
+impl ExponentMatrix {
+    // ***** Construction *****
+
+    /// Creates an exponent matrix from its `rows` where `-1` represents a zero block and any
+    /// non negative value represents a circulant shift.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rows don't all have the same length or if an entry is smaller than `-1`.
+    pub fn from_rows(rows: Vec<Vec<isize>>) -> Self {
+        let shifts = rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|entry| match entry {
+                        -1 => None,
+                        shift if shift >= 0 => Some(shift as usize),
+                        _ => panic!("exponents must be -1 or non negative"),
+                    })
+                    .collect()
+            })
+            .collect();
+        Self::from_shifts(shifts)
+    }
+
+    /// Creates an exponent matrix from `shifts` where `None` represents a zero block.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the rows don't all have the same length.
+    pub fn from_shifts(shifts: Vec<Vec<Option<usize>>>) -> Self {
+        let n_block_columns = shifts.first().map(|row| row.len()).unwrap_or(0);
+        if shifts.iter().any(|row| row.len() != n_block_columns) {
+            panic!("rows of the exponent matrix have different lengths");
+        }
+        Self {
+            shifts,
+            n_block_columns,
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of block rows of `self`.
+    pub fn get_n_block_rows(&self) -> usize {
+        self.shifts.len()
+    }
+
+    /// Returns the number of block columns of `self`.
+    pub fn get_n_block_columns(&self) -> usize {
+        self.n_block_columns
+    }
+
+    /// Returns the shift of the block at `row` and `column` or `None` if it is a zero block.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `row` or `column` is out of bound.
+    pub fn get_shift(&self, row: usize, column: usize) -> Option<usize> {
+        self.shifts[row][column]
+    }
+
+    /// Returns the base matrix of `self`. That is, the matrix with a `1` for every circulant
+    /// block and a `0` for every zero block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::{ExponentMatrix, ParityCheckMatrix};
+    ///
+    /// let exponents = ExponentMatrix::from_rows(vec![vec![0, 1, -1], vec![-1, 0, 2]]);
+    /// let expected = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+    ///
+    /// assert_eq!(exponents.get_base_matrix(), expected);
+    /// ```
+    pub fn get_base_matrix(&self) -> ParityCheckMatrix {
+        let checks = self
+            .shifts
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, shift)| shift.is_some())
+                    .map(|(column, _)| column)
+                    .collect()
+            })
+            .collect();
+        ParityCheckMatrix::with_n_bits(self.n_block_columns).with_checks(checks)
+    }
+
+    // ***** Expansion *****
+
+    /// Expands `self` into a parity check matrix where every entry is replaced by a
+    /// `lifting_size x lifting_size` block.
+    ///
+    /// Shifts are taken modulo `lifting_size`. This is the convention used by 5G NR where a
+    /// single exponent matrix is shared by many lifting sizes.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `lifting_size` is 0.
+    pub fn expand_with_lifting_size(&self, lifting_size: usize) -> ParityCheckMatrix {
+        if lifting_size == 0 {
+            panic!("lifting size must be positive");
+        }
+        let checks = self
+            .shifts
+            .iter()
+            .flat_map(|row| {
+                Self::expand_row(row, lifting_size)
+                    .checks_iter()
+                    .map(|check| check.to_vec())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        ParityCheckMatrix::with_n_bits(self.n_block_columns * lifting_size).with_checks(checks)
+    }
+
+    fn expand_row(row: &[Option<usize>], lifting_size: usize) -> ParityCheckMatrix {
+        row.iter()
+            .map(|shift| Self::expand_entry(*shift, lifting_size))
+            .fold(ParityCheckMatrix::new(), |block_row, block| {
+                block_row.get_horizontal_concat_with(&block)
+            })
+    }
+
+    fn expand_entry(shift: Option<usize>, lifting_size: usize) -> ParityCheckMatrix {
+        match shift {
+            Some(shift) => {
+                ParityCheckMatrix::circulant_right(&[shift % lifting_size], lifting_size)
+            }
+            None => ParityCheckMatrix::with_n_bits(lifting_size)
+                .with_checks(vec![Vec::new(); lifting_size]),
+        }
+    }
+
+    // ***** Cycles *****
+
+    /// Checks if the expansion of `self` with the given `lifting_size` has some 4-cycles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ExponentMatrix;
+    ///
+    /// // 0 - 1 + 1 - 0 = 0, so there are 4-cycles.
+    /// let exponents = ExponentMatrix::from_rows(vec![vec![0, 1], vec![0, 1]]);
+    /// assert!(exponents.has_4_cycles(5));
+    ///
+    /// // 0 - 1 + 2 - 0 = 1, so there are no 4-cycles.
+    /// let exponents = ExponentMatrix::from_rows(vec![vec![0, 1], vec![0, 2]]);
+    /// assert!(!exponents.has_4_cycles(5));
+    /// ```
+    pub fn has_4_cycles(&self, lifting_size: usize) -> bool {
+        self.has_cycles_of_length_matching(lifting_size, |detector, row, column| {
+            detector.closes_a_4_cycle(row, column)
+        })
+    }
+
+    /// Checks if the expansion of `self` with the given `lifting_size` has some 6-cycles.
+    pub fn has_6_cycles(&self, lifting_size: usize) -> bool {
+        self.has_cycles_of_length_matching(lifting_size, |detector, row, column| {
+            detector.closes_a_6_cycle(row, column)
+        })
+    }
+
+    /// Checks if the expansion of `self` with the given `lifting_size` has a cycle smaller than
+    /// `minimal_girth`. Only 4-cycles and 6-cycles are considered.
+    pub fn has_cycles_smaller_than(&self, minimal_girth: usize, lifting_size: usize) -> bool {
+        (minimal_girth > 4 && self.has_4_cycles(lifting_size))
+            || (minimal_girth > 6 && self.has_6_cycles(lifting_size))
+    }
+
+    fn has_cycles_of_length_matching<F>(&self, lifting_size: usize, closes_a_cycle: F) -> bool
+    where
+        F: Fn(&CycleDetector, usize, usize) -> bool,
+    {
+        let detector = CycleDetector {
+            shifts: &self.shifts,
+            lifting_size,
+        };
+        (0..self.get_n_block_rows()).any(|row| {
+            (0..self.n_block_columns).any(|column| {
+                self.shifts[row][column].is_some() && closes_a_cycle(&detector, row, column)
+            })
+        })
+    }
+}
+
+// Checks the shift-sum conditions of the cycles going throught a given block.
+//
+// A cycle in the base matrix going throught blocks with shifts s_1, s_2, ..., s_2k lifts to
+// cycles of the same length if and only if s_1 - s_2 + s_3 - ... - s_2k = 0 modulo the lifting
+// size. Zero blocks are skipped as well as blocks that are not set yet.
+struct CycleDetector<'a> {
+    shifts: &'a [Vec<Option<usize>>],
+    lifting_size: usize,
+}
+
+impl<'a> CycleDetector<'a> {
+    fn closes_a_4_cycle(&self, row: usize, column: usize) -> bool {
+        let n_rows = self.shifts.len();
+        let n_columns = self.shifts[row].len();
+        (0..n_rows).filter(|r| *r != row).any(|other_row| {
+            (0..n_columns).filter(|c| *c != column).any(|other_column| {
+                self.alternating_sum(&[
+                    (row, column),
+                    (other_row, column),
+                    (other_row, other_column),
+                    (row, other_column),
+                ]) == Some(0)
+            })
+        })
+    }
+
+    fn closes_a_6_cycle(&self, row: usize, column: usize) -> bool {
+        let n_rows = self.shifts.len();
+        let n_columns = self.shifts[row].len();
+        (0..n_rows).filter(|r| *r != row).any(|second_row| {
+            (0..n_rows)
+                .filter(|r| *r != row && *r != second_row)
+                .any(|third_row| {
+                    (0..n_columns)
+                        .filter(|c| *c != column)
+                        .any(|second_column| {
+                            (0..n_columns)
+                                .filter(|c| *c != column && *c != second_column)
+                                .any(|third_column| {
+                                    self.alternating_sum(&[
+                                        (row, column),
+                                        (second_row, column),
+                                        (second_row, second_column),
+                                        (third_row, second_column),
+                                        (third_row, third_column),
+                                        (row, third_column),
+                                    ]) == Some(0)
+                                })
+                        })
+                })
+        })
+    }
+
+    // Returns the alternating sum of the shifts along the given closed path or `None` if one of
+    // the blocks is a zero block.
+    fn alternating_sum(&self, path: &[(usize, usize)]) -> Option<usize> {
+        path.iter()
+            .enumerate()
+            .try_fold(0, |sum, (index, (row, column))| {
+                self.shifts[*row][*column].map(|shift| {
+                    let shift = shift % self.lifting_size;
+                    if index % 2 == 0 {
+                        (sum + shift) % self.lifting_size
+                    } else {
+                        (sum + self.lifting_size - shift) % self.lifting_size
+                    }
+                })
+            })
+    }
+}
+
+/// A generator for quasi-cyclic codes with random exponents.
+///
+/// The circulant blocks are given by a base matrix and the shifts are picked one at a time, at
+/// random among the shifts that don't create a cycle smaller than the minimal girth. If it fails
+/// to set every shift, the search restarts up to a given number of attempts.
+///
+/// # Example
+///
+/// ```
+/// use believer::{CodeGenerator, ParityCheckMatrix, QuasiCyclicCodeGenerator};
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+///
+/// // A (3, 4)-regular base matrix.
+/// let base_matrix = ParityCheckMatrix::with_n_bits(4)
+///     .with_checks(vec![vec![0, 1, 2, 3], vec![0, 1, 2, 3], vec![0, 1, 2, 3]]);
+///
+/// let generator = QuasiCyclicCodeGenerator::from_base_matrix(base_matrix, 13)
+///     .with_minimal_girth(8);
+///
+/// let mut rng = ChaCha8Rng::seed_from_u64(123);
+/// let exponents = generator.generate_exponent_matrix_with_rng(&mut rng).unwrap();
+/// assert!(!exponents.has_cycles_smaller_than(8, 13));
+///
+/// let code = generator.generate_with_rng(&mut rng);
+/// assert_eq!(code.get_n_bits(), 52);
+/// assert_eq!(code.get_n_checks(), 39);
+/// ```
+pub struct QuasiCyclicCodeGenerator {
+    base_matrix: ParityCheckMatrix,
+    lifting_size: usize,
+    minimal_girth: usize,
+    n_attempts: usize,
+}
+
+impl QuasiCyclicCodeGenerator {
+    // ***** Construction *****
+
+    /// Creates a generator that will lift every non zero entry of `base_matrix` to a
+    /// `lifting_size x lifting_size` circulant block.
+    ///
+    /// By default, there is no restriction on the minimal girth and 100 attempts are made.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `lifting_size` is 0.
+    pub fn from_base_matrix(base_matrix: ParityCheckMatrix, lifting_size: usize) -> Self {
+        if lifting_size == 0 {
+            panic!("lifting size must be positive");
+        }
+        Self {
+            base_matrix,
+            lifting_size,
+            minimal_girth: 0,
+            n_attempts: 100,
+        }
+    }
+
+    /// Set the minimal girth of the generated codes.
+    ///
+    /// Only 4-cycles and 6-cycles are avoided. That is, any value above 8 is equivalent to 8.
+    pub fn with_minimal_girth(mut self, minimal_girth: usize) -> Self {
+        self.minimal_girth = minimal_girth;
+        self
+    }
+
+    /// Set the number of times the random search is restarted before giving up.
+    pub fn with_n_attempts(mut self, n_attempts: usize) -> Self {
+        self.n_attempts = n_attempts;
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of bits in the generated codes.
+    pub fn get_n_bits(&self) -> usize {
+        self.base_matrix.get_n_bits() * self.lifting_size
+    }
+
+    /// Returns the number of checks in the generated codes.
+    pub fn get_n_checks(&self) -> usize {
+        self.base_matrix.get_n_checks() * self.lifting_size
+    }
+
+    // ***** Generation *****
+
+    /// Returns `Some` random exponent matrix without cycles smaller than the minimal girth or
+    /// `None` if none was found in the given number of attempts.
+    pub fn generate_exponent_matrix_with_rng<R: Rng>(&self, rng: &mut R) -> Option<ExponentMatrix> {
+        (0..self.n_attempts).find_map(|_| self.try_to_generate_exponent_matrix_with_rng(rng))
+    }
+
+    fn try_to_generate_exponent_matrix_with_rng<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> Option<ExponentMatrix> {
+        let mut shifts = self.get_empty_shifts();
+        for (row, check) in self.base_matrix.checks_iter().enumerate() {
+            for column in check.iter() {
+                let candidates = self.get_valid_shifts_for(&mut shifts, row, *column);
+                if candidates.is_empty() {
+                    return None;
+                }
+                shifts[row][*column] = Some(candidates[rng.gen_range(0, candidates.len())]);
+            }
+        }
+        Some(ExponentMatrix::from_shifts(shifts))
+    }
+
+    fn get_empty_shifts(&self) -> Vec<Vec<Option<usize>>> {
+        vec![vec![None; self.base_matrix.get_n_bits()]; self.base_matrix.get_n_checks()]
+    }
+
+    fn get_valid_shifts_for(
+        &self,
+        shifts: &mut [Vec<Option<usize>>],
+        row: usize,
+        column: usize,
+    ) -> Vec<usize> {
+        let valid_shifts = (0..self.lifting_size)
+            .filter(|shift| {
+                shifts[row][column] = Some(*shift);
+                !self.closes_a_short_cycle(shifts, row, column)
+            })
+            .collect();
+        shifts[row][column] = None;
+        valid_shifts
+    }
+
+    fn closes_a_short_cycle(
+        &self,
+        shifts: &[Vec<Option<usize>>],
+        row: usize,
+        column: usize,
+    ) -> bool {
+        let detector = CycleDetector {
+            shifts,
+            lifting_size: self.lifting_size,
+        };
+        (self.minimal_girth > 4 && detector.closes_a_4_cycle(row, column))
+            || (self.minimal_girth > 6 && detector.closes_a_6_cycle(row, column))
+    }
+
+    /// Returns `Some` random code without cycles smaller than the minimal girth or `None` if
+    /// no exponent matrix was found in the given number of attempts.
+    pub fn try_generate_with_rng<R: Rng>(&self, rng: &mut R) -> Option<ParityCheckMatrix> {
+        self.generate_exponent_matrix_with_rng(rng)
+            .map(|exponents| exponents.expand_with_lifting_size(self.lifting_size))
+    }
+
+    /// Returns `Some` random code without cycles smaller than the minimal girth using the
+    /// thread rng or `None` if no exponent matrix was found in the given number of attempts.
+    pub fn try_generate(&self) -> Option<ParityCheckMatrix> {
+        self.try_generate_with_rng(&mut thread_rng())
+    }
+}
+
+impl CodeGenerator for QuasiCyclicCodeGenerator {
+    /// Returns a random quasi-cyclic code.
+    ///
+    /// # Panic
+    ///
+    /// Panics if no exponent matrix without cycles smaller than the minimal girth is found in
+    /// the given number of attempts. Use `try_generate_with_rng` to handle this case.
+    fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        self.try_generate_with_rng(rng)
+            .expect("no exponent matrix satisfies the minimal girth")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn expansion_of_identity_blocks() {
+        let exponents = ExponentMatrix::from_rows(vec![vec![0, -1], vec![-1, 0]]);
+        let code = exponents.expand_with_lifting_size(3);
+        assert_eq!(code, ParityCheckMatrix::identity_with_n_bits(6));
+    }
+
+    #[test]
+    fn expansion_keeps_the_checks_of_zero_block_rows() {
+        let exponents = ExponentMatrix::from_rows(vec![vec![-1, -1], vec![1, 2]]);
+        let code = exponents.expand_with_lifting_size(3);
+        let expected = ParityCheckMatrix::with_n_bits(6).with_checks(vec![
+            vec![],
+            vec![],
+            vec![],
+            vec![1, 5],
+            vec![2, 3],
+            vec![0, 4],
+        ]);
+        assert_eq!(code, expected);
+    }
+
+    #[test]
+    fn shifts_are_taken_modulo_the_lifting_size() {
+        let exponents = ExponentMatrix::from_rows(vec![vec![7, 0]]);
+        let reduced = ExponentMatrix::from_rows(vec![vec![1, 0]]);
+        assert_eq!(
+            exponents.expand_with_lifting_size(3),
+            reduced.expand_with_lifting_size(3)
+        );
+    }
+
+    #[test]
+    fn detects_6_cycles() {
+        // 0 - 0 + 0 - 0 + 0 - 0 = 0.
+        let exponents =
+            ExponentMatrix::from_rows(vec![vec![0, -1, 0], vec![0, 0, -1], vec![-1, 0, 0]]);
+        assert!(!exponents.has_4_cycles(5));
+        assert!(exponents.has_6_cycles(5));
+
+        let exponents =
+            ExponentMatrix::from_rows(vec![vec![0, -1, 0], vec![0, 0, -1], vec![-1, 0, 1]]);
+        assert!(!exponents.has_6_cycles(5));
+    }
+
+    #[test]
+    fn generated_codes_are_regular_and_without_short_cycles() {
+        let base_matrix = ParityCheckMatrix::with_n_bits(6).with_checks(vec![(0..6).collect(); 3]);
+        let generator =
+            QuasiCyclicCodeGenerator::from_base_matrix(base_matrix, 31).with_minimal_girth(8);
+        let exponents = generator
+            .generate_exponent_matrix_with_rng(&mut ChaCha8Rng::seed_from_u64(10))
+            .unwrap();
+        assert!(!exponents.has_cycles_smaller_than(8, 31));
+
+        let code = exponents.expand_with_lifting_size(31);
+        assert!(code.get_bit_degrees().iter().all(|degree| *degree == 3));
+        assert!(code.get_check_degrees().iter().all(|degree| *degree == 6));
+    }
+
+    #[test]
+    fn impossible_girth_is_reported() {
+        // A 2 x 2 all-ones base matrix always has 4-cycles with a lifting size of 1.
+        let base_matrix = ParityCheckMatrix::with_n_bits(2).with_checks(vec![vec![0, 1]; 2]);
+        let generator =
+            QuasiCyclicCodeGenerator::from_base_matrix(base_matrix, 1).with_minimal_girth(6);
+        assert!(generator
+            .try_generate_with_rng(&mut ChaCha8Rng::seed_from_u64(1))
+            .is_none());
+    }
+
+    #[test]
+    fn reproductibility() {
+        let base_matrix = ParityCheckMatrix::with_n_bits(4).with_checks(vec![(0..4).collect(); 2]);
+        let generator =
+            QuasiCyclicCodeGenerator::from_base_matrix(base_matrix, 7).with_minimal_girth(6);
+        let rng = ChaCha8Rng::seed_from_u64(123);
+        assert_eq!(
+            generator.generate_with_rng(&mut rng.clone()),
+            generator.generate_with_rng(&mut rng.clone())
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Spatially coupled LDPC codes.
+//!
+//! A terminated spatially coupled code is a chain of `coupling_length` positions, each containing
+//! the same number of bits. The bits at a given position are connected to the checks of the next
+//! `coupling_width` positions. There are `coupling_length + coupling_width - 1` check positions
+//! and the checks at both ends of the chain have smaller degrees.
+//!
+//! Bits and checks are indexed position by position. That is, the bits at position `p` are
+//! `p * n_bits_per_position` to `(p + 1) * n_bits_per_position - 1` and similarly for checks.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{CodeGenerator, SpatiallyCoupledCodeGenerator};
+//!
+//! // The (3, 6, 10, 3) ensemble with 12 bits per position.
+//! let generator = SpatiallyCoupledCodeGenerator::new(3, 6, 10, 3).with_random_component(12);
+//! let code = generator.generate();
+//!
+//! assert_eq!(code.get_n_bits(), 120);
+//! assert_eq!(code.get_n_checks(), 72);
+//!
+//! // Decode with windows of 4 positions.
+//! let sliding_window = generator.get_sliding_window(4);
+//! let window = sliding_window.get_window(2);
+//! assert_eq!(window.get_checks(), 12..36);
+//! assert_eq!(window.get_target_bits(), 24..36);
+//! ```
+
+use super::CodeGenerator;
+use super::{Protograph, ProtographCodeGenerator};
+use crate::ParityCheckMatrix;
+use num::integer::gcd;
+use num::Integer;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::ops::Range;
+
+/// A generator for terminated spatially coupled (dv, dc, L, w) LDPC codes.
+///
+/// The component code at each position can either be random or lifted from a protograph. By
+/// default, the component is random with `check_degree` bits per position.
+pub struct SpatiallyCoupledCodeGenerator {
+    bit_degree: usize,
+    check_degree: usize,
+    coupling_length: usize,
+    coupling_width: usize,
+    component: Component,
+}
+
+enum Component {
+    Random {
+        n_bits_per_position: usize,
+    },
+    Protograph {
+        lifting_size: usize,
+        minimal_girth: usize,
+    },
+}
+
+impl SpatiallyCoupledCodeGenerator {
+    // ***** Construction *****
+
+    /// Creates a generator for codes where each bit has degree `bit_degree` and the checks away
+    /// from the boundaries have degree `check_degree`.
+    ///
+    /// There are `coupling_length` bit positions and each bit is connected to checks in the next
+    /// `coupling_width` positions.
+    ///
+    /// # Panic
+    ///
+    /// Panics if any parameter is 0 or if `coupling_width` is larger than `check_degree`. In the
+    /// latter case, some checks at the boundaries would be empty.
+    pub fn new(
+        bit_degree: usize,
+        check_degree: usize,
+        coupling_length: usize,
+        coupling_width: usize,
+    ) -> Self {
+        if bit_degree == 0 || check_degree == 0 || coupling_length == 0 || coupling_width == 0 {
+            panic!("spatially coupled code parameters must be positive");
+        }
+        if coupling_width > check_degree {
+            panic!("the coupling width is larger than the check degree");
+        }
+        Self {
+            bit_degree,
+            check_degree,
+            coupling_length,
+            coupling_width,
+            component: Component::Random {
+                n_bits_per_position: check_degree,
+            },
+        }
+    }
+
+    /// Set the component of `self` to the random ensemble with `n_bits_per_position` bits at each
+    /// position.
+    ///
+    /// The sockets of the bits at each position are shuffled and spread evenly over the next
+    /// `coupling_width` check positions. Parallel edges are removed, so a few bits may have a
+    /// degree smaller than `bit_degree`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_bits_per_position * bit_degree` is not a multiple of `check_degree`.
+    pub fn with_random_component(mut self, n_bits_per_position: usize) -> Self {
+        if !Integer::is_multiple_of(&(n_bits_per_position * self.bit_degree), &self.check_degree) {
+            panic!("the number of checks per position is not an integer");
+        }
+        self.component = Component::Random {
+            n_bits_per_position,
+        };
+        self
+    }
+
+    /// Set the component of `self` to a protograph lifted with circulants of size
+    /// `lifting_size` avoiding cycles smaller than `minimal_girth` when possible.
+    ///
+    /// The uncoupled protograph is the `dv / g` by `dc / g` matrix with all multiplicities equal
+    /// to `g = gcd(dv, dc)`. The `dc` edges of each check are dealt cyclically to the
+    /// `coupling_width` coupled positions, so every check has at least one edge to each of them.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `lifting_size` is 0.
+    pub fn with_protograph_component(mut self, lifting_size: usize, minimal_girth: usize) -> Self {
+        if lifting_size == 0 {
+            panic!("the lifting size must be positive");
+        }
+        self.component = Component::Protograph {
+            lifting_size,
+            minimal_girth,
+        };
+        self
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of bit positions.
+    pub fn get_coupling_length(&self) -> usize {
+        self.coupling_length
+    }
+
+    /// Returns the number of check positions each bit is connected to.
+    pub fn get_coupling_width(&self) -> usize {
+        self.coupling_width
+    }
+
+    /// Returns the number of check positions.
+    pub fn get_n_check_positions(&self) -> usize {
+        self.coupling_length + self.coupling_width - 1
+    }
+
+    /// Returns the number of bits at each position.
+    pub fn get_n_bits_per_position(&self) -> usize {
+        match self.component {
+            Component::Random {
+                n_bits_per_position,
+            } => n_bits_per_position,
+            Component::Protograph { lifting_size, .. } => {
+                self.check_degree / self.get_degrees_gcd() * lifting_size
+            }
+        }
+    }
+
+    /// Returns the number of checks at each position.
+    pub fn get_n_checks_per_position(&self) -> usize {
+        self.get_n_bits_per_position() * self.bit_degree / self.check_degree
+    }
+
+    /// Returns the number of bits in the generated codes.
+    pub fn get_n_bits(&self) -> usize {
+        self.coupling_length * self.get_n_bits_per_position()
+    }
+
+    /// Returns the number of checks in the generated codes.
+    pub fn get_n_checks(&self) -> usize {
+        self.get_n_check_positions() * self.get_n_checks_per_position()
+    }
+
+    /// Returns the coupled protograph used by the protograph component.
+    pub fn get_protograph(&self) -> Protograph {
+        let components = self.get_protograph_components();
+        let n_component_checks = self.bit_degree / self.get_degrees_gcd();
+        let n_component_bits = self.check_degree / self.get_degrees_gcd();
+        let multiplicities = (0..self.get_n_check_positions())
+            .flat_map(|check_position| {
+                let components = &components;
+                (0..n_component_checks).map(move |check| {
+                    (0..self.coupling_length)
+                        .flat_map(|bit_position| {
+                            match components.get(check_position.wrapping_sub(bit_position)) {
+                                Some(component) => component[check].clone(),
+                                None => vec![0; n_component_bits],
+                            }
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+        Protograph::from_multiplicities(multiplicities)
+    }
+
+    /// Returns the sliding window decoding configuration of the generated codes for windows
+    /// spanning `window_size` check positions.
+    pub fn get_sliding_window(&self, window_size: usize) -> SlidingWindow {
+        SlidingWindow {
+            window_size,
+            coupling_length: self.coupling_length,
+            coupling_width: self.coupling_width,
+            n_bits_per_position: self.get_n_bits_per_position(),
+            n_checks_per_position: self.get_n_checks_per_position(),
+        }
+    }
+
+    // ***** Private methods *****
+
+    fn get_degrees_gcd(&self) -> usize {
+        gcd(self.bit_degree, self.check_degree)
+    }
+
+    // Returns the multiplicities between the component checks and the component bits that are
+    // `offset` positions apart for each offset smaller than the coupling width. The edges of
+    // each check of the uncoupled protograph are dealt cyclically to the offsets, starting where
+    // the previous check stopped.
+    fn get_protograph_components(&self) -> Vec<Vec<Vec<usize>>> {
+        let g = self.get_degrees_gcd();
+        let n_component_checks = self.bit_degree / g;
+        let n_component_bits = self.check_degree / g;
+        let mut components =
+            vec![vec![vec![0; n_component_bits]; n_component_checks]; self.coupling_width];
+        for edge in 0..n_component_checks * self.check_degree {
+            let check = edge / self.check_degree;
+            let bit = edge % self.check_degree / g;
+            components[edge % self.coupling_width][check][bit] += 1;
+        }
+        components
+    }
+
+    fn generate_random_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        let n_bits_per_position = self.get_n_bits_per_position();
+        let n_checks_per_position = self.get_n_checks_per_position();
+        let mut sockets_of_check_positions = vec![Vec::new(); self.get_n_check_positions()];
+        for position in 0..self.coupling_length {
+            let mut sockets: Vec<usize> = (position * n_bits_per_position
+                ..(position + 1) * n_bits_per_position)
+                .flat_map(|bit| vec![bit; self.bit_degree])
+                .collect();
+            sockets.shuffle(rng);
+            for (index, bit) in sockets.into_iter().enumerate() {
+                sockets_of_check_positions[position + index % self.coupling_width].push(bit);
+            }
+        }
+        let checks = sockets_of_check_positions
+            .into_iter()
+            .flat_map(|mut sockets| {
+                sockets.shuffle(rng);
+                Self::deal_sockets(sockets, n_checks_per_position)
+            })
+            .collect();
+        ParityCheckMatrix::with_n_bits(self.get_n_bits()).with_checks(checks)
+    }
+
+    // Deals the sockets cyclically to the checks. If a socket would create a parallel edge, it
+    // is given to the next check with some free room instead. It is dropped if there is none.
+    fn deal_sockets(sockets: Vec<usize>, n_checks: usize) -> Vec<Vec<usize>> {
+        let capacity = sockets.len().div_ceil(n_checks);
+        let mut checks = vec![Vec::with_capacity(capacity); n_checks];
+        for (index, bit) in sockets.into_iter().enumerate() {
+            let free_check = (0..n_checks)
+                .map(|shift| (index + shift) % n_checks)
+                .find(|check| checks[*check].len() < capacity && !checks[*check].contains(&bit));
+            if let Some(check) = free_check {
+                checks[check].push(bit);
+            }
+        }
+        checks
+    }
+}
+
+impl CodeGenerator for SpatiallyCoupledCodeGenerator {
+    fn generate_with_rng<R: Rng>(&self, rng: &mut R) -> ParityCheckMatrix {
+        match self.component {
+            Component::Random { .. } => self.generate_random_with_rng(rng),
+            Component::Protograph {
+                lifting_size,
+                minimal_girth,
+            } => ProtographCodeGenerator::from_protograph(self.get_protograph(), lifting_size)
+                .with_circulant_lifting(minimal_girth)
+                .generate_with_rng(rng),
+        }
+    }
+}
+
+/// The configuration of a sliding window decoder for a spatially coupled code.
+///
+/// The window at position `t` contains the checks of positions `t` to `t + window_size - 1`.
+/// Its targets are the bits at position `t` which are decided once the window is decoded. The bits
+/// of the previous positions that are connected to the window checks were already decided by the
+/// previous windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlidingWindow {
+    window_size: usize,
+    coupling_length: usize,
+    coupling_width: usize,
+    n_bits_per_position: usize,
+    n_checks_per_position: usize,
+}
+
+impl SlidingWindow {
+    /// Returns the number of check positions in each window.
+    pub fn get_window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Returns the number of windows. There is one window for each bit position.
+    pub fn get_n_windows(&self) -> usize {
+        self.coupling_length
+    }
+
+    /// Returns the window at `position`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `position` is not smaller than the number of windows.
+    pub fn get_window(&self, position: usize) -> Window {
+        if position >= self.coupling_length {
+            panic!("window position out of bound");
+        }
+        let n_check_positions = self.coupling_length + self.coupling_width - 1;
+        let last_check_position = usize::min(position + self.window_size, n_check_positions);
+        let last_bit_position = usize::min(position + self.window_size, self.coupling_length);
+        let first_decided_position = (position + 1).saturating_sub(self.coupling_width);
+        Window {
+            checks: self.get_check_range(position, last_check_position),
+            bits: self.get_bit_range(position, last_bit_position),
+            target_bits: self.get_bit_range(position, position + 1),
+            decided_bits: self.get_bit_range(first_decided_position, position),
+        }
+    }
+
+    /// Returns an iterator over all windows in decoding order.
+    pub fn windows_iter(&self) -> impl Iterator<Item = Window> + '_ {
+        (0..self.get_n_windows()).map(move |position| self.get_window(position))
+    }
+
+    fn get_bit_range(&self, first_position: usize, last_position: usize) -> Range<usize> {
+        first_position * self.n_bits_per_position..last_position * self.n_bits_per_position
+    }
+
+    fn get_check_range(&self, first_position: usize, last_position: usize) -> Range<usize> {
+        first_position * self.n_checks_per_position..last_position * self.n_checks_per_position
+    }
+}
+
+/// A window of a sliding window decoder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    checks: Range<usize>,
+    bits: Range<usize>,
+    target_bits: Range<usize>,
+    decided_bits: Range<usize>,
+}
+
+impl Window {
+    /// Returns the checks used to decode the window.
+    pub fn get_checks(&self) -> Range<usize> {
+        self.checks.clone()
+    }
+
+    /// Returns the undecided bits of the window.
+    pub fn get_bits(&self) -> Range<usize> {
+        self.bits.clone()
+    }
+
+    /// Returns the bits decided after decoding the window.
+    pub fn get_target_bits(&self) -> Range<usize> {
+        self.target_bits.clone()
+    }
+
+    /// Returns the bits connected to the window checks that were decided by previous windows.
+    pub fn get_decided_bits(&self) -> Range<usize> {
+        self.decided_bits.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn edges_are_within_coupling_width(
+        code: &ParityCheckMatrix,
+        generator: &SpatiallyCoupledCodeGenerator,
+    ) -> bool {
+        code.edges_iter().all(|(check, bit)| {
+            let check_position = check / generator.get_n_checks_per_position();
+            let bit_position = bit / generator.get_n_bits_per_position();
+            check_position >= bit_position
+                && check_position < bit_position + generator.get_coupling_width()
+        })
+    }
+
+    #[test]
+    fn random_component() {
+        let generator = SpatiallyCoupledCodeGenerator::new(3, 6, 8, 3).with_random_component(20);
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(5));
+
+        assert_eq!(code.get_n_bits(), 160);
+        assert_eq!(code.get_n_checks(), 100);
+        assert!(edges_are_within_coupling_width(&code, &generator));
+        assert!(code.get_bit_degrees().iter().all(|degree| *degree <= 3));
+        assert!(code.get_check_degrees().iter().all(|degree| *degree <= 6));
+
+        // Checks away from the boundaries have the full degree up to parallel edges.
+        let check_degrees = code.get_check_degrees();
+        let middle_edges: usize = check_degrees[20..80].iter().sum();
+        assert!(middle_edges > 6 * 60 - 10);
+    }
+
+    #[test]
+    fn protograph_component() {
+        let generator =
+            SpatiallyCoupledCodeGenerator::new(3, 6, 5, 3).with_protograph_component(7, 6);
+        let protograph = generator.get_protograph();
+        assert_eq!(protograph.get_n_bits(), 10);
+        assert_eq!(protograph.get_n_checks(), 7);
+        assert_eq!(protograph.get_bit_degrees(), vec![3; 10]);
+        assert_eq!(protograph.get_check_degrees(), vec![2, 4, 6, 6, 6, 4, 2]);
+
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(5));
+        assert_eq!(code.get_n_bits(), 70);
+        assert_eq!(code.get_n_checks(), 49);
+        assert_eq!(code.get_bit_degrees(), vec![3; 70]);
+        assert!(edges_are_within_coupling_width(&code, &generator));
+    }
+
+    #[test]
+    fn multiplicities_are_spread_over_the_coupling_width() {
+        let generator =
+            SpatiallyCoupledCodeGenerator::new(4, 8, 3, 3).with_protograph_component(5, 6);
+        let protograph = generator.get_protograph();
+        assert_eq!(protograph.get_n_bits(), 6);
+        assert_eq!(protograph.get_multiplicity(0, 0), 2);
+        assert_eq!(protograph.get_multiplicity(1, 0), 1);
+        assert_eq!(protograph.get_multiplicity(2, 0), 1);
+        assert_eq!(protograph.get_multiplicity(3, 0), 0);
+        assert_eq!(protograph.get_bit_degrees(), vec![4; 6]);
+    }
+
+    #[test]
+    fn every_check_has_an_edge_to_every_coupled_position() {
+        let generator =
+            SpatiallyCoupledCodeGenerator::new(3, 4, 4, 3).with_protograph_component(3, 0);
+        let protograph = generator.get_protograph();
+        assert_eq!(protograph.get_bit_degrees(), vec![3; 16]);
+        assert_eq!(
+            protograph.get_check_degrees(),
+            vec![2, 1, 1, 3, 3, 2, 4, 4, 4, 4, 4, 4, 2, 3, 3, 1, 1, 2]
+        );
+        for component in generator.get_protograph_components() {
+            assert!(component
+                .iter()
+                .all(|check| check.iter().sum::<usize>() > 0));
+        }
+
+        let code = generator.generate_with_rng(&mut ChaCha8Rng::seed_from_u64(5));
+        assert_eq!(code.get_n_bits(), 48);
+        assert_eq!(code.get_n_checks(), 54);
+        assert!(code.get_check_degrees().iter().all(|degree| *degree > 0));
+        assert_eq!(code.get_bit_degrees(), vec![3; 48]);
+        assert!(edges_are_within_coupling_width(&code, &generator));
+    }
+
+    #[test]
+    fn sliding_windows() {
+        let generator = SpatiallyCoupledCodeGenerator::new(3, 6, 5, 3).with_random_component(4);
+        let sliding_window = generator.get_sliding_window(3);
+        assert_eq!(sliding_window.get_n_windows(), 5);
+
+        let first = sliding_window.get_window(0);
+        assert_eq!(first.get_checks(), 0..6);
+        assert_eq!(first.get_bits(), 0..12);
+        assert_eq!(first.get_target_bits(), 0..4);
+        assert_eq!(first.get_decided_bits(), 0..0);
+
+        let middle = sliding_window.get_window(3);
+        assert_eq!(middle.get_checks(), 6..12);
+        assert_eq!(middle.get_bits(), 12..20);
+        assert_eq!(middle.get_target_bits(), 12..16);
+        assert_eq!(middle.get_decided_bits(), 4..12);
+
+        let last = sliding_window.get_window(4);
+        assert_eq!(last.get_checks(), 8..14);
+        assert_eq!(last.get_bits(), 16..20);
+        assert_eq!(sliding_window.windows_iter().count(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_if_the_number_of_checks_per_position_is_fractional() {
+        SpatiallyCoupledCodeGenerator::new(3, 6, 5, 3).with_random_component(3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_if_the_coupling_width_is_larger_than_the_check_degree() {
+        SpatiallyCoupledCodeGenerator::new(2, 2, 5, 3);
+    }
+}
@@ -0,0 +1,324 @@
+//! Standard stabilizer codes.
+//!
+//! Each stabilizer is represented by a row of both the X and the Z parity check matrices. Thus,
+//! the X part of a Z stabilizer is an empty check and vice versa.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{GF4Stabilizers, ParityCheckMatrix};
+//!
+//! let steane = GF4Stabilizers::steane_code();
+//! assert_eq!(steane.n_qubits(), 7);
+//! assert_eq!(steane.n_stabilizers(), 6);
+//!
+//! // The same code from a pair of Hamming codes.
+//! let hamming = ParityCheckMatrix::hamming_code(3);
+//! let css = GF4Stabilizers::css_code(&hamming, &hamming).unwrap();
+//! assert_eq!(css.merge().get_rank(), 6);
+//! ```
+
+use super::{GF4Stabilizers, StabilizerError};
+use crate::ParityCheckMatrix;
+
+impl GF4Stabilizers {
+    /// Returns the CSS code where the X stabilizers are the checks of `c2` and the Z stabilizers
+    /// are the checks of `c1`.
+    ///
+    /// The X stabilizers come first.
+    ///
+    /// Returns an error if `c1` and `c2` have different numbers of bits or if the dual of the code
+    /// of `c2` is not a subcode of the code of `c1`. That is, if some checks of `c1` and `c2` have
+    /// an odd overlap.
+    pub fn css_code(
+        c1: &ParityCheckMatrix,
+        c2: &ParityCheckMatrix,
+    ) -> Result<Self, StabilizerError> {
+        if c1.get_n_bits() != c2.get_n_bits() {
+            return Err(StabilizerError::DifferentNumberOfQubits {
+                x_qubits: c2.get_n_bits(),
+                z_qubits: c1.get_n_bits(),
+            });
+        }
+        let code = Self::css_code_unchecked(c1, c2);
+        code.validate()?;
+        Ok(code)
+    }
+
+    // Stacks the checks of `c2` as X stabilizers over the checks of `c1` as Z stabilizers.
+    fn css_code_unchecked(c1: &ParityCheckMatrix, c2: &ParityCheckMatrix) -> Self {
+        let n_bits = c1.get_n_bits();
+        let x_checks = c2
+            .checks_iter()
+            .map(|check| check.to_vec())
+            .chain(c1.checks_iter().map(|_| Vec::new()))
+            .collect();
+        let z_checks = c2
+            .checks_iter()
+            .map(|_| Vec::new())
+            .chain(c1.checks_iter().map(|check| check.to_vec()))
+            .collect();
+        Self {
+            x_checks: ParityCheckMatrix::with_n_bits(n_bits).with_checks(x_checks),
+            z_checks: ParityCheckMatrix::with_n_bits(n_bits).with_checks(z_checks),
+        }
+    }
+
+    /// Returns the [[5, 1, 3]] code generated by the cyclic shifts of XZZXI.
+    pub fn five_qubit_code() -> Self {
+        Self {
+            x_checks: ParityCheckMatrix::with_n_bits(5).with_checks(vec![
+                vec![0, 3],
+                vec![1, 4],
+                vec![0, 2],
+                vec![1, 3],
+            ]),
+            z_checks: ParityCheckMatrix::with_n_bits(5).with_checks(vec![
+                vec![1, 2],
+                vec![2, 3],
+                vec![3, 4],
+                vec![0, 4],
+            ]),
+        }
+    }
+
+    /// Returns the [[7, 1, 3]] Steane code.
+    ///
+    /// This is the CSS code of the Hamming code with checks `[0, 1, 2, 4]`, `[0, 1, 3, 5]` and
+    /// `[0, 2, 3, 6]`.
+    pub fn steane_code() -> Self {
+        let hamming = ParityCheckMatrix::with_n_bits(7).with_checks(vec![
+            vec![0, 1, 2, 4],
+            vec![0, 1, 3, 5],
+            vec![0, 2, 3, 6],
+        ]);
+        Self::css_code_unchecked(&hamming, &hamming)
+    }
+
+    /// Returns the [[9, 1, 3]] Shor code.
+    ///
+    /// The 6 Z stabilizers come first followed by the 2 X stabilizers.
+    pub fn shor_code() -> Self {
+        let mut x_checks = vec![Vec::new(); 6];
+        x_checks.push(vec![0, 1, 2, 3, 4, 5]);
+        x_checks.push(vec![3, 4, 5, 6, 7, 8]);
+        let z_checks = vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![3, 4],
+            vec![4, 5],
+            vec![6, 7],
+            vec![7, 8],
+            Vec::new(),
+            Vec::new(),
+        ];
+        Self {
+            x_checks: ParityCheckMatrix::with_n_bits(9).with_checks(x_checks),
+            z_checks: ParityCheckMatrix::with_n_bits(9).with_checks(z_checks),
+        }
+    }
+
+    /// Returns the [[15, 1, 3]] quantum Reed-Muller code.
+    ///
+    /// The qubit `q` is associated to the non-zero point `q + 1` of the binary space of dimension
+    /// 4. The 4 X stabilizers are the evaluations of the linear monomials and the 10 Z stabilizers
+    /// are the evaluations of the linear and quadratic monomials.
+    pub fn quantum_reed_muller_code() -> Self {
+        let linear_monomials: Vec<usize> = (0..4).map(|variable| 1 << variable).collect();
+        let quadratic_monomials: Vec<usize> = (1..16_usize)
+            .filter(|monomial| monomial.count_ones() == 2)
+            .collect();
+        let evaluate = |monomial: &usize| -> Vec<usize> {
+            (0..15)
+                .filter(|qubit| (qubit + 1) & monomial == *monomial)
+                .collect()
+        };
+        let x_checks = linear_monomials.iter().map(evaluate).collect();
+        let z_checks = linear_monomials
+            .iter()
+            .chain(quadratic_monomials.iter())
+            .map(evaluate)
+            .collect();
+        Self::css_code_unchecked(
+            &ParityCheckMatrix::with_n_bits(15).with_checks(z_checks),
+            &ParityCheckMatrix::with_n_bits(15).with_checks(x_checks),
+        )
+    }
+
+    /// Returns the [[2^r - 1, 2^r - 1 - 2r, 3]] quantum Hamming code.
+    ///
+    /// This is the CSS code of the Hamming code with `r` checks.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `r` is smaller than 3.
+    pub fn quantum_hamming_code(r: usize) -> Self {
+        if r < 3 {
+            panic!("quantum hamming codes need r to be at least 3");
+        }
+        let hamming = ParityCheckMatrix::hamming_code(r);
+        Self::css_code_unchecked(&hamming, &hamming)
+    }
+
+    /// Returns the hypergraph product of `c1` and `c2`.
+    ///
+    /// If `c1` has `n1` bits and `m1` checks and `c2` has `n2` bits and `m2` checks, the first
+    /// `n1 * n2` qubits are the pairs of bits `(i, j)` at `i * n2 + j` and the last `m1 * m2`
+    /// qubits are the pairs of checks `(a, b)` at `n1 * n2 + a * m2 + b`. The X stabilizers are
+    /// the pairs `(a, j)` and come first followed by the Z stabilizers, the pairs `(i, b)`.
+    pub fn hypergraph_product_code(c1: &ParityCheckMatrix, c2: &ParityCheckMatrix) -> Self {
+        let (n1, m1) = (c1.get_n_bits(), c1.get_n_checks());
+        let (n2, m2) = (c2.get_n_bits(), c2.get_n_checks());
+        let c1_transposed = c1.get_transposed_matrix();
+        let c2_transposed = c2.get_transposed_matrix();
+        let get_bits = |matrix: &ParityCheckMatrix, row: usize| -> Vec<usize> {
+            matrix
+                .get_check(row)
+                .map(|check| check.to_vec())
+                .unwrap_or_default()
+        };
+
+        let mut x_checks = Vec::with_capacity(m1 * n2);
+        for a in 0..m1 {
+            for j in 0..n2 {
+                let mut check: Vec<usize> =
+                    get_bits(c1, a).into_iter().map(|i| i * n2 + j).collect();
+                check.extend(
+                    get_bits(&c2_transposed, j)
+                        .into_iter()
+                        .map(|b| n1 * n2 + a * m2 + b),
+                );
+                x_checks.push(check);
+            }
+        }
+        let mut z_checks = Vec::with_capacity(n1 * m2);
+        for i in 0..n1 {
+            for b in 0..m2 {
+                let mut check: Vec<usize> =
+                    get_bits(c2, b).into_iter().map(|j| i * n2 + j).collect();
+                check.extend(
+                    get_bits(&c1_transposed, i)
+                        .into_iter()
+                        .map(|a| n1 * n2 + a * m2 + b),
+                );
+                z_checks.push(check);
+            }
+        }
+        let n_qubits = n1 * n2 + m1 * m2;
+        Self::css_code_unchecked(
+            &ParityCheckMatrix::with_n_bits(n_qubits).with_checks(z_checks),
+            &ParityCheckMatrix::with_n_bits(n_qubits).with_checks(x_checks),
+        )
+    }
+
+    /// Returns the [[d^2 + (d - 1)^2, 1, d]] planar surface code.
+    ///
+    /// This is the hypergraph product of two repetition codes over `distance` bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `distance` is 0.
+    pub fn surface_code(distance: usize) -> Self {
+        if distance == 0 {
+            panic!("the distance must be positive");
+        }
+        let repetition = ParityCheckMatrix::repetition_code(distance);
+        Self::hypergraph_product_code(&repetition, &repetition)
+    }
+
+    /// Returns the [[2 l^2, 2, l]] toric code.
+    ///
+    /// This is the hypergraph product of two cyclic repetition codes over `length` bits.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `length` is smaller than 2.
+    pub fn toric_code(length: usize) -> Self {
+        if length < 2 {
+            panic!("the length must be at least 2");
+        }
+        let cycle = ParityCheckMatrix::with_n_bits(length).with_checks(
+            (0..length)
+                .map(|bit| {
+                    let mut check = vec![bit, (bit + 1) % length];
+                    check.sort_unstable();
+                    check
+                })
+                .collect(),
+        );
+        Self::hypergraph_product_code(&cycle, &cycle)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_n_logical_qubits(code: &GF4Stabilizers) -> usize {
+        code.n_qubits() - code.merge().get_rank()
+    }
+
+    #[test]
+    fn parameters_of_catalog_codes() {
+        let codes = vec![
+            (GF4Stabilizers::five_qubit_code(), 5, 1),
+            (GF4Stabilizers::steane_code(), 7, 1),
+            (GF4Stabilizers::shor_code(), 9, 1),
+            (GF4Stabilizers::quantum_reed_muller_code(), 15, 1),
+            (GF4Stabilizers::quantum_hamming_code(3), 7, 1),
+            (GF4Stabilizers::quantum_hamming_code(4), 15, 7),
+            (GF4Stabilizers::quantum_hamming_code(5), 31, 21),
+            (GF4Stabilizers::surface_code(3), 13, 1),
+            (GF4Stabilizers::surface_code(5), 41, 1),
+            (GF4Stabilizers::toric_code(3), 18, 2),
+        ];
+        for (code, n_qubits, n_logical_qubits) in codes {
+            assert_eq!(code.n_qubits(), n_qubits);
+            assert_eq!(get_n_logical_qubits(&code), n_logical_qubits);
+        }
+    }
+
+    #[test]
+    fn css_stabilizers_are_split_by_type() {
+        let code = GF4Stabilizers::quantum_reed_muller_code();
+        assert_eq!(code.n_stabilizers(), 14);
+        assert!(code
+            .x_checks()
+            .checks_iter()
+            .skip(4)
+            .all(|check| check.get_n_bits() == 0));
+        assert!(code
+            .z_checks()
+            .checks_iter()
+            .take(4)
+            .all(|check| check.get_n_bits() == 0));
+        assert!(code
+            .x_checks()
+            .checks_iter()
+            .take(4)
+            .all(|check| check.get_n_bits() == 8));
+    }
+
+    #[test]
+    fn css_code_fails_if_codes_are_not_orthogonal() {
+        let repetition = ParityCheckMatrix::repetition_code(3);
+        assert_eq!(
+            GF4Stabilizers::css_code(&repetition, &repetition).err(),
+            Some(StabilizerError::AnticommutingStabilizers(vec![
+                (0, 3),
+                (1, 2)
+            ]))
+        );
+    }
+
+    #[test]
+    fn catalog_codes_are_valid() {
+        assert!(GF4Stabilizers::five_qubit_code().validate().is_ok());
+        assert!(GF4Stabilizers::shor_code().validate().is_ok());
+        assert!(GF4Stabilizers::quantum_reed_muller_code()
+            .validate()
+            .is_ok());
+        assert!(GF4Stabilizers::surface_code(4).validate().is_ok());
+        assert!(GF4Stabilizers::toric_code(4).validate().is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+//! Logical operators of a single type.
+
+use super::GF4Stabilizers;
+use crate::{add_checks, ParityCheckMatrix};
+
+impl GF4Stabilizers {
+    /// Returns independent Z logical operators of `self`.
+    ///
+    /// These are Z operators commuting with all stabilizers that are not in the stabilizer group.
+    /// Each logical is given by the positions of its Z Paulis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::GF4Stabilizers;
+    ///
+    /// assert_eq!(GF4Stabilizers::steane_code().get_z_logicals().len(), 1);
+    /// assert_eq!(GF4Stabilizers::five_qubit_code().get_z_logicals(), vec![vec![0, 1, 2, 3, 4]]);
+    /// ```
+    pub fn get_z_logicals(&self) -> Vec<Vec<usize>> {
+        get_logicals(&self.get_x_parts(), &self.get_z_parts(), self.n_qubits())
+    }
+
+    /// Returns independent X logical operators of `self`.
+    ///
+    /// These are X operators commuting with all stabilizers that are not in the stabilizer group.
+    /// Each logical is given by the positions of its X Paulis.
+    pub fn get_x_logicals(&self) -> Vec<Vec<usize>> {
+        get_logicals(&self.get_z_parts(), &self.get_x_parts(), self.n_qubits())
+    }
+
+    fn get_x_parts(&self) -> Vec<Vec<usize>> {
+        get_parts(self.x_checks(), self.n_stabilizers())
+    }
+
+    fn get_z_parts(&self) -> Vec<Vec<usize>> {
+        get_parts(self.z_checks(), self.n_stabilizers())
+    }
+}
+
+// Returns the rows of `checks` padded with empty rows.
+fn get_parts(checks: &ParityCheckMatrix, n_stabilizers: usize) -> Vec<Vec<usize>> {
+    (0..n_stabilizers)
+        .map(|stabilizer| {
+            checks
+                .get_check(stabilizer)
+                .map(|check| check.to_vec())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+// Returns the operators of a given type that commute with the stabilizers whose other parts are
+// `other_parts` and that are not products of stabilizers.
+fn get_logicals(
+    other_parts: &[Vec<usize>],
+    parts: &[Vec<usize>],
+    n_qubits: usize,
+) -> Vec<Vec<usize>> {
+    let other_checks = ParityCheckMatrix::with_n_bits(n_qubits).with_checks(other_parts.to_vec());
+
+    // The operators of the stabilizer group of the given type are the products of stabilizers
+    // whose other parts cancel.
+    let mut operators: Vec<Vec<usize>> = other_checks
+        .get_transposed_matrix()
+        .get_codeword_basis()
+        .into_iter()
+        .map(|product| {
+            product.iter().fold(Vec::new(), |sum, stabilizer| {
+                add_checks(&sum, &parts[*stabilizer])
+            })
+        })
+        .collect();
+    let mut rank = ParityCheckMatrix::with_n_bits(n_qubits)
+        .with_checks(operators.clone())
+        .get_rank();
+
+    let mut logicals = Vec::new();
+    for candidate in other_checks.get_codeword_basis() {
+        operators.push(candidate.clone());
+        let new_rank = ParityCheckMatrix::with_n_bits(n_qubits)
+            .with_checks(operators.clone())
+            .get_rank();
+        if new_rank > rank {
+            rank = new_rank;
+            logicals.push(candidate);
+        } else {
+            operators.pop();
+        }
+    }
+    logicals
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn logicals_anticommute_in_pairs() {
+        for code in [
+            GF4Stabilizers::steane_code(),
+            GF4Stabilizers::shor_code(),
+            GF4Stabilizers::quantum_reed_muller_code(),
+        ]
+        .iter()
+        {
+            let x_logicals = code.get_x_logicals();
+            let z_logicals = code.get_z_logicals();
+            assert_eq!(x_logicals.len(), 1);
+            assert_eq!(z_logicals.len(), 1);
+            let overlap = x_logicals[0]
+                .iter()
+                .filter(|qubit| z_logicals[0].contains(qubit))
+                .count();
+            assert_eq!(overlap % 2, 1);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Stabilizer codes in the GF4 representation.
+//!
+//! A stabilizer is represented by a row in both the X and the Z parity check matrices. The
+//! Pauli acting on qubit `q` is X if `q` is only in the X row, Z if `q` is only in the Z row and
+//! Y if `q` is in both rows.
+//!
+//! All constructors validate that the stabilizers commute.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{GF4Stabilizers, Pauli::{I, X, Z}, StabilizerError};
+//!
+//! let stabilizers = GF4Stabilizers::from_dense_paulis(
+//!     vec![vec![X, Z, Z, X, I], vec![I, X, Z, Z, X]],
+//!     5,
+//! );
+//! assert!(stabilizers.is_ok());
+//!
+//! let error = GF4Stabilizers::from_dense_paulis(vec![vec![X, I], vec![Z, I]], 2);
+//! assert_eq!(
+//!     error.err(),
+//!     Some(StabilizerError::AnticommutingStabilizers(vec![(0, 1)]))
+//! );
+//! ```
+use crate::ParityCheckMatrix;
+use crate::Pauli;
+use crate::PauliString;
+use std::fmt;
+
+mod catalog;
+mod logicals;
+
+#[derive(Clone, PartialEq)]
+pub struct GF4Stabilizers {
+    x_checks: ParityCheckMatrix,
+    z_checks: ParityCheckMatrix,
+}
+
+/// The reasons why a set of Paulis doesn't define a valid stabilizer group.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StabilizerError {
+    /// The X and Z parity check matrices are defined over different numbers of qubits.
+    DifferentNumberOfQubits { x_qubits: usize, z_qubits: usize },
+    /// The pairs of stabilizers that anticommute sorted in lexicographic order.
+    AnticommutingStabilizers(Vec<(usize, usize)>),
+    /// A stabilizer acts on a qubit that is not smaller than the number of qubits.
+    PositionOutOfBound { stabilizer: usize, position: usize },
+    /// A stabilizer is not defined over the expected number of qubits.
+    WrongNumberOfQubits {
+        stabilizer: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for StabilizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DifferentNumberOfQubits { x_qubits, z_qubits } => write!(
+                f,
+                "x checks have {} qubits and z checks have {} qubits",
+                x_qubits, z_qubits
+            ),
+            Self::AnticommutingStabilizers(pairs) => {
+                write!(f, "anticommuting stabilizers: {:?}", pairs)
+            }
+            Self::PositionOutOfBound {
+                stabilizer,
+                position,
+            } => write!(
+                f,
+                "stabilizer {} acts on qubit {} which is out of bound",
+                stabilizer, position
+            ),
+            Self::WrongNumberOfQubits {
+                stabilizer,
+                expected,
+                found,
+            } => write!(
+                f,
+                "stabilizer {} is over {} qubits instead of {}",
+                stabilizer, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StabilizerError {}
+
+impl GF4Stabilizers {
+    pub fn empty_with_n_bits(n_bits: usize) -> Self {
+        Self {
+            x_checks: ParityCheckMatrix::with_n_bits(n_bits),
+            z_checks: ParityCheckMatrix::with_n_bits(n_bits),
+        }
+    }
+
+    /// Creates stabilizers from the Paulis acting on each of the `n_qubits` qubits.
+    ///
+    /// Returns an error if some stabilizers anticommute or act on more than `n_qubits` qubits.
+    pub fn from_dense_paulis(
+        stabilizers: Vec<Vec<Pauli>>,
+        n_qubits: usize,
+    ) -> Result<Self, StabilizerError> {
+        let sparse_stabilizers = stabilizers
+            .into_iter()
+            .map(|stabilizer| {
+                stabilizer
+                    .into_iter()
+                    .enumerate()
+                    .map(|(position, pauli)| (pauli, position))
+                    .collect()
+            })
+            .collect();
+        Self::from_sparse_paulis(sparse_stabilizers, n_qubits)
+    }
+
+    /// Creates stabilizers from the non-identity Paulis of each stabilizer and their positions.
+    ///
+    /// Returns an error if some stabilizers anticommute or if some positions are not smaller
+    /// than `n_qubits`.
+    pub fn from_sparse_paulis(
+        stabilizers: Vec<Vec<(Pauli, usize)>>,
+        n_qubits: usize,
+    ) -> Result<Self, StabilizerError> {
+        for (index, stabilizer) in stabilizers.iter().enumerate() {
+            if let Some((_, position)) = stabilizer
+                .iter()
+                .find(|(_, position)| *position >= n_qubits)
+            {
+                return Err(StabilizerError::PositionOutOfBound {
+                    stabilizer: index,
+                    position: *position,
+                });
+            }
+        }
+        let mut x_checks = Vec::with_capacity(stabilizers.len());
+        let mut z_checks = Vec::with_capacity(stabilizers.len());
+        stabilizers.iter().for_each(|stab| {
+            let mut x_check = Vec::new();
+            let mut z_check = Vec::new();
+            stab.iter().for_each(|(pauli, position)| {
+                let gf4_representation = pauli.as_gf4();
+                if gf4_representation.0 == 1 {
+                    x_check.push(*position);
+                }
+                if gf4_representation.1 == 1 {
+                    z_check.push(*position);
+                }
+            });
+            x_checks.push(x_check);
+            z_checks.push(z_check);
+        });
+        Self::from_parity_check_matrices(
+            ParityCheckMatrix::with_n_bits(n_qubits).with_checks(x_checks),
+            ParityCheckMatrix::with_n_bits(n_qubits).with_checks(z_checks),
+        )
+    }
+
+    /// Creates stabilizers from `PauliString` ignoring their phases.
+    ///
+    /// Returns an error if some stabilizers anticommute or are not over `n_qubits` qubits.
+    pub fn from_pauli_strings(
+        stabilizers: &[PauliString],
+        n_qubits: usize,
+    ) -> Result<Self, StabilizerError> {
+        if let Some((index, stabilizer)) = stabilizers
+            .iter()
+            .enumerate()
+            .find(|(_, stabilizer)| stabilizer.get_n_qubits() != n_qubits)
+        {
+            return Err(StabilizerError::WrongNumberOfQubits {
+                stabilizer: index,
+                expected: n_qubits,
+                found: stabilizer.get_n_qubits(),
+            });
+        }
+        Self::from_dense_paulis(
+            stabilizers
+                .iter()
+                .map(|stabilizer| stabilizer.get_paulis().to_vec())
+                .collect(),
+            n_qubits,
+        )
+    }
+
+    /// Creates stabilizers from their X and Z components.
+    ///
+    /// Returns an error if the matrices have different numbers of bits or if some stabilizers
+    /// anticommute. Missing rows in the shorter matrix are considered empty.
+    pub fn from_parity_check_matrices(
+        x_checks: ParityCheckMatrix,
+        z_checks: ParityCheckMatrix,
+    ) -> Result<Self, StabilizerError> {
+        if x_checks.get_n_bits() != z_checks.get_n_bits() {
+            return Err(StabilizerError::DifferentNumberOfQubits {
+                x_qubits: x_checks.get_n_bits(),
+                z_qubits: z_checks.get_n_bits(),
+            });
+        }
+        let stabilizers = Self { x_checks, z_checks };
+        stabilizers.validate()?;
+        Ok(stabilizers)
+    }
+
+    /// Checks that all stabilizers commute.
+    pub fn validate(&self) -> Result<(), StabilizerError> {
+        let pairs = self.anticommuting_pairs();
+        if pairs.is_empty() {
+            Ok(())
+        } else {
+            Err(StabilizerError::AnticommutingStabilizers(pairs))
+        }
+    }
+
+    /// Returns all pairs of stabilizers with a `-1` symplectic product sorted in lexicographic
+    /// order.
+    pub fn anticommuting_pairs(&self) -> Vec<(usize, usize)> {
+        let stabilizers: Vec<Vec<Pauli>> = (0..self.n_stabilizers())
+            .map(|stabilizer| self.dense_paulis(stabilizer))
+            .collect();
+        let mut pairs = Vec::new();
+        for (first_index, first) in stabilizers.iter().enumerate() {
+            for (second_index, second) in stabilizers.iter().enumerate().skip(first_index + 1) {
+                let commutator: i32 = first
+                    .iter()
+                    .zip(second.iter())
+                    .map(|(a, b)| a.commutator_with(*b))
+                    .product();
+                if commutator == -1 {
+                    pairs.push((first_index, second_index));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Returns the stabilizer at `index` with phase 1 or `None` if `index` is out of bound.
+    pub fn get_stabilizer(&self, index: usize) -> Option<PauliString> {
+        if index < self.n_stabilizers() {
+            Some(PauliString::from_paulis(self.dense_paulis(index)))
+        } else {
+            None
+        }
+    }
+
+    pub fn x_checks(&self) -> &ParityCheckMatrix {
+        &self.x_checks
+    }
+
+    pub fn z_checks(&self) -> &ParityCheckMatrix {
+        &self.z_checks
+    }
+
+    pub fn keep(&self, qubits: &[usize]) -> Self {
+        Self {
+            x_checks: self.x_checks.keep(qubits),
+            z_checks: self.z_checks.keep(qubits),
+        }
+    }
+
+    pub fn merge(&self) -> ParityCheckMatrix {
+        self.x_checks.get_horizontal_concat_with(&self.z_checks)
+    }
+
+    pub fn n_qubits(&self) -> usize {
+        self.x_checks().get_n_bits()
+    }
+
+    pub fn n_stabilizers(&self) -> usize {
+        std::cmp::max(
+            self.x_checks().get_n_checks(),
+            self.z_checks().get_n_checks(),
+        )
+    }
+
+    pub fn without(&self, qubits: &[usize]) -> Self {
+        Self {
+            x_checks: self.x_checks.without(qubits),
+            z_checks: self.z_checks.without(qubits),
+        }
+    }
+
+    // Returns the Pauli acting on each qubit for the given stabilizer.
+    fn dense_paulis(&self, stabilizer: usize) -> Vec<Pauli> {
+        let mut paulis = vec![Pauli::I; self.n_qubits()];
+        if let Some(check) = self.x_checks.get_check(stabilizer) {
+            check.iter().for_each(|qubit| paulis[*qubit] = Pauli::X);
+        }
+        if let Some(check) = self.z_checks.get_check(stabilizer) {
+            check.iter().for_each(|qubit| {
+                paulis[*qubit] = if paulis[*qubit] == Pauli::X {
+                    Pauli::Y
+                } else {
+                    Pauli::Z
+                }
+            });
+        }
+        paulis
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Pauli::{I, X, Y, Z};
+
+    #[test]
+    fn commuting_stabilizers_are_valid() {
+        let stabilizers =
+            GF4Stabilizers::from_sparse_paulis(vec![vec![(Y, 0), (Y, 1)], vec![(X, 0), (X, 1)]], 2);
+        assert!(stabilizers.is_ok());
+    }
+
+    #[test]
+    fn all_anticommuting_pairs_are_reported() {
+        let stabilizers = GF4Stabilizers::from_dense_paulis(
+            vec![vec![X, I, I], vec![Z, I, I], vec![I, X, I], vec![Y, Z, I]],
+            3,
+        );
+        assert_eq!(
+            stabilizers.err(),
+            Some(StabilizerError::AnticommutingStabilizers(vec![
+                (0, 1),
+                (0, 3),
+                (1, 3),
+                (2, 3)
+            ]))
+        );
+    }
+
+    #[test]
+    fn parity_check_matrices_must_have_the_same_number_of_qubits() {
+        let stabilizers = GF4Stabilizers::from_parity_check_matrices(
+            ParityCheckMatrix::with_n_bits(3),
+            ParityCheckMatrix::with_n_bits(4),
+        );
+        assert_eq!(
+            stabilizers.err(),
+            Some(StabilizerError::DifferentNumberOfQubits {
+                x_qubits: 3,
+                z_qubits: 4
+            })
+        );
+    }
+
+    #[test]
+    fn conversion_with_pauli_strings() {
+        let stabilizers: Vec<PauliString> = vec!["XZZXI", "IXZZX", "XIXZZ", "ZXIXZ"]
+            .into_iter()
+            .map(|stabilizer| stabilizer.parse().unwrap())
+            .collect();
+        let code = GF4Stabilizers::from_pauli_strings(&stabilizers, 5).unwrap();
+        assert_eq!(code.get_stabilizer(2), Some(stabilizers[2].clone()));
+        assert_eq!(code.get_stabilizer(4), None);
+    }
+
+    #[test]
+    fn stabilizers_must_be_over_the_given_qubits() {
+        let stabilizers: Vec<PauliString> = vec!["XX".parse().unwrap(), "ZZZ".parse().unwrap()];
+        assert_eq!(
+            GF4Stabilizers::from_pauli_strings(&stabilizers, 2).err(),
+            Some(StabilizerError::WrongNumberOfQubits {
+                stabilizer: 1,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            GF4Stabilizers::from_sparse_paulis(vec![vec![(X, 0)], vec![(Z, 0), (Z, 2)]], 2).err(),
+            Some(StabilizerError::PositionOutOfBound {
+                stabilizer: 1,
+                position: 2
+            })
+        );
+        assert_eq!(
+            GF4Stabilizers::from_dense_paulis(vec![vec![X, X, I]], 2).err(),
+            Some(StabilizerError::PositionOutOfBound {
+                stabilizer: 0,
+                position: 2
+            })
+        );
+    }
+
+    #[test]
+    fn missing_rows_are_identities() {
+        let x_checks = ParityCheckMatrix::with_n_bits(2).with_checks(vec![vec![0, 1], vec![0]]);
+        let z_checks = ParityCheckMatrix::with_n_bits(2).with_checks(vec![vec![0, 1]]);
+        let stabilizers = GF4Stabilizers::from_parity_check_matrices(x_checks, z_checks);
+        assert_eq!(
+            stabilizers.err(),
+            Some(StabilizerError::AnticommutingStabilizers(vec![(0, 1)]))
+        );
+    }
+}
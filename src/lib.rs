@@ -3,6 +3,9 @@
 pub mod channel;
 pub use channel::*;
 
+pub mod circuits;
+pub use circuits::*;
+
 pub mod decoders;
 pub use decoders::*;
 
@@ -12,8 +15,8 @@ pub use gf2::*;
 pub mod gf4_stabilizers;
 pub use gf4_stabilizers::*;
 
-// pub mod generators;
-// pub use generators::*;
+pub mod generators;
+pub use generators::*;
 
 pub mod parity_check_matrix;
 pub use parity_check_matrix::*;
@@ -21,4 +24,13 @@ pub use parity_check_matrix::*;
 pub mod paulis;
 pub use paulis::*;
 
+pub mod pauli_string;
+pub use pauli_string::*;
+
+pub mod stabilizer_tableau;
+pub use stabilizer_tableau::*;
+
+pub mod spacetime_code;
+pub use spacetime_code::*;
+
 // mod sparse_matrix;
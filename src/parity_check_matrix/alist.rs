@@ -0,0 +1,223 @@
+//! Reading and writing parity check matrices in the alist format.
+//!
+//! The alist format of MacKay lists the number of columns (bits) and rows (checks), the maximal
+//! column and row weights, the weight of each column, the weight of each row, the rows of each
+//! column and the columns of each row. The indices start at 1 and the lists are padded with 0 up
+//! to the maximal weight.
+
+use super::ParityCheckMatrix;
+use std::fmt;
+
+/// The reasons why a string can't be parsed as an alist.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AlistParseError {
+    /// A value is not a non-negative integer.
+    InvalidNumber(String),
+    /// The string ends before all the values are read.
+    MissingValue,
+    /// A row or column index is 0 or larger than the number of rows or columns.
+    IndexOutOfBound(usize),
+    /// The columns listed for each row don't match the rows listed for each column.
+    InconsistentColumnsAndRows,
+}
+
+impl fmt::Display for AlistParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(value) => write!(f, "invalid number {}", value),
+            Self::MissingValue => write!(f, "missing value"),
+            Self::IndexOutOfBound(index) => write!(f, "index {} is out of bound", index),
+            Self::InconsistentColumnsAndRows => {
+                write!(f, "the columns and the rows are inconsistent")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AlistParseError {}
+
+impl ParityCheckMatrix {
+    /// Parses a matrix in the alist format where the columns are the bits and the rows are the
+    /// checks.
+    ///
+    /// The padding zeros are optional.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let alist = "3 2\n2 2\n1 2 1\n2 2\n1 0\n1 2\n2 0\n1 2\n2 3\n";
+    /// let matrix = ParityCheckMatrix::from_alist(alist).unwrap();
+    /// assert_eq!(matrix, ParityCheckMatrix::repetition_code(3));
+    /// ```
+    pub fn from_alist(alist: &str) -> Result<Self, AlistParseError> {
+        let mut values = AlistValues::from(alist);
+        let n_bits = values.next_number()?;
+        let n_checks = values.next_number()?;
+        values.next_number()?;
+        values.next_number()?;
+        let bit_degrees = values.next_numbers(n_bits)?;
+        let check_degrees = values.next_numbers(n_checks)?;
+        let checks_of_bits = values.next_lists(&bit_degrees, n_checks)?;
+        let bits_of_checks = values.next_lists(&check_degrees, n_bits)?;
+        let matrix = Self::with_n_bits(n_bits).with_checks(bits_of_checks);
+        let transposed = Self::with_n_bits(n_checks).with_checks(checks_of_bits);
+        if matrix.get_transposed_matrix() != transposed {
+            return Err(AlistParseError::InconsistentColumnsAndRows);
+        }
+        Ok(matrix)
+    }
+
+    /// Returns `self` in the alist format where the columns are the bits and the rows are the
+    /// checks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let matrix = ParityCheckMatrix::repetition_code(3);
+    /// assert_eq!(
+    ///     matrix.to_alist(),
+    ///     "3 2\n2 2\n1 2 1\n2 2\n1 0\n1 2\n2 0\n1 2\n2 3\n"
+    /// );
+    /// ```
+    pub fn to_alist(&self) -> String {
+        let transposed = self.get_transposed_matrix();
+        let bit_degrees = self.get_bit_degrees();
+        let check_degrees = self.get_check_degrees();
+        let max_bit_degree = bit_degrees.iter().max().cloned().unwrap_or(0);
+        let max_check_degree = check_degrees.iter().max().cloned().unwrap_or(0);
+        let mut lines = vec![
+            format!("{} {}", self.get_n_bits(), self.get_n_checks()),
+            format!("{} {}", max_bit_degree, max_check_degree),
+            join(bit_degrees.iter().cloned()),
+            join(check_degrees.iter().cloned()),
+        ];
+        lines.extend(
+            transposed
+                .checks_iter()
+                .map(|checks| join(padded(checks.iter(), max_bit_degree))),
+        );
+        lines.extend(
+            self.checks_iter()
+                .map(|bits| join(padded(bits.iter(), max_check_degree))),
+        );
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+}
+
+// The indices shifted to start at 1 followed by zeros up to `length`.
+fn padded<'a, I>(indices: I, length: usize) -> impl Iterator<Item = usize> + 'a
+where
+    I: Iterator<Item = &'a usize> + 'a,
+{
+    indices
+        .map(|index| index + 1)
+        .chain(std::iter::repeat(0))
+        .take(length)
+}
+
+fn join<I: Iterator<Item = usize>>(values: I) -> String {
+    values
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+struct AlistValues<'a> {
+    values: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> AlistValues<'a> {
+    fn from(alist: &'a str) -> Self {
+        Self {
+            values: alist.split_whitespace(),
+        }
+    }
+
+    fn next_number(&mut self) -> Result<usize, AlistParseError> {
+        let value = self.values.next().ok_or(AlistParseError::MissingValue)?;
+        value
+            .parse()
+            .map_err(|_| AlistParseError::InvalidNumber(value.to_string()))
+    }
+
+    fn next_numbers(&mut self, n_numbers: usize) -> Result<Vec<usize>, AlistParseError> {
+        (0..n_numbers).map(|_| self.next_number()).collect()
+    }
+
+    // Reads a list of `length` indices for each length skipping the padding zeros. The indices
+    // are shifted to start at 0.
+    fn next_lists(
+        &mut self,
+        lengths: &[usize],
+        n_indices: usize,
+    ) -> Result<Vec<Vec<usize>>, AlistParseError> {
+        lengths
+            .iter()
+            .map(|length| (0..*length).map(|_| self.next_index(n_indices)).collect())
+            .collect()
+    }
+
+    fn next_index(&mut self, n_indices: usize) -> Result<usize, AlistParseError> {
+        let mut index = self.next_number()?;
+        while index == 0 {
+            index = self.next_number()?;
+        }
+        if index > n_indices {
+            return Err(AlistParseError::IndexOutOfBound(index));
+        }
+        Ok(index - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alist_round_trip() {
+        let codes = vec![
+            ParityCheckMatrix::hamming_code(4),
+            ParityCheckMatrix::golay_code(),
+            ParityCheckMatrix::with_n_bits(5).with_checks(vec![vec![0, 4], vec![1, 2, 3, 4]]),
+        ];
+        for code in codes {
+            assert_eq!(
+                ParityCheckMatrix::from_alist(&code.to_alist()).unwrap(),
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn padding_zeros_are_optional() {
+        let alist = "3 2\n2 2\n1 2 1\n2 2\n1\n1 2\n2\n1 2\n2 3\n";
+        assert_eq!(
+            ParityCheckMatrix::from_alist(alist).unwrap(),
+            ParityCheckMatrix::repetition_code(3)
+        );
+    }
+
+    #[test]
+    fn invalid_alists_are_rejected() {
+        assert_eq!(
+            ParityCheckMatrix::from_alist("3 2\n2 2\n1 2 1\n2 2\n1 0\n1 2"),
+            Err(AlistParseError::MissingValue)
+        );
+        assert_eq!(
+            ParityCheckMatrix::from_alist("3 x"),
+            Err(AlistParseError::InvalidNumber(String::from("x")))
+        );
+        assert_eq!(
+            ParityCheckMatrix::from_alist("3 2\n2 2\n1 2 1\n2 2\n1 0\n1 3\n2 0\n1 2\n2 3\n"),
+            Err(AlistParseError::IndexOutOfBound(3))
+        );
+        assert_eq!(
+            ParityCheckMatrix::from_alist("3 2\n2 2\n1 2 1\n2 2\n1 0\n1 2\n2 0\n1 3\n2 3\n"),
+            Err(AlistParseError::InconsistentColumnsAndRows)
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Parity check matrices of standard classical codes.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::ParityCheckMatrix;
+//!
+//! let hamming = ParityCheckMatrix::hamming_code(3);
+//! assert_eq!(hamming.get_n_bits(), 7);
+//! assert_eq!(hamming.get_n_checks(), 3);
+//!
+//! // The [15, 7, 5] BCH code.
+//! let bch = ParityCheckMatrix::bch_code(4, 5);
+//! assert_eq!(bch.get_n_bits() - bch.get_rank(), 7);
+//!
+//! // The first order Reed-Muller code of length 16 has dimension 5.
+//! let reed_muller = ParityCheckMatrix::reed_muller_code(1, 4);
+//! assert_eq!(reed_muller.get_n_bits() - reed_muller.get_rank(), 5);
+//! ```
+
+use super::ParityCheckMatrix;
+use std::collections::BTreeSet;
+
+// Primitive polynomials of degree 2 to 16 in binary notation.
+const PRIMITIVE_POLYNOMIALS: [usize; 15] = [
+    0x7, 0xB, 0x13, 0x25, 0x43, 0x89, 0x11D, 0x211, 0x409, 0x805, 0x1053, 0x201B, 0x4443, 0x8003,
+    0x1100B,
+];
+
+// The generator polynomial of the binary Golay code.
+const GOLAY_GENERATOR: [usize; 7] = [0, 2, 4, 5, 6, 10, 11];
+
+impl ParityCheckMatrix {
+    /// Returns the parity check matrix of the repetition code over `n_bits`.
+    ///
+    /// Each check is the sum of two consecutive bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let checks = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+    /// assert_eq!(ParityCheckMatrix::repetition_code(3), checks);
+    /// ```
+    pub fn repetition_code(n_bits: usize) -> Self {
+        let checks = (1..n_bits).map(|bit| vec![bit - 1, bit]).collect();
+        Self::with_n_bits(n_bits).with_checks(checks)
+    }
+
+    /// Returns the parity check matrix of the single parity check code over `n_bits`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let checks = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1, 2]]);
+    /// assert_eq!(ParityCheckMatrix::single_parity_code(3), checks);
+    /// ```
+    pub fn single_parity_code(n_bits: usize) -> Self {
+        Self::with_n_bits(n_bits).with_checks(vec![(0..n_bits).collect()])
+    }
+
+    /// Returns the parity check matrix of the Hamming code with `r` checks.
+    ///
+    /// The code has `2^r - 1` bits and the column of bit `b` is the binary representation of
+    /// `b + 1`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `r` is 0.
+    pub fn hamming_code(r: usize) -> Self {
+        if r == 0 {
+            panic!("hamming codes need at least one check");
+        }
+        let n_bits = (1 << r) - 1;
+        let checks = (0..r)
+            .map(|check| {
+                (0..n_bits)
+                    .filter(|bit| (bit + 1) >> check & 1 == 1)
+                    .collect()
+            })
+            .collect();
+        Self::with_n_bits(n_bits).with_checks(checks)
+    }
+
+    /// Returns the parity check matrix of the extended Hamming code with `2^r` bits.
+    ///
+    /// This is the Hamming code with `r` checks and an additional overall parity bit.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `r` is 0.
+    pub fn extended_hamming_code(r: usize) -> Self {
+        let hamming = Self::hamming_code(r);
+        let mut checks: Vec<Vec<usize>> =
+            hamming.checks_iter().map(|check| check.to_vec()).collect();
+        checks.push((0..=hamming.get_n_bits()).collect());
+        Self::with_n_bits(hamming.get_n_bits() + 1).with_checks(checks)
+    }
+
+    /// Returns the parity check matrix of the narrow-sense primitive binary BCH code of length
+    /// `2^m - 1` with the given `designed_distance`.
+    ///
+    /// The checks are the cyclic shifts of the reciprocal check polynomial. Thus, they are
+    /// independent.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `m` is not between 2 and 16.
+    pub fn bch_code(m: usize, designed_distance: usize) -> Self {
+        if !(2..=16).contains(&m) {
+            panic!("bch codes are only available for m between 2 and 16");
+        }
+        let field = BinaryExtensionField::with_degree(m);
+        let n_bits = field.get_order() - 1;
+        let mut roots = BTreeSet::new();
+        for power in 1..designed_distance.max(1) {
+            let mut conjugate = power % n_bits;
+            while roots.insert(conjugate) {
+                conjugate = (2 * conjugate) % n_bits;
+            }
+        }
+        let generator = field.get_polynomial_with_roots(&roots);
+        cyclic_code_from_generator(&generator, n_bits)
+    }
+
+    /// Returns the parity check matrix of the Reed-Muller code RM(`r`, `m`).
+    ///
+    /// The checks are the generators of the dual code RM(`m - r - 1`, `m`). That is, the
+    /// evaluations of all monomials of degree at most `m - r - 1` on the `2^m` points of the
+    /// binary space where bit `b` is the point with binary representation `b`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `r` is larger than `m`.
+    pub fn reed_muller_code(r: usize, m: usize) -> Self {
+        if r > m {
+            panic!("reed muller codes need r to be at most m");
+        }
+        let n_bits: usize = 1 << m;
+        let dual_order = m - r;
+        let checks = (0..n_bits)
+            .filter(|monomial| (monomial.count_ones() as usize) < dual_order)
+            .map(|monomial| {
+                (0..n_bits)
+                    .filter(|point| point & monomial == monomial)
+                    .collect()
+            })
+            .collect();
+        Self::with_n_bits(n_bits).with_checks(checks)
+    }
+
+    /// Returns the parity check matrix of the [23, 12, 7] binary Golay code.
+    pub fn golay_code() -> Self {
+        let mut generator = vec![false; 12];
+        GOLAY_GENERATOR
+            .iter()
+            .for_each(|power| generator[*power] = true);
+        cyclic_code_from_generator(&generator, 23)
+    }
+
+    /// Returns the parity check matrix of the [24, 12, 8] extended binary Golay code.
+    pub fn extended_golay_code() -> Self {
+        let golay = Self::golay_code();
+        let mut checks: Vec<Vec<usize>> = golay.checks_iter().map(|check| check.to_vec()).collect();
+        checks.push((0..24).collect());
+        Self::with_n_bits(24).with_checks(checks)
+    }
+}
+
+// Returns the parity check matrix of the cyclic code of length `n_bits` with the given generator
+// polynomial. Polynomials are given by their coefficients from the constant term.
+fn cyclic_code_from_generator(generator: &[bool], n_bits: usize) -> ParityCheckMatrix {
+    let check_polynomial = divide_x_to_the_n_minus_one_by(generator, n_bits);
+    let dimension = check_polynomial.len() - 1;
+    let checks = (0..n_bits - dimension)
+        .map(|shift| {
+            (0..=dimension)
+                .filter(|power| check_polynomial[dimension - power])
+                .map(|power| power + shift)
+                .collect()
+        })
+        .collect();
+    ParityCheckMatrix::with_n_bits(n_bits).with_checks(checks)
+}
+
+// Returns the quotient of x^n - 1 by `divisor` over GF(2).
+fn divide_x_to_the_n_minus_one_by(divisor: &[bool], n: usize) -> Vec<bool> {
+    let divisor_degree = divisor.len() - 1;
+    let mut remainder = vec![false; n + 1];
+    remainder[0] = true;
+    remainder[n] = true;
+    let mut quotient = vec![false; n - divisor_degree + 1];
+    for power in (0..quotient.len()).rev() {
+        if remainder[power + divisor_degree] {
+            quotient[power] = true;
+            divisor
+                .iter()
+                .enumerate()
+                .filter(|(_, coefficient)| **coefficient)
+                .for_each(|(index, _)| remainder[power + index] ^= true);
+        }
+    }
+    quotient
+}
+
+// The field with 2^m elements represented with log and exponential tables.
+struct BinaryExtensionField {
+    exponentials: Vec<usize>,
+    logarithms: Vec<usize>,
+}
+
+impl BinaryExtensionField {
+    fn with_degree(m: usize) -> Self {
+        let order = 1 << m;
+        let primitive_polynomial = PRIMITIVE_POLYNOMIALS[m - 2];
+        let mut exponentials = Vec::with_capacity(order - 1);
+        let mut logarithms = vec![0; order];
+        let mut element = 1;
+        for power in 0..order - 1 {
+            exponentials.push(element);
+            logarithms[element] = power;
+            element <<= 1;
+            if element & order != 0 {
+                element ^= primitive_polynomial;
+            }
+        }
+        Self {
+            exponentials,
+            logarithms,
+        }
+    }
+
+    fn get_order(&self) -> usize {
+        self.logarithms.len()
+    }
+
+    fn multiply(&self, a: usize, b: usize) -> usize {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            let power = (self.logarithms[a] + self.logarithms[b]) % self.exponentials.len();
+            self.exponentials[power]
+        }
+    }
+
+    // Returns the product of (x - a^root) for all roots. The roots must be a union of cyclotomic
+    // cosets for the coefficients to be binary.
+    fn get_polynomial_with_roots(&self, roots: &BTreeSet<usize>) -> Vec<bool> {
+        let mut polynomial = vec![1];
+        for root in roots.iter() {
+            let root = self.exponentials[*root];
+            let mut product = vec![0; polynomial.len() + 1];
+            for (power, coefficient) in polynomial.iter().enumerate() {
+                product[power + 1] ^= coefficient;
+                product[power] ^= self.multiply(*coefficient, root);
+            }
+            polynomial = product;
+        }
+        polynomial
+            .into_iter()
+            .map(|coefficient| coefficient == 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use itertools::Itertools;
+
+    fn get_dimension(code: &ParityCheckMatrix) -> usize {
+        code.get_n_bits() - code.get_rank()
+    }
+
+    // Checks that every set of `distance - 1` columns is independent.
+    fn has_distance_at_least(code: &ParityCheckMatrix, distance: usize) -> bool {
+        (0..code.get_n_bits())
+            .combinations(distance - 1)
+            .all(|bits| code.keep(&bits).get_rank() == distance - 1)
+    }
+
+    fn has_codeword_of_weight(code: &ParityCheckMatrix, weight: usize) -> bool {
+        (0..code.get_n_bits()).combinations(weight).any(|bits| {
+            code.checks_iter()
+                .all(|check| check.iter().filter(|bit| bits.contains(bit)).count() % 2 == 0)
+        })
+    }
+
+    #[test]
+    fn repetition_and_single_parity_codes() {
+        let repetition = ParityCheckMatrix::repetition_code(5);
+        assert_eq!(get_dimension(&repetition), 1);
+        assert!(has_distance_at_least(&repetition, 5));
+
+        let single_parity = ParityCheckMatrix::single_parity_code(5);
+        assert_eq!(get_dimension(&single_parity), 4);
+        assert!(has_codeword_of_weight(&single_parity, 2));
+    }
+
+    #[test]
+    fn hamming_codes() {
+        for r in 2..6 {
+            let code = ParityCheckMatrix::hamming_code(r);
+            assert_eq!(code.get_n_bits(), (1 << r) - 1);
+            assert_eq!(get_dimension(&code), (1 << r) - 1 - r);
+        }
+        let code = ParityCheckMatrix::hamming_code(3);
+        assert!(has_distance_at_least(&code, 3));
+        assert!(has_codeword_of_weight(&code, 3));
+
+        let extended = ParityCheckMatrix::extended_hamming_code(3);
+        assert_eq!(extended.get_n_bits(), 8);
+        assert_eq!(get_dimension(&extended), 4);
+        assert!(has_distance_at_least(&extended, 4));
+    }
+
+    #[test]
+    fn bch_codes() {
+        // [15, 11, 3], [15, 7, 5] and [15, 5, 7] codes.
+        for &(designed_distance, dimension) in &[(3, 11), (5, 7), (7, 5)] {
+            let code = ParityCheckMatrix::bch_code(4, designed_distance);
+            assert_eq!(code.get_n_bits(), 15);
+            assert_eq!(code.get_n_checks(), 15 - dimension);
+            assert_eq!(get_dimension(&code), dimension);
+        }
+        assert!(has_distance_at_least(&ParityCheckMatrix::bch_code(4, 5), 5));
+
+        // [31, 21, 5] code.
+        assert_eq!(get_dimension(&ParityCheckMatrix::bch_code(5, 5)), 21);
+    }
+
+    #[test]
+    fn bch_code_with_designed_distance_3_is_a_hamming_code() {
+        let code = ParityCheckMatrix::bch_code(3, 3);
+        assert_eq!(get_dimension(&code), 4);
+        assert!(has_distance_at_least(&code, 3));
+    }
+
+    #[test]
+    fn reed_muller_codes() {
+        let code = ParityCheckMatrix::reed_muller_code(1, 4);
+        assert_eq!(code.get_n_bits(), 16);
+        assert_eq!(get_dimension(&code), 5);
+        assert!(has_distance_at_least(&code, 8));
+
+        assert_eq!(
+            get_dimension(&ParityCheckMatrix::reed_muller_code(2, 5)),
+            16
+        );
+        assert_eq!(
+            ParityCheckMatrix::reed_muller_code(2, 3),
+            ParityCheckMatrix::single_parity_code(8)
+        );
+        assert_eq!(ParityCheckMatrix::reed_muller_code(3, 3).get_n_checks(), 0);
+    }
+
+    #[test]
+    fn golay_codes() {
+        let code = ParityCheckMatrix::golay_code();
+        assert_eq!(code.get_n_bits(), 23);
+        assert_eq!(get_dimension(&code), 12);
+        assert!(has_distance_at_least(&code, 7));
+
+        let extended = ParityCheckMatrix::extended_golay_code();
+        assert_eq!(extended.get_n_bits(), 24);
+        assert_eq!(get_dimension(&extended), 12);
+    }
+}
@@ -0,0 +1,105 @@
+use super::ParityCheckMatrix;
+use std::collections::VecDeque;
+
+// A tool to find the length of the shortest cycle of the Tanner graph of a parity check matrix.
+//
+// The nodes are the bits followed by the checks. Since every cycle goes through some bit, the
+// girth is the minimum over all bits of the shortest cycle found by a breadth-first search
+// starting from that bit.
+pub(super) struct GirthFinder {
+    neighbors: Vec<Vec<usize>>,
+    n_bits: usize,
+}
+
+impl GirthFinder {
+    pub(super) fn from(matrix: &ParityCheckMatrix) -> Self {
+        let n_bits = matrix.get_n_bits();
+        let mut neighbors = vec![Vec::new(); n_bits + matrix.get_n_checks()];
+        for (check, bits) in matrix.checks_iter().enumerate() {
+            for bit in bits.iter() {
+                neighbors[*bit].push(n_bits + check);
+                neighbors[n_bits + check].push(*bit);
+            }
+        }
+        Self { neighbors, n_bits }
+    }
+
+    pub(super) fn get_girth(&self) -> Option<usize> {
+        (0..self.n_bits).fold(None, |girth, bit| {
+            match (girth, self.get_shortest_cycle_from(bit, girth)) {
+                (Some(girth), Some(cycle)) => Some(usize::min(girth, cycle)),
+                (girth, cycle) => girth.or(cycle),
+            }
+        })
+    }
+
+    // Returns the length of the shortest cycle found from `root` if it is shorter than `bound`.
+    fn get_shortest_cycle_from(&self, root: usize, bound: Option<usize>) -> Option<usize> {
+        let mut distances = vec![None; self.neighbors.len()];
+        let mut parents = vec![None; self.neighbors.len()];
+        let mut queue = VecDeque::new();
+        distances[root] = Some(0);
+        queue.push_back(root);
+        let mut shortest = bound;
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].unwrap();
+            if shortest.is_some_and(|shortest| 2 * distance + 1 >= shortest) {
+                break;
+            }
+            for neighbor in self.neighbors[node].iter() {
+                match distances[*neighbor] {
+                    None => {
+                        distances[*neighbor] = Some(distance + 1);
+                        parents[*neighbor] = Some(node);
+                        queue.push_back(*neighbor);
+                    }
+                    Some(other_distance) if parents[node] != Some(*neighbor) => {
+                        let cycle = distance + other_distance + 1;
+                        if shortest.is_none() || Some(cycle) < shortest {
+                            shortest = Some(cycle);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        shortest.filter(|shortest| bound.is_none() || Some(*shortest) < bound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn girth_of_a_ring() {
+        for n_bits in 3..8 {
+            let checks = (0..n_bits)
+                .map(|bit| vec![bit, (bit + 1) % n_bits])
+                .collect();
+            let matrix = ParityCheckMatrix::with_n_bits(n_bits).with_checks(checks);
+            assert_eq!(matrix.get_girth(), Some(2 * n_bits));
+        }
+    }
+
+    #[test]
+    fn girth_of_classical_codes() {
+        assert_eq!(ParityCheckMatrix::hamming_code(3).get_girth(), Some(4));
+        assert_eq!(ParityCheckMatrix::single_parity_code(4).get_girth(), None);
+        assert_eq!(ParityCheckMatrix::with_n_bits(3).get_girth(), None);
+    }
+
+    #[test]
+    fn girth_is_the_shortest_of_many_cycles() {
+        // A 4-cycle through bits 4 and 5 and a 6-cycle through bits 0, 1 and 2.
+        let checks = vec![
+            vec![0, 1],
+            vec![1, 2],
+            vec![0, 2, 3],
+            vec![3, 4, 5],
+            vec![4, 5],
+        ];
+        let matrix = ParityCheckMatrix::with_n_bits(6).with_checks(checks);
+        assert_eq!(matrix.get_girth(), Some(4));
+    }
+}
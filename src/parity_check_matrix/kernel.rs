@@ -0,0 +1,102 @@
+//! Codewords of a parity check matrix.
+
+use super::ParityCheckMatrix;
+
+impl ParityCheckMatrix {
+    /// Returns a basis of the codewords of `self`. That is, a basis of the kernel of `self`.
+    ///
+    /// Each codeword is given by the positions of its non-zero bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let basis = ParityCheckMatrix::repetition_code(3).get_codeword_basis();
+    /// assert_eq!(basis, vec![vec![0, 1, 2]]);
+    ///
+    /// let basis = ParityCheckMatrix::hamming_code(3).get_codeword_basis();
+    /// assert_eq!(basis.len(), 4);
+    /// ```
+    pub fn get_codeword_basis(&self) -> Vec<Vec<usize>> {
+        let n_bits = self.get_n_bits();
+        let mut rows: Vec<Vec<bool>> = self
+            .checks_iter()
+            .map(|check| {
+                let mut row = vec![false; n_bits];
+                check.iter().for_each(|bit| row[*bit] = true);
+                row
+            })
+            .collect();
+
+        // Reduced row echelon form.
+        let mut pivots = Vec::new();
+        for column in 0..n_bits {
+            let rank = pivots.len();
+            if let Some(pivot) = (rank..rows.len()).find(|row| rows[*row][column]) {
+                rows.swap(rank, pivot);
+                for row in 0..rows.len() {
+                    if row != rank && rows[row][column] {
+                        let pivot_row = rows[rank].clone();
+                        rows[row]
+                            .iter_mut()
+                            .zip(pivot_row.iter())
+                            .for_each(|(bit, pivot_bit)| *bit ^= pivot_bit);
+                    }
+                }
+                pivots.push(column);
+            }
+        }
+
+        // Each free column gives a codeword.
+        let mut is_pivot = vec![false; n_bits];
+        pivots.iter().for_each(|column| is_pivot[*column] = true);
+        (0..n_bits)
+            .filter(|column| !is_pivot[*column])
+            .map(|free_column| {
+                let mut codeword: Vec<usize> = pivots
+                    .iter()
+                    .enumerate()
+                    .filter(|(row, _)| rows[*row][free_column])
+                    .map(|(_, pivot)| *pivot)
+                    .collect();
+                codeword.push(free_column);
+                codeword.sort_unstable();
+                codeword
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::GF2;
+
+    #[test]
+    fn basis_vectors_are_independent_codewords() {
+        let code = ParityCheckMatrix::bch_code(4, 5);
+        let basis = code.get_codeword_basis();
+        assert_eq!(basis.len(), 7);
+        for codeword in basis.iter() {
+            let message: Vec<GF2> = (0..15)
+                .map(|bit| {
+                    if codeword.contains(&bit) {
+                        GF2::B1
+                    } else {
+                        GF2::B0
+                    }
+                })
+                .collect();
+            assert!(code.has_codeword(&message));
+        }
+        let basis_matrix = ParityCheckMatrix::with_n_bits(15).with_checks(basis);
+        assert_eq!(basis_matrix.get_rank(), 7);
+    }
+
+    #[test]
+    fn empty_matrix_has_all_unit_vectors() {
+        let code = ParityCheckMatrix::with_n_bits(2);
+        assert_eq!(code.get_codeword_basis(), vec![vec![0], vec![1]]);
+    }
+}
@@ -1,6 +1,10 @@
 //! A sparse implementation of a parity check matrix.
 
 use crate::GF2;
+use serde::{Deserialize, Serialize};
+
+pub mod alist;
+pub use alist::AlistParseError;
 
 pub mod check;
 use check::get_bitwise_sum;
@@ -12,11 +16,17 @@ pub use check_view::CheckView;
 pub mod checks_iter;
 pub use checks_iter::ChecksIter;
 
+mod classical_codes;
+
+mod kernel;
+
 pub mod edges_iter;
 pub use edges_iter::EdgesIter;
 
 mod ranker;
-use ranker::Ranker;
+
+mod girth;
+use girth::GirthFinder;
 
 mod transposer;
 use transposer::Transposer;
@@ -24,8 +34,17 @@ use transposer::Transposer;
 mod concatener;
 use concatener::Concatener;
 
+mod serialization;
+use serialization::SerializedParityCheckMatrix;
+
 /// A sparse implementation of a parity check matrix.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// A matrix is serialized as its number of bits and the list of the bits of each check.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(
+    try_from = "SerializedParityCheckMatrix",
+    into = "SerializedParityCheckMatrix"
+)]
 pub struct ParityCheckMatrix {
     check_ranges: Vec<usize>,
     bit_indices: Vec<usize>,
@@ -63,7 +82,7 @@ impl ParityCheckMatrix {
         Self {
             check_ranges: Vec::new(),
             bit_indices: Vec::new(),
-            n_bits: n_bits,
+            n_bits,
         }
     }
 
@@ -166,7 +185,7 @@ impl ParityCheckMatrix {
 
     /// Returns the number of checks in `self`.
     pub fn get_n_checks(&self) -> usize {
-        if self.check_ranges().len() > 0 {
+        if !self.check_ranges().is_empty() {
             self.check_ranges().len() - 1
         } else {
             0
@@ -231,7 +250,7 @@ impl ParityCheckMatrix {
     ///
     /// assert!(parity_check.get_check(2).is_none());
     /// ```
-    pub fn get_check(&self, check: usize) -> Option<CheckView> {
+    pub fn get_check(&self, check: usize) -> Option<CheckView<'_>> {
         self.check_ranges.get(check).and_then(|&check_start| {
             self.check_ranges
                 .get(check + 1)
@@ -275,6 +294,24 @@ impl ParityCheckMatrix {
         self.rank()
     }
 
+    /// Returns the length of the shortest cycle of the Tanner graph of `self` or `None` if the
+    /// graph has no cycle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use believer::ParityCheckMatrix;
+    ///
+    /// let checks = vec![vec![0, 1, 2], vec![1, 2, 3]];
+    /// let matrix = ParityCheckMatrix::with_n_bits(4).with_checks(checks);
+    /// assert_eq!(matrix.get_girth(), Some(4));
+    ///
+    /// assert_eq!(ParityCheckMatrix::repetition_code(5).get_girth(), None);
+    /// ```
+    pub fn get_girth(&self) -> Option<usize> {
+        GirthFinder::from(self).get_girth()
+    }
+
     pub fn rank(&self) -> usize{
         
         let n_cols = self.n_bits;
@@ -293,11 +330,11 @@ impl ParityCheckMatrix {
 
             for i in 0..n_rows { 
 
-                if tmp_matrix[i].len() > 0 && tmp_matrix[i][0] == j    { // select a NEW pivot
+                if !tmp_matrix[i].is_empty() && tmp_matrix[i][0] == j    { // select a NEW pivot
 
                     for k in 0..i {
 
-                        if  tmp_matrix[k].len() > 0 && tmp_matrix[k][0] == j  {
+                        if  !tmp_matrix[k].is_empty() && tmp_matrix[k][0] == j  {
 
                             tmp_matrix[k] = add_checks(&tmp_matrix[i],&tmp_matrix[k]);
 
@@ -306,7 +343,7 @@ impl ParityCheckMatrix {
                     }
                     for k in (i+1)..n_rows {
 
-                        if tmp_matrix[k].len() > 0 && tmp_matrix[k][0] == j   {
+                        if !tmp_matrix[k].is_empty() && tmp_matrix[k][0] == j   {
 
                             tmp_matrix[k] = add_checks(&tmp_matrix[i],&tmp_matrix[k]);
 
@@ -315,7 +352,7 @@ impl ParityCheckMatrix {
 
                     }
 
-                    unsafe {tmp_matrix[i].set_len(0)};
+                    tmp_matrix[i].clear();
                     rank += 1;
 
                     break
@@ -343,24 +380,19 @@ impl ParityCheckMatrix {
         tmp_matrix
     }
 
-    pub fn init_rank_tmp(&self, tmp_matrix: &mut Vec<Vec<usize>>) {
+    pub fn init_rank_tmp(&self, tmp_matrix: &mut [Vec<usize>]) {
 
-        for i in 0..tmp_matrix.len() {
+        for (i, tmp_row) in tmp_matrix.iter_mut().enumerate() {
             let new_row = &self.bit_indices[self.check_ranges[i]..self.check_ranges[i+1]];
 
-            tmp_matrix[i].clear();
-            
-            for j in 0..new_row.len() {
-                tmp_matrix[i].push(new_row[j]);
-            }
-
-
+            tmp_row.clear();
+            tmp_row.extend_from_slice(new_row);
         }
 
     }
 
 
-    pub fn rank_mut(&self, tmp_matrix: &mut Vec<Vec<usize>>, tmp_sum: &mut Vec<usize>) -> usize{
+    pub fn rank_mut(&self, tmp_matrix: &mut [Vec<usize>], tmp_sum: &mut Vec<usize>) -> usize{
         
         let n_cols = self.n_bits;
         let n_rows = self.get_n_checks();
@@ -372,11 +404,11 @@ impl ParityCheckMatrix {
 
             for i in 0..n_rows { 
 
-                if tmp_matrix[i].len() > 0 && tmp_matrix[i][0] == j    { // select a NEW pivot
+                if !tmp_matrix[i].is_empty() && tmp_matrix[i][0] == j    { // select a NEW pivot
 
                     for k in 0..i {
 
-                        if  tmp_matrix[k].len() > 0 && tmp_matrix[k][0] == j  {
+                        if  !tmp_matrix[k].is_empty() && tmp_matrix[k][0] == j  {
                             
                             add_checks_mut(&tmp_matrix[i], &tmp_matrix[k],tmp_sum);
                             transfer_to(tmp_sum, &mut tmp_matrix[k]);
@@ -388,7 +420,7 @@ impl ParityCheckMatrix {
                     }
                     for k in (i+1)..n_rows {
 
-                        if tmp_matrix[k].len() > 0 && tmp_matrix[k][0] == j   {
+                        if !tmp_matrix[k].is_empty() && tmp_matrix[k][0] == j   {
 
                             add_checks_mut(&tmp_matrix[i], &tmp_matrix[k],tmp_sum);
                             transfer_to(tmp_sum, &mut tmp_matrix[k]);
@@ -399,7 +431,7 @@ impl ParityCheckMatrix {
 
                     }
 
-                    unsafe {tmp_matrix[i].set_len(0)};
+                    tmp_matrix[i].clear();
                     rank += 1;
 
                     break
@@ -496,7 +528,7 @@ impl ParityCheckMatrix {
     /// assert_eq!(iter.next(), parity_check.get_check(1));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn checks_iter(&self) -> ChecksIter {
+    pub fn checks_iter(&self) -> ChecksIter<'_> {
         ChecksIter::from(self)
     }
 
@@ -517,7 +549,7 @@ impl ParityCheckMatrix {
     /// assert_eq!(iter.next(), Some((1, 2)));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn edges_iter(&self) -> EdgesIter {
+    pub fn edges_iter(&self) -> EdgesIter<'_> {
         EdgesIter::from(self)
     }
 
@@ -592,13 +624,12 @@ impl ParityCheckMatrix {
 
                     //println!("binary search, bits:{:?}, bit:{}, res:{}",bits, bit + (self.get_n_bits()/2), binary_search(bits, &(bit + (self.get_n_bits()/2))));
 
-                    let mut found = false;
                     //println!("binary search, bits:{:?}, bit:{}, res:{}",bits, bit + (self.get_n_bits()/2), binary_search(bits, &(bit + (self.get_n_bits()/2))));
-                    if bit < &(self.get_n_bits()/2) {
-                        found = binary_search(bits, bit);
+                    let found = if bit < &(self.get_n_bits()/2) {
+                        binary_search(bits, bit)
                     } else {
-                        found = binary_search(bits, &(bit - (self.get_n_bits()/2)));// do we found the check in either the Z part or the X part. Both have width n_bits/2
-                    }
+                        binary_search(bits, &(bit - (self.get_n_bits()/2)))// do we found the check in either the Z part or the X part. Both have width n_bits/2
+                    };
                     
                     if found {
                         target.bit_indices.push(*bit);
@@ -630,13 +661,12 @@ impl ParityCheckMatrix {
                 .iter()
                 .filter(|&bit| {
 
-                    let mut found = false;
                     //println!("binary search, bits:{:?}, bit:{}, res:{}",bits, bit + (self.get_n_bits()/2), binary_search(bits, &(bit + (self.get_n_bits()/2))));
-                    if bit < &(self.get_n_bits()/2) {
-                        found = !binary_search(bits, bit);
+                    let found = if bit < &(self.get_n_bits()/2) {
+                        !binary_search(bits, bit)
                     } else {
-                        found = !binary_search(bits, &(bit - (self.get_n_bits()/2)));// do we found the check in either the Z part or the X part. Both have width n_bits/2
-                    }
+                        !binary_search(bits, &(bit - (self.get_n_bits()/2)))// do we found the check in either the Z part or the X part. Both have width n_bits/2
+                    };
 
                     if found {
                         target.bit_indices.push(*bit);
@@ -681,9 +711,33 @@ impl ParityCheckMatrix {
         self.keep(&to_keep)
     }
 
+    /// Returns a copy of `self` without the checks that have no bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use believer::*;
+    /// let checks = ParityCheckMatrix::with_n_bits(3).with_checks(vec![
+    ///     vec![0, 1],
+    ///     vec![],
+    ///     vec![1, 2],
+    /// ]);
+    /// let expected = ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0, 1], vec![1, 2]]);
+    ///
+    /// assert_eq!(checks.without_empty_checks(), expected);
+    /// ```
+    pub fn without_empty_checks(&self) -> Self {
+        let checks = self
+            .checks_iter()
+            .filter(|check| check.get_n_bits() > 0)
+            .map(|check| check.to_vec())
+            .collect();
+        Self::with_n_bits(self.get_n_bits()).with_checks(checks)
+    }
+
     pub fn gbc(&self, b: &ParityCheckMatrix) -> ParityCheckMatrix {
         // should check that A and B commute and that Hx*Hz^T = 0
-        let hx = self.get_horizontal_concat_with(&b);
+        let hx = self.get_horizontal_concat_with(b);
         let hz = b
             .get_transposed_matrix()
             .get_horizontal_concat_with(&self.get_transposed_matrix());
@@ -703,13 +757,13 @@ impl ParityCheckMatrix {
                 bit_indices.push((j+i)%l);
 
             }
-            (&mut bit_indices[i*w..i*w + w_a]).sort_unstable();
+            bit_indices[i*w..i*w + w_a].sort_unstable();
             for j in b {
 
                 bit_indices.push((j+i)%l + l);
 
             }
-            (&mut bit_indices[i*w + w_a..(i+1)*w]).sort_unstable();
+            bit_indices[i*w + w_a..(i+1)*w].sort_unstable();
         }
         for i in l..2*l {
             for j in b {
@@ -717,13 +771,13 @@ impl ParityCheckMatrix {
                 bit_indices.push((l-j+i)%l + 2*l);
 
             }
-            (&mut bit_indices[i*w..i*w + w_b]).sort_unstable();
+            bit_indices[i*w..i*w + w_b].sort_unstable();
             for j in a {
 
                 bit_indices.push((l-j+i)%l + 3*l);
 
             }
-            (&mut bit_indices[i*w + w_b..(i+1)*w]).sort_unstable();
+            bit_indices[i*w + w_b..(i+1)*w].sort_unstable();
             
         }
 
@@ -796,11 +850,8 @@ impl ParityCheckMatrix {
 }
 
 fn transfer_to(v1: &[usize], v2: &mut Vec<usize>){
-
-    unsafe{ v2.set_len(v1.len())}
-    for i in 0..v1.len() {
-        v2[i] = v1[i];
-    }
+    v2.clear();
+    v2.extend_from_slice(v1);
 }
 
 pub fn add_checks_mut(check_0: &[usize], check_1: &[usize], sum: &mut Vec<usize>){
@@ -839,7 +890,7 @@ pub fn binary_search(data: &[usize], target: &usize) -> bool {
 
     // println!("data:{:?}",data);
     // println!("target:{:?}",target);
-    if data.len() > 0 {
+    if !data.is_empty() {
 
         let mut high = data.len()-1;
         let mut low = 0;
@@ -860,9 +911,9 @@ pub fn binary_search(data: &[usize], target: &usize) -> bool {
             }
 
         }
-        return false
+        false
     } else {
-        return false
+        false
     }
 }
 
@@ -898,6 +949,12 @@ pub fn add_checks(check_0: &[usize], check_1: &[usize]) -> Vec<usize> {
     sum
 }
 
+impl Default for ParityCheckMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::fmt::Display for ParityCheckMatrix {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for check in self.checks_iter() {
@@ -926,13 +983,16 @@ mod test {
     }
 
     #[test]
-    fn empty_checks_are_removed_on_construction() {
+    fn empty_checks_are_kept_on_construction() {
+        // Empty checks are kept so that the x and z parts of stabilizers stay aligned.
         let checks = vec![vec![], vec![0, 1], vec![], vec![1, 2]];
         let matrix = ParityCheckMatrix::with_n_bits(3).with_checks(checks);
 
-        assert_eq!(matrix.get_check(0).unwrap().as_ref(), &[0, 1]);
-        assert_eq!(matrix.get_check(1).unwrap().as_ref(), &[1, 2]);
-        assert_eq!(matrix.get_n_checks(), 2);
+        assert!(matrix.get_check(0).unwrap().as_ref().is_empty());
+        assert_eq!(matrix.get_check(1).unwrap().as_ref(), &[0, 1]);
+        assert!(matrix.get_check(2).unwrap().as_ref().is_empty());
+        assert_eq!(matrix.get_check(3).unwrap().as_ref(), &[1, 2]);
+        assert_eq!(matrix.get_n_checks(), 4);
     }
 
     #[test]
@@ -1,8 +1,11 @@
 use super::get_bitwise_sum;
 use super::ParityCheckMatrix;
 
+// Superseded by `ParityCheckMatrix::rank`, but kept as a readable reference implementation.
+#[allow(dead_code)]
 type Row = Vec<usize>;
 
+#[allow(dead_code)]
 pub(super) struct Ranker {
     n_columns: usize,
     n_rows: usize,
@@ -11,6 +14,7 @@ pub(super) struct Ranker {
     rows: Vec<Row>,
 }
 
+#[allow(dead_code)]
 impl Ranker {
     pub(super) fn from_parity_check_matrix(matrix: &ParityCheckMatrix) -> Self {
         Self {
@@ -59,7 +63,7 @@ impl Ranker {
     }
 
     fn get_and_remove_row_at_index(&mut self, index: usize) -> Row {
-        std::mem::replace(&mut self.rows[index], Vec::new())
+        std::mem::take(&mut self.rows[index])
     }
 
     fn pivot_rows_that_start_in_active_column_with(&mut self, pivot: Row) {
@@ -0,0 +1,64 @@
+use super::{Check, ParityCheckMatrix};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+// The serialized form of a parity check matrix, listing the bits of each check.
+#[derive(Serialize, Deserialize)]
+pub(super) struct SerializedParityCheckMatrix {
+    n_bits: usize,
+    checks: Vec<Check>,
+}
+
+impl From<ParityCheckMatrix> for SerializedParityCheckMatrix {
+    fn from(matrix: ParityCheckMatrix) -> Self {
+        Self {
+            n_bits: matrix.get_n_bits(),
+            checks: matrix.checks_iter().map(|check| check.to_vec()).collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedParityCheckMatrix> for ParityCheckMatrix {
+    type Error = String;
+
+    fn try_from(matrix: SerializedParityCheckMatrix) -> Result<Self, Self::Error> {
+        let is_out_of_bounds = matrix
+            .checks
+            .iter()
+            .flatten()
+            .any(|bit| *bit >= matrix.n_bits);
+        if is_out_of_bounds {
+            Err(String::from("some checks are out of bounds"))
+        } else {
+            Ok(ParityCheckMatrix::with_n_bits(matrix.n_bits).with_checks(matrix.checks))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let matrix = ParityCheckMatrix::hamming_code(3);
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ParityCheckMatrix>(&json).unwrap(),
+            matrix
+        );
+
+        let empty = ParityCheckMatrix::with_n_bits(4);
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ParityCheckMatrix>(&json).unwrap(),
+            empty
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_checks_are_rejected() {
+        let json = r#"{"n_bits":3,"checks":[[0,1],[2,3]]}"#;
+        assert!(serde_json::from_str::<ParityCheckMatrix>(json).is_err());
+    }
+}
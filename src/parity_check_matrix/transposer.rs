@@ -82,6 +82,7 @@ mod test {
             vec![0, 2],
             vec![0, 2],
             vec![1, 2],
+            vec![],
             vec![0, 1],
         ]);
 
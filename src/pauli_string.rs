@@ -0,0 +1,564 @@
+//! Multi-qubit Pauli operators with a global phase.
+//!
+//! A `PauliString` is a tensor product of single qubit `Pauli` operators multiplied by a phase in
+//! `{1, i, -1, -i}`. Each `Pauli::Y` is the hermitian Y operator.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{PauliString, Phase};
+//!
+//! let a: PauliString = "XZZXI".parse().unwrap();
+//! let b: PauliString = "Y0 Z4".parse().unwrap();
+//!
+//! assert_eq!(a.weight(), 4);
+//! assert_eq!(b.support(), vec![0, 4]);
+//! assert!(!a.commutes_with(&b.extended_to(5)));
+//!
+//! let product = &a * &b.extended_to(5);
+//! assert_eq!(product.get_phase(), Phase::I);
+//! assert_eq!(product.to_string(), "iZZZXZ");
+//! ```
+
+use crate::{Pauli, GF2};
+use std::fmt;
+use std::ops::Mul;
+use std::str::FromStr;
+
+/// A global phase in `{1, i, -1, -i}`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Phase {
+    One,
+    I,
+    MinusOne,
+    MinusI,
+}
+
+impl Phase {
+    /// Returns the phase `i^power`.
+    pub fn from_power_of_i(power: usize) -> Self {
+        match power % 4 {
+            0 => Self::One,
+            1 => Self::I,
+            2 => Self::MinusOne,
+            _ => Self::MinusI,
+        }
+    }
+
+    /// Returns the power `p` in `0..4` such that `self` is `i^p`.
+    pub fn power_of_i(self) -> usize {
+        match self {
+            Self::One => 0,
+            Self::I => 1,
+            Self::MinusOne => 2,
+            Self::MinusI => 3,
+        }
+    }
+
+    /// Returns `true` if `self` is `1` or `-1`.
+    pub fn is_real(self) -> bool {
+        self == Self::One || self == Self::MinusOne
+    }
+}
+
+impl Mul for Phase {
+    type Output = Self;
+    // Multiplying powers of i adds the exponents.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Self) -> Self::Output {
+        Self::from_power_of_i(self.power_of_i() + other.power_of_i())
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::One => write!(f, ""),
+            Self::I => write!(f, "i"),
+            Self::MinusOne => write!(f, "-"),
+            Self::MinusI => write!(f, "-i"),
+        }
+    }
+}
+
+/// A dense tensor product of Paulis with a global phase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PauliString {
+    paulis: Vec<Pauli>,
+    phase: Phase,
+}
+
+/// The reasons why a string can't be parsed as a `PauliString`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PauliStringParseError {
+    /// A character is not one of `I`, `X`, `Y` or `Z`.
+    InvalidPauli(char),
+    /// A sparse term doesn't have a valid qubit index.
+    InvalidQubit(String),
+    /// A qubit appears in many sparse terms.
+    RepeatedQubit(usize),
+    /// A qubit index is not smaller than the number of qubits.
+    QubitOutOfBound(usize),
+}
+
+impl fmt::Display for PauliStringParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPauli(character) => write!(f, "invalid pauli {}", character),
+            Self::InvalidQubit(term) => write!(f, "invalid qubit in {}", term),
+            Self::RepeatedQubit(qubit) => write!(f, "qubit {} is repeated", qubit),
+            Self::QubitOutOfBound(qubit) => write!(f, "qubit {} is out of bound", qubit),
+        }
+    }
+}
+
+impl std::error::Error for PauliStringParseError {}
+
+impl PauliString {
+    // ***** Construction *****
+
+    /// Creates the identity over `n_qubits`.
+    pub fn identity(n_qubits: usize) -> Self {
+        Self::from_paulis(vec![Pauli::I; n_qubits])
+    }
+
+    /// Creates a `PauliString` with phase 1 from the Pauli acting on each qubit.
+    pub fn from_paulis(paulis: Vec<Pauli>) -> Self {
+        Self {
+            paulis,
+            phase: Phase::One,
+        }
+    }
+
+    /// Creates a `PauliString` with phase 1 over `n_qubits` from the non-identity Paulis and their
+    /// positions.
+    ///
+    /// # Panic
+    ///
+    /// Panics if a position is out of bound.
+    pub fn from_sparse_paulis(paulis: &[(Pauli, usize)], n_qubits: usize) -> Self {
+        let mut pauli_string = Self::identity(n_qubits);
+        paulis
+            .iter()
+            .for_each(|(pauli, qubit)| pauli_string.paulis[*qubit] = *pauli);
+        pauli_string
+    }
+
+    /// Creates a `PauliString` with phase 1 from its symplectic representation `(x|z)`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `symplectic` has an odd length.
+    pub fn from_symplectic(symplectic: &[GF2]) -> Self {
+        if symplectic.len() % 2 == 1 {
+            panic!("symplectic vectors must have an even length");
+        }
+        let n_qubits = symplectic.len() / 2;
+        let paulis = (0..n_qubits)
+            .map(
+                |qubit| match (symplectic[qubit], symplectic[qubit + n_qubits]) {
+                    (GF2::B0, GF2::B0) => Pauli::I,
+                    (GF2::B1, GF2::B0) => Pauli::X,
+                    (GF2::B1, GF2::B1) => Pauli::Y,
+                    (GF2::B0, GF2::B1) => Pauli::Z,
+                },
+            )
+            .collect();
+        Self::from_paulis(paulis)
+    }
+
+    /// Parses a sparse string like `"X0 Z3"` over `n_qubits`.
+    ///
+    /// The terms are separated by whitespaces and the string can start with a phase `+`, `-`,
+    /// `i`, `+i` or `-i`.
+    pub fn from_sparse_str(string: &str, n_qubits: usize) -> Result<Self, PauliStringParseError> {
+        let (phase, string) = split_phase(string.trim());
+        let mut pauli_string = Self::identity(n_qubits).with_phase(phase);
+        for term in string.split_whitespace() {
+            let mut characters = term.chars();
+            let pauli = parse_pauli(characters.next().unwrap())?;
+            let qubit: usize = characters
+                .as_str()
+                .parse()
+                .map_err(|_| PauliStringParseError::InvalidQubit(term.to_string()))?;
+            if qubit >= n_qubits {
+                return Err(PauliStringParseError::QubitOutOfBound(qubit));
+            }
+            if pauli_string.paulis[qubit] != Pauli::I {
+                return Err(PauliStringParseError::RepeatedQubit(qubit));
+            }
+            pauli_string.paulis[qubit] = pauli;
+        }
+        Ok(pauli_string)
+    }
+
+    /// Returns `self` with the given `phase`.
+    pub fn with_phase(mut self, phase: Phase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Returns a copy of `self` acting trivially on the qubits from `self.get_n_qubits()` to
+    /// `n_qubits`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_qubits` is smaller than the number of qubits of `self`.
+    pub fn extended_to(&self, n_qubits: usize) -> Self {
+        if n_qubits < self.get_n_qubits() {
+            panic!("can't extend to fewer qubits");
+        }
+        let mut paulis = self.paulis.clone();
+        paulis.resize(n_qubits, Pauli::I);
+        Self {
+            paulis,
+            phase: self.phase,
+        }
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of qubits of `self`.
+    pub fn get_n_qubits(&self) -> usize {
+        self.paulis.len()
+    }
+
+    /// Returns the global phase of `self`.
+    pub fn get_phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Returns the Pauli acting on `qubit` or `None` if `qubit` is out of bound.
+    pub fn get_pauli(&self, qubit: usize) -> Option<Pauli> {
+        self.paulis.get(qubit).cloned()
+    }
+
+    /// Returns the Pauli acting on each qubit.
+    pub fn get_paulis(&self) -> &[Pauli] {
+        &self.paulis
+    }
+
+    /// Returns the number of qubits where `self` is not the identity.
+    pub fn weight(&self) -> usize {
+        self.paulis
+            .iter()
+            .filter(|pauli| **pauli != Pauli::I)
+            .count()
+    }
+
+    /// Returns the qubits where `self` is not the identity in increasing order.
+    pub fn support(&self) -> Vec<usize> {
+        self.paulis
+            .iter()
+            .enumerate()
+            .filter(|(_, pauli)| **pauli != Pauli::I)
+            .map(|(qubit, _)| qubit)
+            .collect()
+    }
+
+    /// Returns `true` if `self` is the identity up to the phase.
+    pub fn is_identity(&self) -> bool {
+        self.weight() == 0
+    }
+
+    /// Returns `+1` if `self` and `other` are commuting and `-1` otherwise.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `self` and `other` have different numbers of qubits.
+    pub fn commutator_with(&self, other: &Self) -> i32 {
+        self.assert_same_n_qubits(other);
+        self.paulis
+            .iter()
+            .zip(other.paulis.iter())
+            .map(|(a, b)| a.commutator_with(*b))
+            .product()
+    }
+
+    /// Returns `true` if `self` and `other` are commuting.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `self` and `other` have different numbers of qubits.
+    pub fn commutes_with(&self, other: &Self) -> bool {
+        self.commutator_with(other) == 1
+    }
+
+    /// Returns the symplectic representation `(x|z)` of `self` ignoring the phase.
+    pub fn to_symplectic(&self) -> Vec<GF2> {
+        let as_gf2 = |bit| if bit == 1 { GF2::B1 } else { GF2::B0 };
+        let x_part = self.paulis.iter().map(|pauli| as_gf2(pauli.as_gf4().0));
+        let z_part = self.paulis.iter().map(|pauli| as_gf2(pauli.as_gf4().1));
+        x_part.chain(z_part).collect()
+    }
+
+    /// Returns the sparse string representation of `self` like `"-X0 Z3"`.
+    ///
+    /// The identity is represented by the phase only.
+    pub fn to_sparse_string(&self) -> String {
+        let terms: Vec<String> = self
+            .paulis
+            .iter()
+            .enumerate()
+            .filter(|(_, pauli)| **pauli != Pauli::I)
+            .map(|(qubit, pauli)| format!("{}{}", pauli_as_char(*pauli), qubit))
+            .collect();
+        format!("{}{}", self.phase, terms.join(" "))
+    }
+
+    fn assert_same_n_qubits(&self, other: &Self) {
+        if self.get_n_qubits() != other.get_n_qubits() {
+            panic!("pauli strings have different numbers of qubits");
+        }
+    }
+}
+
+/// Returns the product of two `PauliString` including the phase.
+///
+/// # Panic
+///
+/// Panics if the operands have different numbers of qubits.
+///
+/// # Example
+///
+/// ```
+/// # use believer::*;
+/// let x: PauliString = "XI".parse().unwrap();
+/// let y: PauliString = "YI".parse().unwrap();
+///
+/// assert_eq!(&x * &y, "iZI".parse().unwrap());
+/// assert_eq!(&y * &x, "-iZI".parse().unwrap());
+/// ```
+impl Mul for &PauliString {
+    type Output = PauliString;
+    fn mul(self, other: Self) -> Self::Output {
+        self.assert_same_n_qubits(other);
+        let mut phase = self.phase * other.phase;
+        let paulis = self
+            .paulis
+            .iter()
+            .zip(other.paulis.iter())
+            .map(|(a, b)| {
+                phase = phase * get_product_phase(*a, *b);
+                *a * *b
+            })
+            .collect();
+        PauliString { paulis, phase }
+    }
+}
+
+impl Mul for PauliString {
+    type Output = PauliString;
+    fn mul(self, other: Self) -> Self::Output {
+        &self * &other
+    }
+}
+
+impl fmt::Display for PauliString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let paulis: String = self
+            .paulis
+            .iter()
+            .map(|pauli| pauli_as_char(*pauli))
+            .collect();
+        write!(f, "{}{}", self.phase, paulis)
+    }
+}
+
+/// Parses either a dense string like `"XZZXI"` or a sparse string like `"X0 Z3"`.
+///
+/// Both can start with a phase `+`, `-`, `i`, `+i` or `-i`. A sparse string is over the smallest
+/// number of qubits containing all terms.
+impl FromStr for PauliString {
+    type Err = PauliStringParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        let is_sparse = string
+            .chars()
+            .any(|c| c.is_ascii_digit() || c.is_whitespace());
+        if is_sparse {
+            let (_, terms) = split_phase(string);
+            let n_qubits = terms
+                .split_whitespace()
+                .map(|term| {
+                    term.get(1..)
+                        .and_then(|qubit| qubit.parse::<usize>().ok())
+                        .map(|qubit| qubit + 1)
+                        .ok_or_else(|| PauliStringParseError::InvalidQubit(term.to_string()))
+                })
+                .try_fold(0, |max, n_qubits| n_qubits.map(|n| usize::max(max, n)))?;
+            Self::from_sparse_str(string, n_qubits)
+        } else {
+            let (phase, paulis) = split_phase(string);
+            let paulis = paulis
+                .chars()
+                .map(parse_pauli)
+                .collect::<Result<Vec<Pauli>, _>>()?;
+            Ok(Self::from_paulis(paulis).with_phase(phase))
+        }
+    }
+}
+
+// Returns the phase at the start of `string` and the rest of `string`.
+fn split_phase(string: &str) -> (Phase, &str) {
+    for (prefix, phase) in &[
+        ("+i", Phase::I),
+        ("-i", Phase::MinusI),
+        ("i", Phase::I),
+        ("+", Phase::One),
+        ("-", Phase::MinusOne),
+    ] {
+        if let Some(rest) = string.strip_prefix(prefix) {
+            return (*phase, rest);
+        }
+    }
+    (Phase::One, string)
+}
+
+fn parse_pauli(character: char) -> Result<Pauli, PauliStringParseError> {
+    match character {
+        'I' => Ok(Pauli::I),
+        'X' => Ok(Pauli::X),
+        'Y' => Ok(Pauli::Y),
+        'Z' => Ok(Pauli::Z),
+        _ => Err(PauliStringParseError::InvalidPauli(character)),
+    }
+}
+
+fn pauli_as_char(pauli: Pauli) -> char {
+    match pauli {
+        Pauli::I => 'I',
+        Pauli::X => 'X',
+        Pauli::Y => 'Y',
+        Pauli::Z => 'Z',
+    }
+}
+
+// Returns the phase of the product of `a` and `b`. That is, `i` for XY, YZ and ZX, `-i` for YX,
+// ZY and XZ and `1` otherwise.
+fn get_product_phase(a: Pauli, b: Pauli) -> Phase {
+    match (a, b) {
+        (Pauli::X, Pauli::Y) | (Pauli::Y, Pauli::Z) | (Pauli::Z, Pauli::X) => Phase::I,
+        (Pauli::Y, Pauli::X) | (Pauli::Z, Pauli::Y) | (Pauli::X, Pauli::Z) => Phase::MinusI,
+        _ => Phase::One,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Pauli::{I, X, Y, Z};
+
+    fn parse(string: &str) -> PauliString {
+        string.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing_and_printing() {
+        let dense = parse("XZZXI");
+        assert_eq!(dense.get_paulis(), &[X, Z, Z, X, I]);
+        assert_eq!(dense.to_string(), "XZZXI");
+        assert_eq!(dense.to_sparse_string(), "X0 Z1 Z2 X3");
+
+        let sparse = parse("-i X0 Z3");
+        assert_eq!(sparse.get_phase(), Phase::MinusI);
+        assert_eq!(sparse.get_paulis(), &[X, I, I, Z]);
+        assert_eq!(sparse.to_string(), "-iXIIZ");
+        assert_eq!(parse(&sparse.to_sparse_string()), sparse);
+
+        assert_eq!(
+            PauliString::from_sparse_str("Y1", 3),
+            Ok(PauliString::from_paulis(vec![I, Y, I]))
+        );
+        assert_eq!(parse("+i").get_n_qubits(), 0);
+    }
+
+    #[test]
+    fn invalid_strings() {
+        assert_eq!(
+            "XAZ".parse::<PauliString>(),
+            Err(PauliStringParseError::InvalidPauli('A'))
+        );
+        assert_eq!(
+            "X0 Zb".parse::<PauliString>(),
+            Err(PauliStringParseError::InvalidQubit("Zb".to_string()))
+        );
+        assert_eq!(
+            "X0 Z0".parse::<PauliString>(),
+            Err(PauliStringParseError::RepeatedQubit(0))
+        );
+        assert_eq!(
+            PauliString::from_sparse_str("X0 Z3", 3),
+            Err(PauliStringParseError::QubitOutOfBound(3))
+        );
+    }
+
+    #[test]
+    fn products_track_phases() {
+        assert_eq!(parse("X") * parse("Y"), parse("iZ"));
+        assert_eq!(parse("Z") * parse("Y"), parse("-iX"));
+        assert_eq!(parse("XX") * parse("YY"), parse("-ZZ"));
+        assert_eq!(parse("-XZ") * parse("iXZ"), parse("-iII"));
+
+        // Paulis square to the identity.
+        let pauli = parse("iXYZI");
+        assert_eq!(&pauli * &pauli, parse("-IIII"));
+
+        // Anticommuting operators have opposite products.
+        let a = parse("XZZXI");
+        let b = parse("ZYXII").with_phase(Phase::MinusOne);
+        assert!(!a.commutes_with(&b));
+        assert_eq!(
+            (&a * &b).get_phase(),
+            (&b * &a).get_phase() * Phase::MinusOne
+        );
+    }
+
+    #[test]
+    fn commutation() {
+        let stabilizers: Vec<PauliString> = vec!["XZZXI", "IXZZX", "XIXZZ", "ZXIXZ"]
+            .into_iter()
+            .map(parse)
+            .collect();
+        for a in stabilizers.iter() {
+            for b in stabilizers.iter() {
+                assert_eq!(a.commutator_with(b), 1);
+            }
+        }
+        assert_eq!(parse("XI").commutator_with(&parse("ZZ")), -1);
+        assert!(parse("XX").commutes_with(&parse("ZZ")));
+    }
+
+    #[test]
+    fn weight_and_support() {
+        let pauli = parse("IXIYZ");
+        assert_eq!(pauli.weight(), 3);
+        assert_eq!(pauli.support(), vec![1, 3, 4]);
+        assert!(PauliString::identity(3).is_identity());
+        assert_eq!(
+            PauliString::from_sparse_paulis(&[(X, 1), (Z, 4)], 5),
+            parse("IXIIZ")
+        );
+    }
+
+    #[test]
+    fn symplectic_representation() {
+        let pauli = parse("-XYZI");
+        let symplectic = pauli.to_symplectic();
+        assert_eq!(
+            symplectic,
+            vec![
+                GF2::B1,
+                GF2::B1,
+                GF2::B0,
+                GF2::B0,
+                GF2::B0,
+                GF2::B1,
+                GF2::B1,
+                GF2::B0
+            ]
+        );
+        assert_eq!(PauliString::from_symplectic(&symplectic), parse("XYZI"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Codes for repeated rounds of noisy syndrome measurements.
+
+use crate::{GF4Stabilizers, ParityCheckMatrix, GF2};
+
+/// The spacetime code of a check matrix measured during many rounds.
+///
+/// During each round, new errors occur on the bits and the checks are measured with noisy
+/// outcomes. The measurements of the last round are perfect which models the final measurement
+/// of the data qubits. The detectors of a round are the differences between the measurements of
+/// that round and of the previous round.
+///
+/// The bits of the spacetime code are the errors of each round followed by the measurement
+/// errors of each round except the last one. Thus, the spacetime check matrix is a diagonal
+/// concatenation of copies of the check matrix, horizontally concatenated with the time-like
+/// edges linking the same check in consecutive rounds.
+///
+/// An error is a logical failure if the accumulated error over all rounds anticommutes with
+/// some logical operators.
+///
+/// # Example
+///
+/// ```
+/// use believer::{GF4Stabilizers, SpacetimeCode};
+///
+/// // X errors of the Steane code during 3 rounds.
+/// let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 3);
+///
+/// assert_eq!(code.get_n_data_bits(), 21);
+/// assert_eq!(code.get_n_measurement_bits(), 6);
+/// assert_eq!(code.get_checks().get_n_checks(), 9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpacetimeCode {
+    round_checks: ParityCheckMatrix,
+    round_logicals: ParityCheckMatrix,
+    n_rounds: usize,
+    checks: ParityCheckMatrix,
+    logicals: ParityCheckMatrix,
+}
+
+impl SpacetimeCode {
+    // ***** Construction *****
+
+    /// Creates the spacetime code of `round_checks` measured during `n_rounds` where the
+    /// failures are detected by `round_logicals`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_rounds` is 0 or if the matrices have different numbers of bits.
+    pub fn from_checks_and_logicals(
+        round_checks: ParityCheckMatrix,
+        round_logicals: ParityCheckMatrix,
+        n_rounds: usize,
+    ) -> Self {
+        if n_rounds == 0 {
+            panic!("there must be at least one round");
+        }
+        if round_checks.get_n_bits() != round_logicals.get_n_bits() {
+            panic!("checks and logicals have different numbers of bits");
+        }
+        let checks = get_spacetime_checks(&round_checks, n_rounds);
+        let logicals =
+            get_spacetime_logicals(&round_logicals, round_checks.get_n_checks(), n_rounds);
+        Self {
+            round_checks,
+            round_logicals,
+            n_rounds,
+            checks,
+            logicals,
+        }
+    }
+
+    /// Creates the spacetime code for the X errors of `code` during `n_rounds`.
+    ///
+    /// The checks are the non-empty Z parts of the stabilizers and the logicals are the Z
+    /// logical operators. This is meant for CSS codes.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_rounds` is 0.
+    pub fn for_x_errors(code: &GF4Stabilizers, n_rounds: usize) -> Self {
+        Self::from_checks_and_logicals(
+            code.z_checks().without_empty_checks(),
+            ParityCheckMatrix::with_n_bits(code.n_qubits()).with_checks(code.get_z_logicals()),
+            n_rounds,
+        )
+    }
+
+    /// Creates the spacetime code for the Z errors of `code` during `n_rounds`.
+    ///
+    /// The checks are the non-empty X parts of the stabilizers and the logicals are the X
+    /// logical operators. This is meant for CSS codes.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `n_rounds` is 0.
+    pub fn for_z_errors(code: &GF4Stabilizers, n_rounds: usize) -> Self {
+        Self::from_checks_and_logicals(
+            code.x_checks().without_empty_checks(),
+            ParityCheckMatrix::with_n_bits(code.n_qubits()).with_checks(code.get_x_logicals()),
+            n_rounds,
+        )
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of rounds.
+    pub fn get_n_rounds(&self) -> usize {
+        self.n_rounds
+    }
+
+    /// Returns the check matrix measured during each round.
+    pub fn get_round_checks(&self) -> &ParityCheckMatrix {
+        &self.round_checks
+    }
+
+    /// Returns the logical operators of a single round.
+    pub fn get_round_logicals(&self) -> &ParityCheckMatrix {
+        &self.round_logicals
+    }
+
+    /// Returns the spacetime check matrix. There is a check for each detector.
+    pub fn get_checks(&self) -> &ParityCheckMatrix {
+        &self.checks
+    }
+
+    /// Returns the logical operators applied on the errors of all rounds.
+    pub fn get_logicals(&self) -> &ParityCheckMatrix {
+        &self.logicals
+    }
+
+    /// Returns the number of spacetime bits.
+    pub fn get_n_bits(&self) -> usize {
+        self.checks.get_n_bits()
+    }
+
+    /// Returns the number of bits representing data errors. These are the first bits.
+    pub fn get_n_data_bits(&self) -> usize {
+        self.round_checks.get_n_bits() * self.n_rounds
+    }
+
+    /// Returns the number of bits representing measurement errors. These are the last bits.
+    pub fn get_n_measurement_bits(&self) -> usize {
+        self.round_checks.get_n_checks() * (self.n_rounds - 1)
+    }
+
+    /// Returns the spacetime bit of the error on `bit` during `round`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `bit` or `round` is out of bound.
+    pub fn get_data_bit(&self, bit: usize, round: usize) -> usize {
+        if bit >= self.round_checks.get_n_bits() || round >= self.n_rounds {
+            panic!("data bit out of bound");
+        }
+        round * self.round_checks.get_n_bits() + bit
+    }
+
+    /// Returns the spacetime bit of the measurement error of `check` during `round`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `check` or `round` is out of bound. The last round has no measurement error.
+    pub fn get_measurement_bit(&self, check: usize, round: usize) -> usize {
+        if check >= self.round_checks.get_n_checks() || round + 1 >= self.n_rounds {
+            panic!("measurement bit out of bound");
+        }
+        self.get_n_data_bits() + round * self.round_checks.get_n_checks() + check
+    }
+
+    // ***** Errors *****
+
+    /// Returns the accumulated error over all rounds of the given spacetime `error`.
+    pub fn get_accumulated_error(&self, error: &[GF2]) -> Vec<GF2> {
+        let n_bits = self.round_checks.get_n_bits();
+        let mut accumulated = vec![GF2::B0; n_bits];
+        error[..self.get_n_data_bits()]
+            .chunks(n_bits)
+            .for_each(|round_error| {
+                accumulated
+                    .iter_mut()
+                    .zip(round_error.iter())
+                    .for_each(|(total, bit)| *total = *total + *bit)
+            });
+        accumulated
+    }
+
+    /// Checks if the spacetime `error` flips some logical operators.
+    pub fn is_logical_error(&self, error: &[GF2]) -> bool {
+        self.logicals.get_syndrome_of(error).contains(&GF2::B1)
+    }
+}
+
+fn get_spacetime_checks(round_checks: &ParityCheckMatrix, n_rounds: usize) -> ParityCheckMatrix {
+    let data_checks = (1..n_rounds).fold(round_checks.clone(), |checks, _| {
+        checks.get_diagonal_concat_with(round_checks)
+    });
+
+    // A measurement error flips the detectors of its round and of the next round.
+    let n_checks = round_checks.get_n_checks();
+    let time_checks = (0..n_rounds)
+        .flat_map(|round| {
+            (0..n_checks).map(move |check| {
+                let mut bits = Vec::with_capacity(2);
+                if round > 0 {
+                    bits.push((round - 1) * n_checks + check);
+                }
+                if round + 1 < n_rounds {
+                    bits.push(round * n_checks + check);
+                }
+                bits
+            })
+        })
+        .collect();
+    let time_checks =
+        ParityCheckMatrix::with_n_bits(n_checks * (n_rounds - 1)).with_checks(time_checks);
+
+    data_checks.get_horizontal_concat_with(&time_checks)
+}
+
+fn get_spacetime_logicals(
+    round_logicals: &ParityCheckMatrix,
+    n_checks: usize,
+    n_rounds: usize,
+) -> ParityCheckMatrix {
+    (1..n_rounds)
+        .fold(round_logicals.clone(), |logicals, _| {
+            logicals.get_horizontal_concat_with(round_logicals)
+        })
+        .get_horizontal_concat_with(&ParityCheckMatrix::with_n_bits(n_checks * (n_rounds - 1)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spacetime_checks_of_repetition_code() {
+        let code = SpacetimeCode::from_checks_and_logicals(
+            ParityCheckMatrix::repetition_code(3),
+            ParityCheckMatrix::with_n_bits(3).with_checks(vec![vec![0]]),
+            3,
+        );
+        let expected = ParityCheckMatrix::with_n_bits(13).with_checks(vec![
+            vec![0, 1, 9],
+            vec![1, 2, 10],
+            vec![3, 4, 9, 11],
+            vec![4, 5, 10, 12],
+            vec![6, 7, 11],
+            vec![7, 8, 12],
+        ]);
+        assert_eq!(code.get_checks(), &expected);
+        assert_eq!(
+            code.get_logicals(),
+            &ParityCheckMatrix::with_n_bits(13).with_checks(vec![vec![0, 3, 6]])
+        );
+    }
+
+    #[test]
+    fn measurement_errors_in_two_rounds_are_undetected_but_harmless() {
+        let code = SpacetimeCode::for_x_errors(&GF4Stabilizers::steane_code(), 4);
+        let mut error = vec![GF2::B0; code.get_n_bits()];
+
+        // An error on qubit 0 at round 1 hidden by measurement errors and canceled at round 2.
+        error[code.get_data_bit(0, 1)] = GF2::B1;
+        error[code.get_data_bit(0, 2)] = GF2::B1;
+        for check in code
+            .get_round_checks()
+            .get_transposed_matrix()
+            .get_check(0)
+            .unwrap()
+            .iter()
+        {
+            error[code.get_measurement_bit(*check, 1)] = GF2::B1;
+        }
+        assert!(code
+            .get_checks()
+            .get_syndrome_of(&error)
+            .iter()
+            .all(|value| *value == GF2::B0));
+        assert!(!code.is_logical_error(&error));
+        assert_eq!(code.get_accumulated_error(&error), vec![GF2::B0; 7]);
+    }
+}
@@ -0,0 +1,528 @@
+//! A stabilizer tableau simulator for Clifford circuits.
+//!
+//! This is the CHP algorithm of Aaronson and Gottesman. The tableau contains `n` destabilizers,
+//! `n` stabilizers and a scratch row. Each row is a Pauli in the symplectic representation used by
+//! `GF4Stabilizers` together with a sign.
+//!
+//! # Example
+//!
+//! ```
+//! use believer::{StabilizerTableau, GF2};
+//! use rand::SeedableRng;
+//! use rand_chacha::ChaCha8Rng;
+//!
+//! let mut rng = ChaCha8Rng::seed_from_u64(123);
+//!
+//! // Prepare a Bell state.
+//! let mut tableau = StabilizerTableau::new(2);
+//! tableau.h(0);
+//! tableau.cnot(0, 1);
+//!
+//! let first = tableau.measure_z_with_rng(0, &mut rng);
+//! let second = tableau.measure_z_with_rng(1, &mut rng);
+//! assert!(first.is_random());
+//! assert!(!second.is_random());
+//! assert_eq!(first.get_outcome(), second.get_outcome());
+//! ```
+
+use crate::{GF4Stabilizers, Pauli, PauliString, Phase, GF2};
+use rand::{thread_rng, Rng};
+
+/// The tableau of a stabilizer state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StabilizerTableau {
+    n_qubits: usize,
+    x: Vec<Vec<bool>>,
+    z: Vec<Vec<bool>>,
+    signs: Vec<bool>,
+}
+
+/// The outcome of a measurement.
+///
+/// The outcome is `GF2::B0` for the `+1` eigenvalue and `GF2::B1` for the `-1` eigenvalue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    outcome: GF2,
+    is_random: bool,
+}
+
+impl Measurement {
+    /// Returns the measured eigenvalue as a bit.
+    pub fn get_outcome(&self) -> GF2 {
+        self.outcome
+    }
+
+    /// Returns `true` if the outcome was picked uniformly at random and `false` if it was
+    /// determined by the state.
+    pub fn is_random(&self) -> bool {
+        self.is_random
+    }
+}
+
+impl StabilizerTableau {
+    // ***** Construction *****
+
+    /// Creates the tableau of the all zeros state over `n_qubits`.
+    pub fn new(n_qubits: usize) -> Self {
+        let mut x = vec![vec![false; n_qubits]; 2 * n_qubits + 1];
+        let mut z = vec![vec![false; n_qubits]; 2 * n_qubits + 1];
+        for qubit in 0..n_qubits {
+            x[qubit][qubit] = true;
+            z[qubit + n_qubits][qubit] = true;
+        }
+        Self {
+            n_qubits,
+            x,
+            z,
+            signs: vec![false; 2 * n_qubits + 1],
+        }
+    }
+
+    /// Prepares the code state of `code` where all stabilizers have eigenvalue `+1`.
+    ///
+    /// The state starts in the all zeros state and each stabilizer is measured with the `+1`
+    /// outcome. This is the same as correcting a `-1` outcome with a Pauli that anticommutes with
+    /// that stabilizer and commutes with the previously measured ones. The logical qubits are left
+    /// in some arbitrary state.
+    ///
+    /// Stabilizers that are products of previous ones keep the sign given by the product.
+    pub fn prepare_code_state(code: &GF4Stabilizers) -> Self {
+        let mut tableau = Self::new(code.n_qubits());
+        let mut is_code_row = vec![false; code.n_qubits()];
+        for index in 0..code.n_stabilizers() {
+            let stabilizer = code.get_stabilizer(index).unwrap();
+            if tableau
+                .first_anticommuting_stabilizer(&stabilizer)
+                .is_none()
+            {
+                // The stabilizer is a product of the current stabilizers. If one of them is not a
+                // code stabilizer, it is first replaced by its destabilizer.
+                let (_, rows) = tableau.get_deterministic_outcome(&stabilizer);
+                match rows.into_iter().find(|row| !is_code_row[*row]) {
+                    Some(row) => {
+                        let destabilizer = tableau.get_row(row).with_phase(Phase::One);
+                        tableau.measure_pauli_at_row(&destabilizer, row + code.n_qubits(), GF2::B0);
+                    }
+                    None => continue,
+                }
+            }
+            let row = tableau.first_anticommuting_stabilizer(&stabilizer).unwrap();
+            tableau.measure_pauli_at_row(&stabilizer, row, GF2::B0);
+            is_code_row[row - code.n_qubits()] = true;
+        }
+        tableau
+    }
+
+    // ***** Getters *****
+
+    /// Returns the number of qubits of `self`.
+    pub fn get_n_qubits(&self) -> usize {
+        self.n_qubits
+    }
+
+    /// Returns the stabilizers of the state with phase `1` or `-1`.
+    pub fn get_stabilizers(&self) -> Vec<PauliString> {
+        (self.n_qubits..2 * self.n_qubits)
+            .map(|row| self.get_row(row))
+            .collect()
+    }
+
+    /// Returns the destabilizers of the state with phase `1` or `-1`.
+    pub fn get_destabilizers(&self) -> Vec<PauliString> {
+        (0..self.n_qubits).map(|row| self.get_row(row)).collect()
+    }
+
+    // ***** Gates *****
+
+    /// Applies the Hadamard gate on `qubit`.
+    pub fn h(&mut self, qubit: usize) {
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.x[row][qubit] && self.z[row][qubit];
+            std::mem::swap(&mut self.x[row][qubit], &mut self.z[row][qubit]);
+        }
+    }
+
+    /// Applies the phase gate on `qubit`.
+    pub fn s(&mut self, qubit: usize) {
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.x[row][qubit] && self.z[row][qubit];
+            self.z[row][qubit] ^= self.x[row][qubit];
+        }
+    }
+
+    /// Applies the controlled not gate from `control` to `target`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `control` and `target` are the same qubit.
+    pub fn cnot(&mut self, control: usize, target: usize) {
+        if control == target {
+            panic!("control and target must be different qubits");
+        }
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.x[row][control]
+                && self.z[row][target]
+                && (self.x[row][target] == self.z[row][control]);
+            self.x[row][target] ^= self.x[row][control];
+            self.z[row][control] ^= self.z[row][target];
+        }
+    }
+
+    /// Applies the controlled Z gate on `first` and `second`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `first` and `second` are the same qubit.
+    pub fn cz(&mut self, first: usize, second: usize) {
+        self.h(second);
+        self.cnot(first, second);
+        self.h(second);
+    }
+
+    /// Applies the Pauli X gate on `qubit`.
+    pub fn x(&mut self, qubit: usize) {
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.z[row][qubit];
+        }
+    }
+
+    /// Applies the Pauli Y gate on `qubit`.
+    pub fn y(&mut self, qubit: usize) {
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.x[row][qubit] ^ self.z[row][qubit];
+        }
+    }
+
+    /// Applies the Pauli Z gate on `qubit`.
+    pub fn z(&mut self, qubit: usize) {
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= self.x[row][qubit];
+        }
+    }
+
+    /// Applies all the Paulis of `pauli` ignoring the global phase.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `pauli` is not over the same number of qubits as `self`.
+    pub fn apply_pauli(&mut self, pauli: &PauliString) {
+        self.assert_same_n_qubits(pauli);
+        for row in 0..2 * self.n_qubits {
+            self.signs[row] ^= !self.commutes_with_row(pauli, row);
+        }
+    }
+
+    // ***** Measurements *****
+
+    /// Measures `qubit` in the Z basis with random number generator `rng`.
+    pub fn measure_z_with_rng<R: Rng>(&mut self, qubit: usize, rng: &mut R) -> Measurement {
+        let pauli = PauliString::from_sparse_paulis(&[(Pauli::Z, qubit)], self.n_qubits);
+        self.measure_pauli_with_rng(&pauli, rng)
+    }
+
+    /// Measures `qubit` in the Z basis using the thread random number generator.
+    pub fn measure_z(&mut self, qubit: usize) -> Measurement {
+        self.measure_z_with_rng(qubit, &mut thread_rng())
+    }
+
+    /// Measures `qubit` in the X basis with random number generator `rng`.
+    pub fn measure_x_with_rng<R: Rng>(&mut self, qubit: usize, rng: &mut R) -> Measurement {
+        let pauli = PauliString::from_sparse_paulis(&[(Pauli::X, qubit)], self.n_qubits);
+        self.measure_pauli_with_rng(&pauli, rng)
+    }
+
+    /// Measures `qubit` in the X basis using the thread random number generator.
+    pub fn measure_x(&mut self, qubit: usize) -> Measurement {
+        self.measure_x_with_rng(qubit, &mut thread_rng())
+    }
+
+    /// Measures the observable `pauli` with random number generator `rng`.
+    ///
+    /// The outcome is relative to the sign of `pauli`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `pauli` is not over the same number of qubits as `self` or if its phase is
+    /// imaginary.
+    pub fn measure_pauli_with_rng<R: Rng>(
+        &mut self,
+        pauli: &PauliString,
+        rng: &mut R,
+    ) -> Measurement {
+        self.assert_same_n_qubits(pauli);
+        if !pauli.get_phase().is_real() {
+            panic!("can't measure a pauli with an imaginary phase");
+        }
+        let sign = if pauli.get_phase() == Phase::MinusOne {
+            GF2::B1
+        } else {
+            GF2::B0
+        };
+        let unsigned = pauli.clone().with_phase(Phase::One);
+        match self.first_anticommuting_stabilizer(&unsigned) {
+            Some(row) => {
+                let outcome = if rng.gen() { GF2::B1 } else { GF2::B0 };
+                self.measure_pauli_at_row(&unsigned, row, outcome);
+                Measurement {
+                    outcome: outcome + sign,
+                    is_random: true,
+                }
+            }
+            None => Measurement {
+                outcome: self.get_deterministic_outcome(&unsigned).0 + sign,
+                is_random: false,
+            },
+        }
+    }
+
+    /// Measures the observable `pauli` using the thread random number generator.
+    pub fn measure_pauli(&mut self, pauli: &PauliString) -> Measurement {
+        self.measure_pauli_with_rng(pauli, &mut thread_rng())
+    }
+
+    // ***** Private methods *****
+
+    fn assert_same_n_qubits(&self, pauli: &PauliString) {
+        if pauli.get_n_qubits() != self.n_qubits {
+            panic!("the pauli and the tableau have different numbers of qubits");
+        }
+    }
+
+    fn get_row(&self, row: usize) -> PauliString {
+        let paulis = (0..self.n_qubits)
+            .map(|qubit| match (self.x[row][qubit], self.z[row][qubit]) {
+                (false, false) => Pauli::I,
+                (true, false) => Pauli::X,
+                (true, true) => Pauli::Y,
+                (false, true) => Pauli::Z,
+            })
+            .collect();
+        let phase = if self.signs[row] {
+            Phase::MinusOne
+        } else {
+            Phase::One
+        };
+        PauliString::from_paulis(paulis).with_phase(phase)
+    }
+
+    fn set_row(&mut self, row: usize, pauli: &PauliString, sign: bool) {
+        for (qubit, pauli) in pauli.get_paulis().iter().enumerate() {
+            let (x, z) = pauli.as_gf4();
+            self.x[row][qubit] = x == 1;
+            self.z[row][qubit] = z == 1;
+        }
+        self.signs[row] = sign;
+    }
+
+    fn copy_row(&mut self, from: usize, to: usize) {
+        self.x[to] = self.x[from].clone();
+        self.z[to] = self.z[from].clone();
+        self.signs[to] = self.signs[from];
+    }
+
+    fn commutes_with_row(&self, pauli: &PauliString, row: usize) -> bool {
+        let n_anticommuting_qubits = pauli
+            .get_paulis()
+            .iter()
+            .enumerate()
+            .filter(|(qubit, pauli)| {
+                let (x, z) = pauli.as_gf4();
+                ((x == 1) && self.z[row][*qubit]) ^ ((z == 1) && self.x[row][*qubit])
+            })
+            .count();
+        n_anticommuting_qubits % 2 == 0
+    }
+
+    fn first_anticommuting_stabilizer(&self, pauli: &PauliString) -> Option<usize> {
+        (self.n_qubits..2 * self.n_qubits).find(|row| !self.commutes_with_row(pauli, *row))
+    }
+
+    // Replaces the stabilizer at `row` which anticommutes with `pauli` by `pauli` with the given
+    // `outcome` as sign. The other rows are updated to commute with `pauli`.
+    fn measure_pauli_at_row(&mut self, pauli: &PauliString, row: usize, outcome: GF2) {
+        for other in 0..2 * self.n_qubits {
+            if other != row && !self.commutes_with_row(pauli, other) {
+                self.row_sum(other, row);
+            }
+        }
+        self.copy_row(row, row - self.n_qubits);
+        self.set_row(row, pauli, outcome == GF2::B1);
+    }
+
+    // Returns the sign of `pauli` in the stabilizer group and the indices of the stabilizers
+    // whose product is `pauli`. The indices are relative to the first stabilizer row.
+    fn get_deterministic_outcome(&mut self, pauli: &PauliString) -> (GF2, Vec<usize>) {
+        let scratch = 2 * self.n_qubits;
+        self.x[scratch] = vec![false; self.n_qubits];
+        self.z[scratch] = vec![false; self.n_qubits];
+        self.signs[scratch] = false;
+        let rows: Vec<usize> = (0..self.n_qubits)
+            .filter(|row| !self.commutes_with_row(pauli, *row))
+            .collect();
+        for row in rows.iter() {
+            self.row_sum(scratch, row + self.n_qubits);
+        }
+        let outcome = if self.signs[scratch] {
+            GF2::B1
+        } else {
+            GF2::B0
+        };
+        (outcome, rows)
+    }
+
+    // Multiplies the row `target` by the row `source` keeping track of the sign.
+    fn row_sum(&mut self, target: usize, source: usize) {
+        let mut phase: i64 = 2 * (self.signs[target] as i64) + 2 * (self.signs[source] as i64);
+        for qubit in 0..self.n_qubits {
+            phase += get_product_exponent(
+                self.x[source][qubit],
+                self.z[source][qubit],
+                self.x[target][qubit],
+                self.z[target][qubit],
+            );
+            self.x[target][qubit] ^= self.x[source][qubit];
+            self.z[target][qubit] ^= self.z[source][qubit];
+        }
+        self.signs[target] = phase.rem_euclid(4) == 2;
+    }
+}
+
+// Returns the exponent of i when multiplying the Pauli (x1, z1) by the Pauli (x2, z2).
+fn get_product_exponent(x1: bool, z1: bool, x2: bool, z2: bool) -> i64 {
+    let (x2, z2) = (x2 as i64, z2 as i64);
+    match (x1, z1) {
+        (false, false) => 0,
+        (true, true) => z2 - x2,
+        (true, false) => z2 * (2 * x2 - 1),
+        (false, true) => x2 * (1 - 2 * z2),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parse(string: &str) -> PauliString {
+        string.parse().unwrap()
+    }
+
+    #[test]
+    fn initial_state_is_all_zeros() {
+        let mut tableau = StabilizerTableau::new(3);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for qubit in 0..3 {
+            let measurement = tableau.measure_z_with_rng(qubit, &mut rng);
+            assert_eq!(measurement.get_outcome(), GF2::B0);
+            assert!(!measurement.is_random());
+        }
+        assert!(tableau.measure_x_with_rng(0, &mut rng).is_random());
+    }
+
+    #[test]
+    fn pauli_gates_flip_outcomes() {
+        let mut tableau = StabilizerTableau::new(2);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        tableau.x(0);
+        tableau.y(1);
+        assert_eq!(
+            tableau.measure_z_with_rng(0, &mut rng).get_outcome(),
+            GF2::B1
+        );
+        assert_eq!(
+            tableau.measure_z_with_rng(1, &mut rng).get_outcome(),
+            GF2::B1
+        );
+
+        tableau.h(0);
+        tableau.z(0);
+        assert_eq!(
+            tableau.measure_x_with_rng(0, &mut rng).get_outcome(),
+            GF2::B0
+        );
+    }
+
+    #[test]
+    fn two_phase_gates_are_a_z_gate() {
+        let mut tableau = StabilizerTableau::new(1);
+        tableau.h(0);
+        tableau.s(0);
+        tableau.s(0);
+        tableau.h(0);
+        assert_eq!(tableau.get_stabilizers(), vec![parse("-Z")]);
+    }
+
+    #[test]
+    fn phase_gate_maps_x_to_y() {
+        let mut tableau = StabilizerTableau::new(1);
+        tableau.h(0);
+        tableau.s(0);
+        assert_eq!(tableau.get_stabilizers(), vec![parse("Y")]);
+    }
+
+    #[test]
+    fn entangling_gates() {
+        let mut tableau = StabilizerTableau::new(2);
+        tableau.h(0);
+        tableau.cnot(0, 1);
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        assert_eq!(
+            tableau.measure_pauli_with_rng(&parse("XX"), &mut rng),
+            Measurement {
+                outcome: GF2::B0,
+                is_random: false
+            }
+        );
+        assert_eq!(
+            tableau
+                .measure_pauli_with_rng(&parse("-YY"), &mut rng)
+                .get_outcome(),
+            GF2::B0
+        );
+
+        let mut tableau = StabilizerTableau::new(2);
+        tableau.h(0);
+        tableau.h(1);
+        tableau.cz(0, 1);
+        let stabilizers = tableau.get_stabilizers();
+        assert!(stabilizers.contains(&parse("XZ")));
+        assert!(stabilizers.contains(&parse("ZX")));
+    }
+
+    #[test]
+    fn repeated_measurements_agree() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        for _ in 0..20 {
+            let mut tableau = StabilizerTableau::new(3);
+            tableau.h(0);
+            tableau.cnot(0, 1);
+            tableau.cnot(1, 2);
+            let first = tableau.measure_x_with_rng(1, &mut rng);
+            assert!(first.is_random());
+            let second = tableau.measure_x_with_rng(1, &mut rng);
+            assert!(!second.is_random());
+            assert_eq!(first.get_outcome(), second.get_outcome());
+        }
+    }
+
+    #[test]
+    fn code_states_are_stabilized() {
+        let codes = vec![
+            GF4Stabilizers::five_qubit_code(),
+            GF4Stabilizers::steane_code(),
+            GF4Stabilizers::shor_code(),
+            GF4Stabilizers::quantum_reed_muller_code(),
+        ];
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for code in codes {
+            let mut tableau = StabilizerTableau::prepare_code_state(&code);
+            for index in 0..code.n_stabilizers() {
+                let stabilizer = code.get_stabilizer(index).unwrap();
+                let measurement = tableau.measure_pauli_with_rng(&stabilizer, &mut rng);
+                assert!(!measurement.is_random());
+                assert_eq!(measurement.get_outcome(), GF2::B0);
+            }
+        }
+    }
+}